@@ -1,22 +1,137 @@
 use glam::Vec3;
 
+/// Shape of the simulation domain boundary.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoundaryShape {
+    /// Soft sphere of radius `boundary_radius` that pushes particles back inside.
+    Sphere,
+    /// Axis-aligned box `[-half_extents, half_extents]` whose faces wrap around:
+    /// particles leaving one side reappear on the opposite side. Components
+    /// below `MIN_PERIODIC_HALF_EXTENT` (or NaN) are raised to it.
+    Periodic { half_extents: Vec3 },
+}
+
+/// How the per-particle material values of a contact pair are combined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MaterialCombine {
+    /// Arithmetic mean of the two values.
+    Average,
+    /// The smaller value (the slipperier / less bouncy material wins).
+    Min,
+    /// Square root of the product (a zero on either side gives zero).
+    GeometricMean,
+}
+
+impl MaterialCombine {
+    /// Combine the material values `a` and `b` of the two particles in a contact.
+    pub fn combine(self, a: f32, b: f32) -> f32 {
+        match self {
+            MaterialCombine::Average => 0.5 * (a + b),
+            MaterialCombine::Min => a.min(b),
+            MaterialCombine::GeometricMean => (a * b).max(0.0).sqrt(),
+        }
+    }
+}
+
+/// Upper bound `Solver::set_solver_quality` clamps requested substeps to.
+pub const MAX_SUBSTEPS: u32 = 32;
+/// Upper bound `Solver::set_solver_quality` clamps requested iterations to.
+pub const MAX_SOLVER_ITERATIONS: u32 = 64;
+/// Smallest periodic half extent; zero would divide by zero in the wrap math.
+pub const MIN_PERIODIC_HALF_EXTENT: f32 = 0.01;
+
 pub struct PhysicsConfig {
     pub substeps: u32,
     pub solver_iterations: u32,
+    /// Acceleration applied to every particle when `artistic_forces_enabled` is off.
     pub gravity: Vec3,
+    /// Apply the artistic forces: curl flow, vortex, flow gravity and drag,
+    /// shape attraction, free-flight turbulence, audio and the per-step speed
+    /// damping. When off, particles feel only `gravity`, the pointer, the central
+    /// attractor, the boundary, external forces and N-body/EM, for pure physics
+    /// sandboxes.
+    pub artistic_forces_enabled: bool,
+    /// Point gravity from the origin toward the pointer position each step instead
+    /// of down ("tilting" the scene like a marble maze). Turns both the built-in
+    /// flow gravity and `gravity` (keeping its magnitude).
+    pub gravity_follows_pointer: bool,
+    /// Fraction of velocity removed per step from particles that were within the
+    /// pointer radius when the pointer deactivated (0 = off). Fades to nothing
+    /// over `pointer_release_duration`, so pulled particles settle without overshoot.
+    pub pointer_release_damping: f32,
+    /// Seconds over which the pointer release damping fades out.
+    pub pointer_release_duration: f32,
+    /// Strength of the built-in flow gravity (down, or toward the pointer).
+    pub flow_gravity: f32,
+    /// Couples simulation speed to loudness: each step runs at
+    /// `speed_multiplier * (1 + audio_speed_coupling * audio_energy)` (0 = off).
+    pub audio_speed_coupling: f32,
     pub global_damping: f32,
+    /// Per-step velocity damping of the flow phase when shapes are loose (calm_factor = 0).
+    pub linear_damping_min: f32,
+    /// Per-step velocity damping of the flow phase when shapes are calm (calm_factor = 1).
+    pub linear_damping_max: f32,
+    /// Quadratic drag coefficient (acceleration `-v * |v| * quadratic_drag`).
+    pub quadratic_drag: f32,
+    /// Apply the quadratic drag term. Turning it off keeps fast n-body and
+    /// attractor orbits from bleeding energy while the flow forces still act.
+    pub quadratic_drag_enabled: bool,
+    /// Per-phase drag multiplier (indexed by `Phase as usize`): scales quadratic drag
+    /// and raises the linear damping factor to this power (>1 = thicker, <1 = lighter).
+    pub phase_drag: [f32; 7],
     pub max_velocity: f32,
+    /// Max particle speed below which a step counts toward `Solver::settled_frames`.
+    pub settle_velocity_threshold: f32,
     pub boundary_radius: f32,
+    /// Boundary mode (spherical containment or periodic wrap).
+    pub boundary_shape: BoundaryShape,
     pub shape_strength: f32,
+    /// Distance to the shape target inside which the close-range pull toward it
+    /// ramps in (force-based shape attraction).
+    pub shape_close_distance: f32,
+    /// Distance to the shape target inside which particles get the final snap
+    /// pull and extra velocity damping.
+    pub shape_snap_distance: f32,
+    /// Strength of the wandering vortex in the flow field (0 = pure curl noise).
+    pub flow_vortex_strength: f32,
+    /// Amplitude of the travelling sine wave in the flow's z component (0 = none;
+    /// the z curl noise remains).
+    pub flow_z_wave_amplitude: f32,
+    /// Frequency multiplier of the flow z-wave in space and time (1 = default).
+    pub flow_z_wave_frequency: f32,
+    /// Per-phase mask (indexed by `Phase as usize`) of particles pulled toward the shape target.
+    pub shape_attract_phases: [bool; 7],
     /// Enable particle-particle collision constraints (opt-in).
     /// When false, the solver uses the original integration path.
     pub collisions_enabled: bool,
+    /// Steps over which velocities cross-fade after `collisions_enabled` switches the
+    /// integration path (0 = switch abruptly). Each blended step mixes the velocity
+    /// carried into the step with the new path's estimate, weighting the latter more.
+    pub path_blend_frames: u32,
     /// Rest density for fluid particles (rho_0, kg/m^3).
     pub fluid_rest_density: f32,
+    /// PBF density passes per solver iteration (1 = shared with contacts only).
+    /// Extra passes run before the contact solve, without adding contact iterations.
+    pub fluid_inner_iterations: u32,
+    /// Most neighbors (closest first, self included) each fluid particle sums in
+    /// density and pressure kernels (0 = no cap). Bounds cost under heavy compression.
+    pub max_fluid_neighbors: usize,
+    /// Give Free particles a density and PBF density constraint too, so spray
+    /// re-entering a fluid body blends in smoothly instead of popping.
+    pub free_contributes_to_density: bool,
     /// XSPH viscosity coefficient for fluid smoothing.
     pub fluid_viscosity: f32,
+    /// Cohesion between particles of the same fluid (same rest density).
+    pub fluid_surface_tension: f32,
+    /// Repulsion between particles of different fluids (immiscible interface).
+    pub fluid_interface_tension: f32,
     /// Vorticity confinement strength for fluid particles.
     pub fluid_vorticity: f32,
+    /// Maximum vorticity confinement force per particle (prevents blow-up).
+    pub fluid_vorticity_clamp: f32,
+    /// Normal restitution of fluid particles hitting the boundary sphere (0 = stick).
+    /// Rebounds also get a small random tangential spray.
+    pub fluid_restitution: f32,
     /// SPH smoothing kernel radius h.
     pub smoothing_radius: f32,
     /// Enable Macklin tensile instability correction.
@@ -25,28 +140,115 @@ pub struct PhysicsConfig {
     pub cloth_stiffness: f32,
     /// Compliance for cloth bending constraints (lower = stiffer).
     pub cloth_bending: f32,
+    /// Most contacts any particle takes part in per substep (0 = unlimited).
+    /// Dense piles keep only their deepest penetrations.
+    pub max_contacts_per_particle: usize,
+    /// Contact detection margin for speculative contacts (0 = off). Pairs within this
+    /// gap are swept over the substep, so fast particles can't pass through each other.
+    pub speculative_margin: f32,
+    /// Relaxation factor for contact corrections (1.0 = full resolution, <1 = softer stacks).
+    pub contact_relaxation: f32,
+    /// Most penetration depth a contact resolves per solver iteration (0 = no cap).
+    /// Small values give soft, gummy collisions that separate over several steps.
+    pub max_penetration_correction: f32,
+    /// Stop a substep's solver iterations early once the largest averaged position
+    /// correction of an iteration is below this length (0 = always run them all).
+    pub convergence_epsilon: f32,
+    /// Treat each rope bond (a distance constraint whose particles have at most two
+    /// bonds each) as a capsule and collide bond segments against each other, so
+    /// ropes and hair can't pass through one another. Bonded pairs then get no
+    /// particle contact.
+    pub capsule_contacts: bool,
     /// Coulomb friction coefficient for contact constraints.
     pub friction: f32,
+    /// Friction coefficient for contacts involving Granular particles (the larger
+    /// of this and `friction` applies). High values let sand hold a repose angle.
+    pub granular_friction: f32,
+    /// Tangential speed below which Granular contacts stick completely.
+    pub granular_static_velocity: f32,
     /// Coefficient of restitution (bounciness) for contacts.
     pub restitution: f32,
+    /// Per-phase restitution multiplier (indexed by `Phase as usize`). A contact
+    /// uses `restitution` times the smaller multiplier of its two particles.
+    pub phase_restitution: [f32; 7],
+    /// Reflect the approach velocity of particle contacts by the combined contact
+    /// restitution after each substep. Off by default, so contacts stay inelastic.
+    pub contact_restitution: bool,
+    /// How the per-particle `friction` and `restitution` material multipliers of
+    /// a contact pair are combined.
+    pub material_combine: MaterialCombine,
+    /// Derive each dynamic particle's mass from its radius as `density * 4/3 pi r^3`.
+    pub mass_from_radius: bool,
+    /// Density used by `mass_from_radius`.
+    pub mass_radius_density: f32,
     /// Shape matching stiffness for rigid bodies [0..1].
     pub shape_matching_stiffness: f32,
-    /// Spatial hash grid cell size (0 = auto-compute from particle radius).
+    /// Temperature above which Rigid/Static particles melt into Fluid.
+    pub melt_temperature: f32,
+    /// Temperature below which Fluid particles freeze.
+    pub freeze_temperature: f32,
+    /// Frozen particles become Static instead of joining a new rigid body.
+    pub freeze_to_static: bool,
+    /// Spatial hash grid cell size (0 = auto: twice the largest particle radius, at least 0.2).
     pub grid_cell_size: f32,
-    /// Spatial hash table size.
+    /// Rebuild the spatial grid on every XPBD substep (false = first substep of each
+    /// frame only; cheaper, but contacts of fast particles can be missed). With
+    /// `false`, a contact is only guaranteed to be found while the pair's combined
+    /// radius plus its travel since the first substep fits in one grid cell.
+    pub grid_rebuild_every_substep: bool,
+    /// Largest spatial hash table size. The grid is allocated on first use with
+    /// the next power of two at or above twice the particle count, up to this.
     pub grid_table_size: usize,
+    /// On the XPBD path, form shapes through the position-level shape target constraint
+    /// alone, dropping the spring force applied on top of it (the Euler path always
+    /// uses the force). Shapes form in a few steps without ringing.
+    pub shape_as_constraint: bool,
     /// XPBD compliance when shape_strength = 0 (very soft attraction).
     pub shape_compliance_at_zero: f32,
     /// XPBD compliance when shape_strength = 1 (near-rigid attraction).
     pub shape_compliance_at_one: f32,
-    /// Boundary stiffness (how hard the boundary pushes back).
+    /// Boundary stiffness (how hard the boundary pushes back), used as the inverse
+    /// compliance of the XPBD boundary constraint. `f32::INFINITY` (the default)
+    /// projects fully onto the wall; finite values leave part of the overlap.
     pub boundary_stiffness: f32,
+    /// Spring coefficient of the soft boundary return force in `apply_forces`.
+    pub boundary_return_stiffness: f32,
+    /// Hard-clamp positions to the boundary sphere after single-pass (Euler)
+    /// integration, reflecting the outward velocity by `restitution`. The
+    /// soft return force alone lets particles briefly overshoot.
+    pub euler_boundary_clamp: bool,
+    /// Largest distance a particle may move in one `step`; longer moves are
+    /// scaled back along with the velocity. Safety net against a bad substep
+    /// launching a particle across the scene. 0 disables the clamp.
+    pub max_particle_displacement: f32,
     /// Enable N-body gravitational interaction.
     pub nbody_enabled: bool,
     /// Gravitational constant for N-body.
     pub nbody_g: f32,
     /// Softening parameter to prevent singularity at r=0.
     pub nbody_softening: f32,
+    /// Grow softening at close range as `softening * (1 + k / r)` for N-body and EM forces.
+    pub adaptive_softening: bool,
+    /// `k` in the adaptive softening law (distance scale of the close-range boost).
+    pub adaptive_softening_k: f32,
+    /// Pull every particle toward a single fixed point (independent of N-body).
+    pub central_attractor_enabled: bool,
+    /// World-space position of the central attractor.
+    pub central_attractor_pos: Vec3,
+    /// Attractor mass; acceleration is `nbody_g * mass / (dist^2 + nbody_softening^2)`.
+    pub central_attractor_mass: f32,
+    /// Pull every particle toward the closest point of the segment
+    /// `beam_start..beam_end` (beam/laser effects).
+    pub beam_enabled: bool,
+    /// First endpoint of the beam segment.
+    pub beam_start: Vec3,
+    /// Second endpoint of the beam segment.
+    pub beam_end: Vec3,
+    /// Spring constant of the beam pull: acceleration `(closest - pos) * beam_strength`.
+    pub beam_strength: f32,
+    /// Radius around the pointer inside which its force fades smoothly to zero,
+    /// so a particle sitting on the cursor is not ejected. 0 disables the falloff.
+    pub pointer_min_distance: f32,
     /// Barnes-Hut opening angle. 0.0 = exact O(N^2), 0.7 = typical, higher = faster.
     pub nbody_theta: f32,
     /// Enable electromagnetic forces.
@@ -55,6 +257,20 @@ pub struct PhysicsConfig {
     pub em_coulomb_k: f32,
     /// External magnetic field vector for Lorentz force.
     pub em_magnetic_field: Vec3,
+    /// Full turns of the spiral ring laid out by `Solver::new` and `reinitialize`.
+    pub init_spiral_turns: f32,
+    /// Inner and outer radius of the initial spiral ring.
+    pub init_spiral_radius: [f32; 2],
+    /// Smallest particle radius assigned by `Solver::new`, `resize` and `reinitialize`.
+    pub radius_min: f32,
+    /// Largest particle radius assigned by `Solver::new`, `resize` and `reinitialize`.
+    pub radius_max: f32,
+    /// Fraction of the ambient flow (`Solver::sample_flow`) added to a spawned
+    /// particle's velocity, so it drifts with the flow from birth (0 = off).
+    pub spawn_flow_inheritance: f32,
+    /// Lifetime in seconds given to spawned particles, after which `step` kills
+    /// them and frees the slot for reuse (0 = immortal).
+    pub spawn_lifetime: f32,
 }
 
 impl Default for PhysicsConfig {
@@ -63,33 +279,97 @@ impl Default for PhysicsConfig {
             substeps: 4,
             solver_iterations: 3,
             gravity: Vec3::new(0.0, -9.81, 0.0),
+            artistic_forces_enabled: true,
+            gravity_follows_pointer: false,
+            pointer_release_damping: 0.0,
+            pointer_release_duration: 0.5,
+            flow_gravity: 0.04,
+            audio_speed_coupling: 0.0,
             global_damping: 0.99,
+            linear_damping_min: 0.93,
+            linear_damping_max: 0.965,
+            quadratic_drag: 0.018,
+            quadratic_drag_enabled: true,
+            phase_drag: [1.0; 7],
             max_velocity: 18.0,
+            settle_velocity_threshold: 0.05,
             boundary_radius: 4.5,
+            boundary_shape: BoundaryShape::Sphere,
             shape_strength: 0.85,
+            shape_close_distance: 0.5,
+            shape_snap_distance: 0.15,
+            flow_vortex_strength: 0.35,
+            flow_z_wave_amplitude: 0.35,
+            flow_z_wave_frequency: 1.0,
+            shape_attract_phases: [true; 7],
             collisions_enabled: false,
+            path_blend_frames: 0,
             fluid_rest_density: 1000.0,
+            fluid_inner_iterations: 1,
+            max_fluid_neighbors: 0,
+            free_contributes_to_density: false,
             fluid_viscosity: 0.01,
+            fluid_surface_tension: 0.0,
+            fluid_interface_tension: 0.0,
             fluid_vorticity: 0.1,
+            fluid_vorticity_clamp: 50.0,
+            fluid_restitution: 0.0,
             smoothing_radius: 0.1,
             tensile_correction: true,
             cloth_stiffness: 0.001,
             cloth_bending: 0.01,
+            max_contacts_per_particle: 32,
+            speculative_margin: 0.0,
+            contact_relaxation: 1.0,
+            max_penetration_correction: 0.0,
+            convergence_epsilon: 0.0,
+            capsule_contacts: false,
             friction: 0.3,
+            granular_friction: 0.9,
+            granular_static_velocity: 0.05,
             restitution: 0.2,
+            phase_restitution: [1.0; 7],
+            contact_restitution: false,
+            material_combine: MaterialCombine::Average,
+            mass_from_radius: false,
+            mass_radius_density: 1000.0,
             shape_matching_stiffness: 0.9,
+            melt_temperature: 1.0,
+            freeze_temperature: -1.0,
+            freeze_to_static: false,
             grid_cell_size: 0.0,
+            grid_rebuild_every_substep: true,
             grid_table_size: 131072,
+            shape_as_constraint: false,
             shape_compliance_at_zero: 100.0,
             shape_compliance_at_one: 0.0001,
-            boundary_stiffness: 100.0,
+            boundary_stiffness: f32::INFINITY,
+            boundary_return_stiffness: 0.6,
+            euler_boundary_clamp: false,
+            max_particle_displacement: 0.0,
             nbody_enabled: false,
             nbody_g: 0.001,
             nbody_softening: 0.01,
+            adaptive_softening: false,
+            adaptive_softening_k: 0.05,
+            central_attractor_enabled: false,
+            central_attractor_pos: Vec3::ZERO,
+            central_attractor_mass: 1000.0,
+            beam_enabled: false,
+            beam_start: Vec3::new(-1.0, 0.0, 0.0),
+            beam_end: Vec3::new(1.0, 0.0, 0.0),
+            beam_strength: 4.0,
+            pointer_min_distance: 0.05,
             nbody_theta: 0.7,
             em_enabled: false,
             em_coulomb_k: 1.0,
             em_magnetic_field: Vec3::ZERO,
+            init_spiral_turns: 20.0,
+            init_spiral_radius: [0.5, 2.0],
+            radius_min: 0.05,
+            radius_max: 0.1,
+            spawn_flow_inheritance: 0.0,
+            spawn_lifetime: 0.0,
         }
     }
 }
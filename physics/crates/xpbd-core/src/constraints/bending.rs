@@ -18,6 +18,7 @@ use crate::particle::ParticleSet;
 /// The constraint measures the dihedral angle between the two triangle normals
 /// and applies corrections to maintain it at a rest angle. Higher compliance
 /// values produce softer bending; zero compliance produces a rigid hinge.
+#[derive(Clone)]
 pub struct BendingConstraint {
     /// Shared edge vertex A.
     pub i: u32,
@@ -35,6 +36,9 @@ pub struct BendingConstraint {
     pub lambda: f32,
 }
 
+/// Words per constraint in `Solver::pack_bending_constraints`.
+pub const PACKED_BENDING_STRIDE: usize = 6;
+
 impl BendingConstraint {
     /// Create a new bending constraint.
     ///
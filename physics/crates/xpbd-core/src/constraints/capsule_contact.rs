@@ -0,0 +1,230 @@
+use glam::Vec3;
+
+use crate::constraints::distance::DistanceConstraint;
+use crate::grid::SpatialHashGrid;
+
+/// A detected contact between two capsules (bonded particle pairs).
+///
+/// Each capsule is the segment between two particles, swept by their radii.
+/// The contact acts at the closest points of the two segments, at parameters
+/// `s` along `a` and `t` along `b`.
+#[derive(Clone, Debug)]
+pub struct CapsuleContact {
+    pub a: [u32; 2],      // segment A endpoints
+    pub b: [u32; 2],      // segment B endpoints
+    pub s: f32,           // closest point on A = lerp(a[0], a[1], s)
+    pub t: f32,           // closest point on B = lerp(b[0], b[1], t)
+    pub normal: Vec3,     // contact normal (A->B, normalized)
+    pub penetration: f32, // overlap depth (positive = overlapping)
+}
+
+/// Capsule segments for the rope bonds among `constraints`.
+///
+/// A rope bond joins two particles that each have at most two bonds, so only
+/// chains (ropes and hair) become capsules. Cloth vertices carry shear and
+/// structural bonds to three or more neighbors and are left to particle contacts.
+pub fn capsule_segments(constraints: &[DistanceConstraint]) -> Vec<[u32; 2]> {
+    let mut degree: Vec<u32> = Vec::new();
+    for c in constraints {
+        let top = c.i.max(c.j) as usize;
+        if degree.len() <= top {
+            degree.resize(top + 1, 0);
+        }
+        degree[c.i as usize] += 1;
+        degree[c.j as usize] += 1;
+    }
+    constraints
+        .iter()
+        .filter(|c| degree[c.i as usize] <= 2 && degree[c.j as usize] <= 2)
+        .map(|c| [c.i, c.j])
+        .collect()
+}
+
+/// Parameters `(s, t)` in \[0,1\] of the closest points between segments
+/// `p1 -> q1` and `p2 -> q2`. Degenerate (zero-length) segments are handled
+/// as points.
+///
+/// Reference: Ericson, "Real-Time Collision Detection", section 5.1.9.
+pub fn closest_points_on_segments(p1: Vec3, q1: Vec3, p2: Vec3, q2: Vec3) -> (f32, f32) {
+    const EPS: f32 = 1e-12;
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= EPS && e <= EPS {
+        return (0.0, 0.0);
+    }
+    if a <= EPS {
+        return (0.0, (f / e).clamp(0.0, 1.0));
+    }
+    let c = d1.dot(r);
+    if e <= EPS {
+        return ((-c / a).clamp(0.0, 1.0), 0.0);
+    }
+
+    let b = d1.dot(d2);
+    let denom = a * e - b * b;
+    let mut s = if denom > EPS { ((b * f - c * e) / denom).clamp(0.0, 1.0) } else { 0.0 };
+    let mut t = (b * s + f) / e;
+    if t < 0.0 {
+        t = 0.0;
+        s = (-c / a).clamp(0.0, 1.0);
+    } else if t > 1.0 {
+        t = 1.0;
+        s = ((b - c) / a).clamp(0.0, 1.0);
+    }
+    (s, t)
+}
+
+/// Detect overlapping capsule pairs among `segments`.
+///
+/// The capsule radius at a point is interpolated from the endpoint radii.
+/// Segments sharing a particle (neighbors along the same rope) are skipped,
+/// as are pairs where every endpoint is static.
+///
+/// `grid` must index `positions` and serves as the broad phase: a segment
+/// overlapping segment A has an endpoint within half of A's length, half of
+/// the longest segment and two radii of A's midpoint, so only the segments
+/// of particles found there are tested.
+pub fn detect_capsule_contacts(
+    positions: &[Vec3],
+    radii: &[f32],
+    inv_mass: &[f32],
+    segments: &[[u32; 2]],
+    grid: &SpatialHashGrid,
+) -> Vec<CapsuleContact> {
+    let mut contacts = Vec::new();
+    if segments.len() < 2 {
+        return contacts;
+    }
+
+    // Segments touching each particle (CSR layout)
+    let top = segments.iter().flatten().copied().max().unwrap_or(0) as usize;
+    let mut start = vec![0u32; top + 2];
+    for &p in segments.iter().flatten() {
+        start[p as usize + 1] += 1;
+    }
+    for k in 1..start.len() {
+        start[k] += start[k - 1];
+    }
+    let mut fill = start.clone();
+    let mut touching = vec![0u32; segments.len() * 2];
+    for (k, seg) in segments.iter().enumerate() {
+        for &p in seg {
+            touching[fill[p as usize] as usize] = k as u32;
+            fill[p as usize] += 1;
+        }
+    }
+
+    let half_len = |seg: [u32; 2]| 0.5 * positions[seg[0] as usize].distance(positions[seg[1] as usize]);
+    let max_half_len = segments.iter().map(|&seg| half_len(seg)).fold(0.0, f32::max);
+    let max_radius = segments
+        .iter()
+        .flatten()
+        .map(|&p| radii[p as usize])
+        .fold(0.0, f32::max);
+
+    // Segment last tested against the current segment (dedups grid reports)
+    let mut tested_by = vec![u32::MAX; segments.len()];
+    for (k, &a) in segments.iter().enumerate() {
+        let (a0, a1) = (a[0] as usize, a[1] as usize);
+        let mid = positions[a0].lerp(positions[a1], 0.5);
+        let reach = half_len(a) + max_half_len + 2.0 * max_radius;
+        grid.query_radius(mid, reach, |p| {
+            let p = p as usize;
+            if p > top {
+                return;
+            }
+            for &m in &touching[start[p] as usize..start[p + 1] as usize] {
+                if m as usize <= k || tested_by[m as usize] == k as u32 {
+                    continue;
+                }
+                tested_by[m as usize] = k as u32;
+                if let Some(contact) = capsule_pair_contact(positions, radii, inv_mass, a, segments[m as usize]) {
+                    contacts.push(contact);
+                }
+            }
+        });
+    }
+    contacts
+}
+
+/// Contact between capsules `a` and `b`, if they overlap and are not adjacent.
+fn capsule_pair_contact(
+    positions: &[Vec3],
+    radii: &[f32],
+    inv_mass: &[f32],
+    a: [u32; 2],
+    b: [u32; 2],
+) -> Option<CapsuleContact> {
+    let (a0, a1) = (a[0] as usize, a[1] as usize);
+    let (b0, b1) = (b[0] as usize, b[1] as usize);
+    if a0 == b0 || a0 == b1 || a1 == b0 || a1 == b1 {
+        return None; // adjacent segments always touch at the shared joint
+    }
+    if inv_mass[a0] + inv_mass[a1] + inv_mass[b0] + inv_mass[b1] == 0.0 {
+        return None;
+    }
+
+    let (s, t) = closest_points_on_segments(
+        positions[a0],
+        positions[a1],
+        positions[b0],
+        positions[b1],
+    );
+    let on_a = positions[a0].lerp(positions[a1], s);
+    let on_b = positions[b0].lerp(positions[b1], t);
+    let diff = on_b - on_a;
+    let dist = diff.length();
+    let min_dist = radii[a0] + (radii[a1] - radii[a0]) * s
+        + radii[b0] + (radii[b1] - radii[b0]) * t;
+    if dist < min_dist && dist > 1e-8 {
+        Some(CapsuleContact {
+            a,
+            b,
+            s,
+            t,
+            normal: diff / dist,
+            penetration: min_dist - dist,
+        })
+    } else {
+        None
+    }
+}
+
+/// Solve capsule contacts using Jacobi-style position corrections.
+///
+/// The separation is split over the four endpoints by their barycentric
+/// weights at the closest points and their inverse masses, so the closest
+/// points move apart along the contact normal by the penetration depth.
+///
+/// `corrections` and `correction_counts` are the same accumulation buffers
+/// `solve_contacts` uses.
+pub fn solve_capsule_contacts(
+    contacts: &[CapsuleContact],
+    inv_mass: &[f32],
+    corrections: &mut [Vec3],
+    correction_counts: &mut [u32],
+) {
+    for contact in contacts {
+        let ends = [
+            (contact.a[0] as usize, -(1.0 - contact.s)),
+            (contact.a[1] as usize, -contact.s),
+            (contact.b[0] as usize, 1.0 - contact.t),
+            (contact.b[1] as usize, contact.t),
+        ];
+        let w_sum: f32 = ends.iter().map(|&(p, bary)| bary * bary * inv_mass[p]).sum();
+        if w_sum < 1e-10 {
+            continue;
+        }
+
+        let lambda = contact.penetration / w_sum;
+        for (p, bary) in ends {
+            corrections[p] += contact.normal * lambda * bary * inv_mass[p];
+            correction_counts[p] += 1;
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use glam::Vec3;
 
 use crate::grid::SpatialHashGrid;
+use crate::particle::Phase;
 
 /// A detected contact between two particles
 #[derive(Clone)]
@@ -11,13 +12,41 @@ pub struct ContactConstraint {
     pub penetration: f32, // overlap depth (positive = overlapping)
 }
 
+/// Extra friction for contacts involving a `Phase::Granular` particle, so sand
+/// piles at a repose angle instead of flowing flat.
+///
+/// The default (all zeros) makes granular contacts behave like any other.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GranularFriction {
+    /// Friction coefficient for granular contacts; the larger of this and the
+    /// base friction is used.
+    pub friction: f32,
+    /// Relative tangential speed below which a granular contact sticks
+    /// completely (static friction), regardless of penetration depth.
+    pub static_velocity: f32,
+}
+
 /// Detect all particle-particle contacts using the spatial grid.
 /// Returns a list of contacts where sphere-sphere overlap is detected.
+///
+/// Contacts are returned sorted by `(i, j)` (always `i < j`), so the result
+/// does not depend on the grid's bucket layout or hash table size. Each pair
+/// yields at most one contact: a pair reached through two cells sharing a
+/// hash bucket used to be reported once per cell, doubling its correction.
+///
+/// `max_per_particle` bounds how many contacts any one particle takes part
+/// in (0 = unlimited). When a particle exceeds it, the deepest penetrations
+/// are kept; surviving contacts stay in `(i, j)` order.
+///
+/// Pairs where both particles are static (`inv_mass == 0`) are skipped, since
+/// neither side could be corrected.
 pub fn detect_contacts(
     positions: &[Vec3],
     radii: &[f32],
+    inv_mass: &[f32],
     count: usize,
     grid: &SpatialHashGrid,
+    max_per_particle: usize,
 ) -> Vec<ContactConstraint> {
     let mut contacts = Vec::new();
 
@@ -26,7 +55,10 @@ pub fn detect_contacts(
             if j <= i as u32 {
                 return; // avoid duplicate pairs + self
             }
-            let diff = positions[j as usize] - positions[i];
+            if inv_mass[i] == 0.0 && inv_mass[j as usize] == 0.0 {
+                return; // static-static pairs never move
+            }
+            let diff = grid.min_image_diff(positions[j as usize], positions[i]);
             let dist = diff.length();
             let min_dist = radii[i] + radii[j as usize];
             if dist < min_dist && dist > 1e-8 {
@@ -42,9 +74,146 @@ pub fn detect_contacts(
         });
     }
 
+    // Bucket order varies with the table size, and cells can share a bucket
+    contacts.sort_by_key(|c| (c.i, c.j));
+    contacts.dedup_by_key(|c| (c.i, c.j));
+
+    if max_per_particle > 0 {
+        cap_contacts_per_particle(&mut contacts, count, max_per_particle);
+    }
     contacts
 }
 
+/// Detect contacts with a speculative `margin`: pairs within `margin` of touching
+/// at `predicted` are swept along their straight-line paths from `previous`, and
+/// become contacts if they overlapped at any point during the substep.
+///
+/// The normal comes from the pre-step separation rather than the predicted one,
+/// so a fast pair that crossed over (or fully passed through) each other within
+/// one substep is pushed back to the side it came from, with a penetration that
+/// can exceed the sum of radii. For pairs at rest this matches `detect_contacts`.
+/// Pairs with both particles static are skipped. Like `detect_contacts`, the
+/// result is sorted by `(i, j)` with one contact per pair.
+pub fn detect_speculative_contacts(
+    predicted: &[Vec3],
+    previous: &[Vec3],
+    radii: &[f32],
+    inv_mass: &[f32],
+    count: usize,
+    grid: &SpatialHashGrid,
+    margin: f32,
+) -> Vec<ContactConstraint> {
+    let mut contacts = Vec::new();
+    // Reach of the widest pair involving `i` is `radii[i]` plus the largest radius
+    let max_radius = radii[..count].iter().copied().fold(0.0, f32::max);
+
+    for i in 0..count {
+        grid.query_radius(predicted[i], radii[i] + max_radius + margin, |j| {
+            let ju = j as usize;
+            if j <= i as u32 || (inv_mass[i] == 0.0 && inv_mass[ju] == 0.0) {
+                return;
+            }
+            let min_dist = radii[i] + radii[ju];
+            let end = grid.min_image_diff(predicted[ju], predicted[i]);
+            if end.length() >= min_dist + margin {
+                return;
+            }
+            let start = grid.min_image_diff(previous[ju], previous[i]);
+
+            // Closest approach of the relative path start -> end over the substep
+            let travel = end - start;
+            let travel_sq = travel.length_squared();
+            let t = if travel_sq > 1e-12 {
+                (-start.dot(travel) / travel_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            if (start + travel * t).length() >= min_dist {
+                return;
+            }
+
+            let normal = if start.length_squared() > 1e-16 {
+                start.normalize()
+            } else if end.length_squared() > 1e-16 {
+                end.normalize()
+            } else {
+                return;
+            };
+            let penetration = min_dist - end.dot(normal);
+            if penetration > 0.0 {
+                contacts.push(ContactConstraint {
+                    i: i as u32,
+                    j,
+                    normal,
+                    penetration,
+                });
+            }
+        });
+    }
+
+    // Cells can share a hash bucket, so a pair may have been seen twice
+    contacts.sort_by_key(|c| (c.i, c.j));
+    contacts.dedup_by_key(|c| (c.i, c.j));
+    contacts
+}
+
+/// Drop the shallowest contacts of particles involved in more than `max` contacts.
+///
+/// Contacts are accepted deepest-first while both particles have room, so a
+/// deep overlap is never dropped in favor of a shallow one.
+pub fn cap_contacts_per_particle(contacts: &mut Vec<ContactConstraint>, count: usize, max: usize) {
+    let mut per_particle = vec![0usize; count];
+    for c in contacts.iter() {
+        per_particle[c.i as usize] += 1;
+        per_particle[c.j as usize] += 1;
+    }
+    if per_particle.iter().all(|&n| n <= max) {
+        return;
+    }
+
+    let mut order: Vec<usize> = (0..contacts.len()).collect();
+    order.sort_by(|&a, &b| contacts[b].penetration.total_cmp(&contacts[a].penetration));
+
+    per_particle.fill(0);
+    let mut keep = vec![false; contacts.len()];
+    for k in order {
+        let (i, j) = (contacts[k].i as usize, contacts[k].j as usize);
+        if per_particle[i] < max && per_particle[j] < max {
+            per_particle[i] += 1;
+            per_particle[j] += 1;
+            keep[k] = true;
+        }
+    }
+
+    let mut k = 0;
+    contacts.retain(|_| {
+        k += 1;
+        keep[k - 1]
+    });
+}
+
+/// Per-call settings of `solve_contacts`: the friction model and the
+/// correction knobs.
+pub struct ContactParams<'a, F: Fn(usize, usize) -> f32> {
+    /// Phase of each particle; contacts touching a `Phase::Granular` particle
+    /// use `granular` friction.
+    pub phase: &'a [Phase],
+    /// Coulomb friction coefficient of a pair `(i, j)` (0 = frictionless).
+    pub friction: F,
+    /// Granular contacts use at least `granular.friction`, and slow tangential
+    /// slip is fully arrested.
+    pub granular: GranularFriction,
+    /// Successive over-relaxation factor applied to the normal correction:
+    /// 1.0 resolves the full penetration at once, values below 1.0 under-relax
+    /// (smoother stacks), values above 1.0 over-relax.
+    pub contact_sor: f32,
+    /// Caps the depth resolved per contact per call (0 = no cap), so deep
+    /// overlaps separate gradually for a soft, squishy look.
+    pub max_correction: f32,
+    /// Substep time step.
+    pub dt: f32,
+}
+
 /// Solve contact constraints using Jacobi-style position corrections.
 /// Pushes overlapping particles apart proportionally to penetration depth.
 /// Applies Coulomb friction to tangential relative velocity.
@@ -54,18 +223,16 @@ pub fn detect_contacts(
 ///
 /// `predicted` are the current predicted positions. `previous` are the positions
 /// before prediction (used to estimate velocity for friction).
-/// `friction` is the Coulomb friction coefficient (0 = frictionless).
-/// `dt` is the substep time step.
-pub fn solve_contacts(
+pub fn solve_contacts<F: Fn(usize, usize) -> f32>(
     contacts: &[ContactConstraint],
     predicted: &[Vec3],
     previous: &[Vec3],
     inv_mass: &[f32],
     corrections: &mut [Vec3],
     correction_counts: &mut [u32],
-    friction: f32,
-    dt: f32,
+    params: ContactParams<F>,
 ) {
+    let ContactParams { phase, friction, granular, contact_sor, max_correction, dt } = params;
     for contact in contacts {
         let i = contact.i as usize;
         let j = contact.j as usize;
@@ -78,12 +245,24 @@ pub fn solve_contacts(
         }
 
         // Mass-weighted normal correction
-        let correction = contact.normal * contact.penetration / w_sum;
+        let mut depth = contact.penetration * contact_sor;
+        if max_correction > 0.0 {
+            depth = depth.min(max_correction);
+        }
+        let correction = contact.normal * depth / w_sum;
         corrections[i] -= correction * w_i;
         corrections[j] += correction * w_j;
 
+        let is_granular = phase[i] == Phase::Granular || phase[j] == Phase::Granular;
+        let friction = friction(i, j);
+        let (friction, static_velocity) = if is_granular {
+            (friction.max(granular.friction), granular.static_velocity)
+        } else {
+            (friction, 0.0)
+        };
+
         // Coulomb friction: reduce tangential relative velocity
-        if friction > 0.0 && dt > 1e-10 {
+        if (friction > 0.0 || static_velocity > 0.0) && dt > 1e-10 {
             let vel_i = (predicted[i] - previous[i]) / dt;
             let vel_j = (predicted[j] - previous[j]) / dt;
             let rel_vel = vel_i - vel_j;
@@ -92,8 +271,13 @@ pub fn solve_contacts(
             let vt_len = vt.length();
             if vt_len > 1e-8 {
                 // Coulomb: tangential impulse <= mu * normal impulse
+                // (static friction: slow granular slip is cancelled outright)
                 let max_friction = friction * contact.penetration * 0.5;
-                let friction_mag = (vt_len * dt).min(max_friction);
+                let friction_mag = if vt_len < static_velocity {
+                    vt_len * dt
+                } else {
+                    (vt_len * dt).min(max_friction)
+                };
                 let tangent = vt / vt_len;
                 let friction_correction_i = tangent * friction_mag * w_i / w_sum;
                 let friction_correction_j = tangent * friction_mag * w_j / w_sum;
@@ -106,3 +290,49 @@ pub fn solve_contacts(
         correction_counts[j] += 1;
     }
 }
+
+/// Relative normal velocity `(v_j - v_i) . n` of each contact (negative = approaching).
+///
+/// Call before velocities are re-derived from the solved positions, so
+/// `apply_contact_restitution` knows how fast each pair was closing.
+pub fn contact_normal_velocities(contacts: &[ContactConstraint], velocity: &[Vec3]) -> Vec<f32> {
+    contacts
+        .iter()
+        .map(|c| (velocity[c.j as usize] - velocity[c.i as usize]).dot(c.normal))
+        .collect()
+}
+
+/// Give approaching contacts a separating normal velocity of `e * closing speed`.
+///
+/// `pre_normal_velocity` comes from `contact_normal_velocities` before the solve;
+/// `velocity` holds the post-solve velocities and is corrected in place, split by
+/// inverse mass. `restitution(i, j)` returns the coefficient for a pair.
+pub fn apply_contact_restitution<F: Fn(usize, usize) -> f32>(
+    contacts: &[ContactConstraint],
+    pre_normal_velocity: &[f32],
+    velocity: &mut [Vec3],
+    inv_mass: &[f32],
+    restitution: F,
+) {
+    for (contact, &pre_vn) in contacts.iter().zip(pre_normal_velocity) {
+        if pre_vn >= 0.0 {
+            continue; // was already separating
+        }
+        let i = contact.i as usize;
+        let j = contact.j as usize;
+        let w_sum = inv_mass[i] + inv_mass[j];
+        if w_sum < 1e-10 {
+            continue;
+        }
+
+        let vn = (velocity[j] - velocity[i]).dot(contact.normal);
+        let target = -restitution(i, j) * pre_vn;
+        let dv = target - vn;
+        if dv <= 0.0 {
+            continue; // already separating at least that fast
+        }
+        let impulse = contact.normal * dv / w_sum;
+        velocity[i] -= impulse * inv_mass[i];
+        velocity[j] += impulse * inv_mass[j];
+    }
+}
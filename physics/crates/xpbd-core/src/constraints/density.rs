@@ -23,6 +23,75 @@ fn is_fluid_phase(phase: Phase) -> bool {
     matches!(phase, Phase::Fluid | Phase::Gas)
 }
 
+/// Whether live particle `i` gets a density (and, when solving, a density
+/// constraint): Fluid and Gas always, Free too with `include_free`.
+#[inline]
+fn has_density(particles: &ParticleSet, i: usize, include_free: bool) -> bool {
+    let phase = particles.phase[i];
+    particles.alive[i] && (is_fluid_phase(phase) || (include_free && phase == Phase::Free))
+}
+
+/// Grid neighbors of particle `i` within `h` as `(j, x_i - x_j)`, written to `out`.
+///
+/// The list includes `i` itself, and lists each neighbor once even when
+/// several of the searched cells share a hash bucket. With `max_neighbors > 0`,
+/// only that many of the closest are kept, which bounds the cost of kernel
+/// sums in heavily compressed regions at the price of underestimating density
+/// there.
+pub fn gather_fluid_neighbors(
+    particles: &ParticleSet,
+    grid: &SpatialHashGrid,
+    i: usize,
+    h: f32,
+    max_neighbors: usize,
+    out: &mut Vec<(usize, Vec3)>,
+) {
+    out.clear();
+    let pos_i = particles.predicted[i];
+    grid.query_neighbors(pos_i, |j| {
+        let j = j as usize;
+        let r = grid.min_image_diff(pos_i, particles.predicted[j]);
+        if r.length_squared() < h * h {
+            out.push((j, r));
+        }
+    });
+    if max_neighbors > 0 && out.len() > max_neighbors {
+        out.select_nth_unstable_by(max_neighbors - 1, |a, b| {
+            a.1.length_squared().total_cmp(&b.1.length_squared())
+        });
+        out.truncate(max_neighbors);
+    }
+}
+
+/// Estimate SPH density for every fluid/gas particle from predicted positions.
+///
+/// Writes `particles.density` using the poly6 kernel over grid neighbors
+/// (unit mass). The grid must already be built from `particles.predicted`.
+/// `max_neighbors` caps the neighbors summed per particle (0 = no cap).
+/// With `include_free`, Free particles get a density as well.
+pub fn compute_densities(
+    particles: &mut ParticleSet,
+    grid: &SpatialHashGrid,
+    smoothing_radius: f32,
+    max_neighbors: usize,
+    include_free: bool,
+) {
+    let h = smoothing_radius;
+    let mut neighbors = Vec::new();
+    for i in 0..particles.count {
+        if !has_density(particles, i, include_free) {
+            continue;
+        }
+
+        gather_fluid_neighbors(particles, grid, i, h, max_neighbors, &mut neighbors);
+        // NOTE: Assumes unit mass for all particles. If per-particle mass
+        // is added (via inv_mass field), multiply by mass_j here.
+        let rho: f32 = neighbors.iter().map(|&(_, r)| poly6_kernel(r.length(), h)).sum();
+
+        particles.density[i] = rho;
+    }
+}
+
 /// Solve PBF density constraints for fluid/gas particles.
 ///
 /// Reference: "Position Based Fluids", Macklin & Muller, SIGGRAPH 2013
@@ -32,6 +101,12 @@ fn is_fluid_phase(phase: Phase) -> bool {
 /// 2. Compute lambda (Lagrange multiplier) with epsilon relaxation.
 /// 3. Compute position corrections with optional tensile instability fix.
 ///
+/// `rest_density` is the default rho_0; particles with a non-zero
+/// `particles.rest_density` use their own value instead. `max_neighbors`
+/// caps the closest neighbors each particle considers (0 = no cap).
+/// With `include_free`, Free particles are constrained like fluid so spray
+/// rejoining a fluid body blends in instead of popping.
+///
 /// Position corrections are accumulated into `particles.corrections` and
 /// `particles.correction_counts` using Jacobi-style updates, so the caller
 /// is responsible for zeroing these buffers before the first constraint
@@ -43,10 +118,15 @@ pub fn solve_density_constraints(
     rest_density: f32,
     smoothing_radius: f32,
     tensile_correction: bool,
+    max_neighbors: usize,
+    include_free: bool,
 ) {
     let count = particles.count;
     let h = smoothing_radius;
-    let inv_rho0 = 1.0 / rest_density.max(1.0);
+    let inv_rest = |own: f32| {
+        let rho0 = if own > 0.0 { own } else { rest_density };
+        1.0 / rho0.max(1.0)
+    };
 
     // Precompute the tensile reference kernel value (poly6 at delta_q distance).
     let poly6_dq = if tensile_correction {
@@ -59,37 +139,19 @@ pub fn solve_density_constraints(
     // ------------------------------------------------------------------
     // Phase 1: Compute density for every fluid/gas particle.
     // ------------------------------------------------------------------
-    for i in 0..count {
-        if !is_fluid_phase(particles.phase[i]) {
-            continue;
-        }
-
-        let pos_i = particles.predicted[i];
-        let mut rho = 0.0_f32;
-
-        grid.query_neighbors(pos_i, |j| {
-            let j = j as usize;
-            let r_len = (pos_i - particles.predicted[j]).length();
-            if r_len < h {
-                // NOTE: Assumes unit mass for all particles. If per-particle mass
-                // is added (via inv_mass field), multiply by mass_j here.
-                rho += poly6_kernel(r_len, h);
-            }
-        });
-
-        particles.density[i] = rho;
-    }
+    compute_densities(particles, grid, h, max_neighbors, include_free);
+    let mut neighbors = Vec::new();
 
     // ------------------------------------------------------------------
     // Phase 2: Compute lambda_i for every fluid/gas particle.
     // ------------------------------------------------------------------
     for i in 0..count {
-        if !is_fluid_phase(particles.phase[i]) {
+        if !has_density(particles, i, include_free) {
             continue;
         }
 
-        let pos_i = particles.predicted[i];
         let rho_i = particles.density[i];
+        let inv_rho0 = inv_rest(particles.rest_density[i]);
 
         // Constraint value: C_i = rho_i / rho_0 - 1
         let c_i = rho_i * inv_rho0 - 1.0;
@@ -98,19 +160,15 @@ pub fn solve_density_constraints(
         let mut grad_sum_sq = 0.0_f32;
         let mut grad_self = Vec3::ZERO;
 
-        grid.query_neighbors(pos_i, |j| {
-            let j = j as usize;
+        gather_fluid_neighbors(particles, grid, i, h, max_neighbors, &mut neighbors);
+        for &(j, r) in &neighbors {
             if j == i {
-                return;
-            }
-            let r = pos_i - particles.predicted[j];
-            let r_len = r.length();
-            if r_len < h {
-                let grad_j = spiky_gradient(r, r_len, h) * inv_rho0;
-                grad_sum_sq += grad_j.length_squared();
-                grad_self += grad_j;
+                continue;
             }
-        });
+            let grad_j = spiky_gradient(r, r.length(), h) * inv_rho0;
+            grad_sum_sq += grad_j.length_squared();
+            grad_self += grad_j;
+        }
 
         grad_sum_sq += grad_self.length_squared();
 
@@ -121,27 +179,23 @@ pub fn solve_density_constraints(
     // Phase 3: Compute position corrections.
     // ------------------------------------------------------------------
     for i in 0..count {
-        if !is_fluid_phase(particles.phase[i]) {
+        if !has_density(particles, i, include_free) {
             continue;
         }
 
-        let pos_i = particles.predicted[i];
         let lambda_i = particles.lambda[i];
+        let inv_rho0 = inv_rest(particles.rest_density[i]);
         let mut delta_p = Vec3::ZERO;
 
-        grid.query_neighbors(pos_i, |j| {
-            let j = j as usize;
+        gather_fluid_neighbors(particles, grid, i, h, max_neighbors, &mut neighbors);
+        for &(j, r) in &neighbors {
             if j == i {
-                return;
+                continue;
             }
-            let r = pos_i - particles.predicted[j];
             let r_len = r.length();
-            if r_len >= h {
-                return;
-            }
 
-            // Use neighbor lambda if it is a fluid/gas particle, otherwise 0.
-            let lambda_j = if is_fluid_phase(particles.phase[j]) {
+            // Use neighbor lambda if it has a density constraint, otherwise 0.
+            let lambda_j = if has_density(particles, j, include_free) {
                 particles.lambda[j]
             } else {
                 0.0
@@ -156,7 +210,7 @@ pub fn solve_density_constraints(
             };
 
             delta_p += (lambda_i + lambda_j + s_corr) * spiky_gradient(r, r_len, h) * inv_rho0;
-        });
+        }
 
         particles.corrections[i] += delta_p;
         particles.correction_counts[i] += 1;
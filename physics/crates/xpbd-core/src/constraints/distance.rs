@@ -7,6 +7,7 @@ use crate::particle::ParticleSet;
 ///
 /// Reference: "XPBD: Position-Based Simulation of Compliant Constrained Dynamics",
 /// Macklin et al., 2016
+#[derive(Clone)]
 pub struct DistanceConstraint {
     /// Particle index A.
     pub i: u32,
@@ -20,6 +21,9 @@ pub struct DistanceConstraint {
     pub lambda: f32,
 }
 
+/// Words per constraint in `Solver::pack_distance_constraints`.
+pub const PACKED_DISTANCE_STRIDE: usize = 4;
+
 impl DistanceConstraint {
     /// Create a new distance constraint between particles `i` and `j`.
     pub fn new(i: u32, j: u32, rest_length: f32, compliance: f32) -> Self {
@@ -1,4 +1,5 @@
 pub mod bending;
+pub mod capsule_contact;
 pub mod contact;
 pub mod density;
 pub mod distance;
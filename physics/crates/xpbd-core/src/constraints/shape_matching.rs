@@ -2,6 +2,9 @@ use glam::{Mat3, Vec3};
 
 use crate::particle::ParticleSet;
 
+/// Default number of polar decomposition iterations per group.
+pub const DEFAULT_POLAR_ITERATIONS: u32 = 10;
+
 /// Rigid body shape matching group.
 ///
 /// Stores particle indices and their rest-state positions relative to the
@@ -12,6 +15,7 @@ use crate::particle::ParticleSet;
 /// 4. Moves particles toward their rotated rest positions
 ///
 /// Reference: "Meshless Deformations Based on Shape Matching", Mueller et al., 2005
+#[derive(Clone)]
 pub struct ShapeMatchGroup {
     pub particle_indices: Vec<u32>,
     /// Rest positions relative to rest center of mass.
@@ -19,14 +23,26 @@ pub struct ShapeMatchGroup {
     pub rest_com: Vec3,
     /// Stiffness in `[0..1]`. A value of `1.0` means fully rigid.
     pub stiffness: f32,
+    /// Newton iterations for the polar decomposition (default 10). Raise for
+    /// stiff, fast-rotating bodies; lower for many small bodies.
+    pub polar_iterations: u32,
+    /// Rotation extracted by the most recent solve (identity until solved).
+    pub rotation: Mat3,
 }
 
 impl ShapeMatchGroup {
     /// Create a shape match group from particle indices.
     ///
-    /// Computes rest positions relative to center of mass from current
-    /// positions (assuming uniform mass for all particles).
-    pub fn from_particles(indices: Vec<u32>, positions: &[Vec3], stiffness: f32) -> Self {
+    /// Computes rest positions relative to the mass-weighted center of mass of
+    /// the current positions, matching the current CoM in `solve_shape_matching`.
+    /// Static particles (`inv_mass == 0`) carry no weight; if every particle is
+    /// static the plain average is used.
+    pub fn from_particles(
+        indices: Vec<u32>,
+        positions: &[Vec3],
+        inv_mass: &[f32],
+        stiffness: f32,
+    ) -> Self {
         let n = indices.len();
         if n == 0 {
             return Self {
@@ -34,15 +50,28 @@ impl ShapeMatchGroup {
                 rest_positions: Vec::new(),
                 rest_com: Vec3::ZERO,
                 stiffness,
+                polar_iterations: DEFAULT_POLAR_ITERATIONS,
+                rotation: Mat3::IDENTITY,
             };
         }
 
-        // Compute rest CoM (uniform mass)
+        // Compute rest CoM (mass-weighted, skip static particles)
         let mut com = Vec3::ZERO;
+        let mut total_mass = 0.0_f32;
         for &idx in &indices {
-            com += positions[idx as usize];
+            let i = idx as usize;
+            if inv_mass[i] == 0.0 {
+                continue;
+            }
+            let mass = 1.0 / inv_mass[i];
+            com += positions[i] * mass;
+            total_mass += mass;
         }
-        com /= n as f32;
+        let com = if total_mass < 1e-10 {
+            indices.iter().map(|&idx| positions[idx as usize]).sum::<Vec3>() / n as f32
+        } else {
+            com / total_mass
+        };
 
         // Compute rest positions relative to CoM
         let rest_pos: Vec<Vec3> = indices
@@ -55,7 +84,38 @@ impl ShapeMatchGroup {
             rest_positions: rest_pos,
             rest_com: com,
             stiffness,
+            polar_iterations: DEFAULT_POLAR_ITERATIONS,
+            rotation: Mat3::IDENTITY,
+        }
+    }
+
+    /// Drop every particle flagged in `removed` (indexed by particle id).
+    ///
+    /// The remaining rest positions are re-centered on their own center of
+    /// mass so the group keeps its shape without drifting toward the removed part.
+    pub fn remove_particles(&mut self, removed: &[bool]) {
+        let mut kept = 0;
+        for k in 0..self.particle_indices.len() {
+            if !removed[self.particle_indices[k] as usize] {
+                self.particle_indices[kept] = self.particle_indices[k];
+                self.rest_positions[kept] = self.rest_positions[k];
+                kept += 1;
+            }
+        }
+        if kept == self.particle_indices.len() {
+            return;
         }
+        self.particle_indices.truncate(kept);
+        self.rest_positions.truncate(kept);
+        if kept == 0 {
+            return;
+        }
+
+        let shift = self.rest_positions.iter().copied().sum::<Vec3>() / kept as f32;
+        for r in &mut self.rest_positions {
+            *r -= shift;
+        }
+        self.rest_com += shift;
     }
 }
 
@@ -66,7 +126,11 @@ impl ShapeMatchGroup {
 /// 2. Build A_pq cross-covariance matrix
 /// 3. Extract rotation R via iterative polar decomposition
 /// 4. Compute goal = R * rest_pos + com, apply correction
-pub fn solve_shape_matching(groups: &[ShapeMatchGroup], particles: &mut ParticleSet) {
+///
+/// Groups and their particles are processed serially in stored order, so the
+/// center-of-mass and A_pq reductions are deterministic. Each group's
+/// extracted rotation is stored in `rotation`.
+pub fn solve_shape_matching(groups: &mut [ShapeMatchGroup], particles: &mut ParticleSet) {
     for group in groups {
         if group.particle_indices.is_empty() {
             continue;
@@ -109,7 +173,8 @@ pub fn solve_shape_matching(groups: &[ShapeMatchGroup], particles: &mut Particle
         let a_pq = a_pq + Mat3::IDENTITY * 1e-6;
 
         // Step 3: Extract rotation via polar decomposition
-        let r = polar_decomposition_iterative(a_pq);
+        let r = polar_decomposition_iterative(a_pq, group.polar_iterations);
+        group.rotation = r;
 
         // Step 4: Apply corrections
         let stiffness = group.stiffness;
@@ -135,13 +200,13 @@ fn mat3_outer(a: Vec3, b: Vec3) -> Mat3 {
 
 /// Iterative polar decomposition: extract rotation from A = R * S.
 ///
-/// Uses 10 iterations of: R_{k+1} = 0.5 * (R_k + R_k^{-T})
+/// Uses `iterations` steps of: R_{k+1} = 0.5 * (R_k + R_k^{-T})
 ///
 /// Converges to the rotation component of the polar decomposition.
 /// If the matrix is singular, returns identity.
-fn polar_decomposition_iterative(a: Mat3) -> Mat3 {
+fn polar_decomposition_iterative(a: Mat3, iterations: u32) -> Mat3 {
     let mut r = a;
-    for _ in 0..10 {
+    for _ in 0..iterations {
         let det = r.determinant();
         if det.abs() < 1e-10 {
             return Mat3::IDENTITY;
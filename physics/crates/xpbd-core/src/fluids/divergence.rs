@@ -0,0 +1,49 @@
+use crate::fluids::spiky_gradient;
+use crate::particle::{ParticleSet, Phase};
+use crate::grid::SpatialHashGrid;
+
+/// Estimate SPH velocity divergence for fluid/gas particles.
+///
+/// Formula: div_i = (1 / rho_i) * sum_j { (v_j - v_i) . grad W_spiky(x_i - x_j, h) }
+/// (unit mass). Zero means locally incompressible flow; positive values mean
+/// the fluid is expanding, negative values that it is compressing.
+///
+/// Reads `particles.predicted` and `particles.density`, so the grid and
+/// densities must be up to date. Results are written to `particles.divergence`;
+/// non-fluid particles get `0.0`.
+pub fn compute_velocity_divergence(
+    particles: &mut ParticleSet,
+    grid: &SpatialHashGrid,
+    smoothing_radius: f32,
+) {
+    let h = smoothing_radius;
+
+    for i in 0..particles.count {
+        if particles.phase[i] != Phase::Fluid && particles.phase[i] != Phase::Gas {
+            particles.divergence[i] = 0.0;
+            continue;
+        }
+
+        let pos_i = particles.predicted[i];
+        let vel_i = particles.velocity[i];
+        let mut div = 0.0_f32;
+
+        grid.query_neighbors(pos_i, |j| {
+            let j = j as usize;
+            if j == i {
+                return;
+            }
+            if particles.phase[j] != Phase::Fluid && particles.phase[j] != Phase::Gas {
+                return;
+            }
+
+            let r = grid.min_image_diff(pos_i, particles.predicted[j]);
+            let r_len = r.length();
+            if r_len < h {
+                div += (particles.velocity[j] - vel_i).dot(spiky_gradient(r, r_len, h));
+            }
+        });
+
+        particles.divergence[i] = div / particles.density[i].max(1e-6);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod divergence;
+pub mod interface_tension;
 pub mod viscosity;
 pub mod vorticity;
 
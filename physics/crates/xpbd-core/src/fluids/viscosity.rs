@@ -42,7 +42,7 @@ pub fn apply_xsph_viscosity(
                 return;
             }
 
-            let r = pos_i - particles.predicted[j];
+            let r = grid.min_image_diff(pos_i, particles.predicted[j]);
             let r_len = r.length();
             if r_len < h {
                 let w = poly6_kernel(r_len, h);
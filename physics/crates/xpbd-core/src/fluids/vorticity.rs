@@ -3,6 +3,9 @@ use crate::fluids::spiky_gradient;
 use crate::particle::{ParticleSet, Phase};
 use crate::grid::SpatialHashGrid;
 
+/// Lower bound on |eta| when normalizing the vorticity location vector.
+const ETA_EPSILON: f32 = 1e-6;
+
 /// Apply vorticity confinement to counteract numerical dissipation.
 ///
 /// Two phases:
@@ -11,19 +14,26 @@ use crate::grid::SpatialHashGrid;
 ///
 /// This adds energy back into the simulation where the discrete solver
 /// has lost it, producing more lively, swirling fluid motion.
+///
+/// The per-particle confinement force is clamped to `max_vorticity_force`
+/// so noisy velocity fields cannot pump energy in without bound
+/// (`f32::INFINITY` disables the clamp).
 pub fn apply_vorticity_confinement(
     particles: &mut ParticleSet,
     grid: &SpatialHashGrid,
     vorticity_strength: f32,
+    max_vorticity_force: f32,
     smoothing_radius: f32,
     dt: f32,
 ) {
     let count = particles.count;
     let h = smoothing_radius;
 
-    // Phase 1: Compute vorticity (curl of velocity field)
+    // Phase 1: Compute vorticity (curl of velocity field), stored per particle
+    // so it can be read back for visualization. Non-fluid particles read zero.
     for i in 0..count {
         if particles.phase[i] != Phase::Fluid && particles.phase[i] != Phase::Gas {
+            particles.vorticity[i] = Vec3::ZERO;
             continue;
         }
 
@@ -38,12 +48,13 @@ pub fn apply_vorticity_confinement(
                 return;
             }
 
-            let r = pos_i - particles.predicted[j];
+            let r = grid.min_image_diff(pos_i, particles.predicted[j]);
             let r_len = r.length();
             if r_len < h && r_len > 1e-6 {
                 let vel_diff = particles.velocity[j] - vel_i;
+                // omega_i = sum v_ij x grad_j W, and grad_j W = -grad_i W
                 let grad = spiky_gradient(r, r_len, h);
-                omega += vel_diff.cross(grad);
+                omega += grad.cross(vel_diff);
             }
         });
 
@@ -75,7 +86,7 @@ pub fn apply_vorticity_confinement(
                 return;
             }
 
-            let r = pos_i - particles.predicted[j];
+            let r = grid.min_image_diff(pos_i, particles.predicted[j]);
             let r_len = r.length();
             if r_len < h && r_len > 1e-6 {
                 let grad = spiky_gradient(r, r_len, h);
@@ -84,10 +95,12 @@ pub fn apply_vorticity_confinement(
         });
 
         let eta_len = eta.length();
-        if eta_len < 1e-6 { continue; }
+        if eta_len < ETA_EPSILON { continue; }
 
-        let n = eta / eta_len;
-        forces[i] = n.cross(omega_i) * vorticity_strength;
+        let n = eta / eta_len.max(ETA_EPSILON);
+        let force = n.cross(omega_i) * vorticity_strength;
+        if !force.is_finite() { continue; }
+        forces[i] = force.clamp_length_max(max_vorticity_force);
     }
 
     // Apply forces as velocity change
@@ -1,5 +1,35 @@
 use glam::Vec3;
 
+/// Audio band energies and sparkle settings for `compute_audio_force`.
+#[derive(Clone, Copy, Debug)]
+pub struct AudioParams {
+    /// Bass frequency band energy \[0,1+\].
+    pub bass: f32,
+    /// Mid frequency band energy \[0,1+\].
+    pub mid: f32,
+    /// Treble frequency band energy \[0,1+\].
+    pub treble: f32,
+    /// Overall audio energy level \[0,1+\].
+    pub energy: f32,
+    /// Time-frequency multiplier for the treble sparkle (1 = default).
+    pub sparkle_frequency: f32,
+    /// Amplitude multiplier for the treble sparkle (0 = vertical lift only).
+    pub sparkle_amount: f32,
+}
+
+impl Default for AudioParams {
+    fn default() -> Self {
+        Self {
+            bass: 0.0,
+            mid: 0.0,
+            treble: 0.0,
+            energy: 0.0,
+            sparkle_frequency: 1.0,
+            sparkle_amount: 1.0,
+        }
+    }
+}
+
 /// Audio-reactive forces for equalizer mode.
 ///
 /// Applies bass outward push, mid-frequency swirl, treble vertical movement,
@@ -15,21 +45,24 @@ use glam::Vec3;
 /// * `id_hash` - Per-particle hash in \[0,1) for phase variation.
 /// * `layer_hash` - Secondary hash for layer-based variation.
 /// * `time` - Accumulated simulation time in seconds.
-/// * `audio_bass` - Bass frequency band energy \[0,1+\].
-/// * `audio_mid` - Mid frequency band energy \[0,1+\].
-/// * `audio_treble` - Treble frequency band energy \[0,1+\].
-/// * `audio_energy` - Overall audio energy level \[0,1+\].
+/// * `params` - Audio band energies and sparkle settings.
 pub fn compute_audio_force(
     pos: Vec3,
     desired: Vec3,
     id_hash: f32,
     layer_hash: f32,
     time: f32,
-    audio_bass: f32,
-    audio_mid: f32,
-    audio_treble: f32,
-    _audio_energy: f32,
+    params: &AudioParams,
 ) -> (Vec3, Vec3) {
+    let AudioParams {
+        bass: audio_bass,
+        mid: audio_mid,
+        treble: audio_treble,
+        sparkle_frequency,
+        sparkle_amount,
+        ..
+    } = *params;
+
     // The audio boost is applied multiplicatively to existing acceleration
     // by the caller. Here we compute the additive audio contributions.
     //
@@ -64,13 +97,15 @@ pub fn compute_audio_force(
 
     // Treble: vertical + sparkle effects
     acc.y += audio_treble * 3.8;
-    acc.z += (time * 5.0 + id_hash * std::f32::consts::TAU).sin()
-        * audio_treble * 2.5;
+    let sparkle_time = time * sparkle_frequency;
+    let sparkle_treble = audio_treble * sparkle_amount;
+    acc.z += (sparkle_time * 5.0 + id_hash * std::f32::consts::TAU).sin()
+        * sparkle_treble * 2.5;
     let sparkle = Vec3::new(
-        (time * 7.0 + id_hash * 12.56).sin(),
-        (time * 8.0 + layer_hash * 9.42).cos(),
-        (time * 6.0 + id_hash * 15.7).sin(),
-    ) * audio_treble * 1.8;
+        (sparkle_time * 7.0 + id_hash * 12.56).sin(),
+        (sparkle_time * 8.0 + layer_hash * 9.42).cos(),
+        (sparkle_time * 6.0 + id_hash * 15.7).sin(),
+    ) * sparkle_treble * 1.8;
     acc += sparkle;
 
     (acc, vel_add)
@@ -93,7 +128,8 @@ mod tests {
     fn test_audio_force_bass_pushes_outward() {
         let pos = Vec3::new(1.0, 0.0, 0.0);
         let desired = Vec3::ZERO;
-        let (acc, _vel) = compute_audio_force(pos, desired, 0.5, 0.3, 0.0, 1.0, 0.0, 0.0, 0.5);
+        let params = AudioParams { bass: 1.0, energy: 0.5, ..AudioParams::default() };
+        let (acc, _vel) = compute_audio_force(pos, desired, 0.5, 0.3, 0.0, &params);
         // Bass should push away from desired (outward)
         assert!(acc.x > 0.0, "bass should push particle outward from target");
     }
@@ -102,7 +138,7 @@ mod tests {
     fn test_audio_force_zero_audio_zero_force() {
         let pos = Vec3::new(1.0, 0.0, 0.0);
         let desired = Vec3::ZERO;
-        let (acc, vel) = compute_audio_force(pos, desired, 0.5, 0.3, 0.0, 0.0, 0.0, 0.0, 0.0);
+        let (acc, vel) = compute_audio_force(pos, desired, 0.5, 0.3, 0.0, &AudioParams::default());
         // With zero audio, forces should be minimal
         assert!(acc.length() < 0.01, "zero audio should produce near-zero force");
         assert!(vel.length() < 0.01);
@@ -122,7 +158,8 @@ mod tests {
     fn test_audio_force_mid_produces_swirl() {
         let pos = Vec3::new(1.0, 0.0, 0.0);
         let desired = Vec3::ZERO;
-        let (acc, _vel) = compute_audio_force(pos, desired, 0.5, 0.3, 0.0, 0.0, 1.0, 0.0, 0.5);
+        let params = AudioParams { mid: 1.0, energy: 0.5, ..AudioParams::default() };
+        let (acc, _vel) = compute_audio_force(pos, desired, 0.5, 0.3, 0.0, &params);
         // Mid should produce rotation/swirl forces
         assert!(acc.length() > 0.5, "mid-only should produce substantial force: {}", acc.length());
     }
@@ -131,9 +168,46 @@ mod tests {
     fn test_audio_force_treble_vertical() {
         let pos = Vec3::new(1.0, 0.0, 0.0);
         let desired = Vec3::ZERO;
-        let (acc, _vel) = compute_audio_force(pos, desired, 0.5, 0.3, 0.0, 0.0, 0.0, 1.0, 0.5);
+        let params = AudioParams { treble: 1.0, energy: 0.5, ..AudioParams::default() };
+        let (acc, _vel) = compute_audio_force(pos, desired, 0.5, 0.3, 0.0, &params);
         // Treble should add positive Y force (line: acc.y += audio_treble * 3.8),
         // partially offset by sparkle cos term; net Y should still be well above 1.0
         assert!(acc.y > 1.5, "treble should push upward: acc.y={}", acc.y);
     }
+
+    #[test]
+    fn test_audio_sparkle_amount_scales_jitter() {
+        let pos = Vec3::new(1.0, 0.0, 0.0);
+        let desired = Vec3::ZERO;
+        let times = [0.0, 0.37, 1.1, 2.9];
+        let treble = AudioParams { treble: 1.0, energy: 0.5, ..AudioParams::default() };
+
+        // No sparkle: treble is a steady vertical lift at every time and hash
+        for &t in &times {
+            for &(id_hash, layer_hash) in &[(0.1, 0.7), (0.5, 0.3), (0.9, 0.2)] {
+                let params = AudioParams { sparkle_amount: 0.0, ..treble };
+                let (acc, _vel) = compute_audio_force(pos, desired, id_hash, layer_hash, t, &params);
+                assert!(
+                    (acc - Vec3::new(0.0, 3.8, 0.0)).length() < 1e-5,
+                    "treble without sparkle should only lift: {:?}",
+                    acc
+                );
+            }
+        }
+
+        // Sparkle reintroduces time-varying jitter, scaled by the amount
+        let jitter = |amount: f32| {
+            let params = AudioParams { sparkle_amount: amount, ..treble };
+            let (a, _) = compute_audio_force(pos, desired, 0.5, 0.3, 0.37, &params);
+            (a - Vec3::new(0.0, 3.8, 0.0)).length()
+        };
+        assert!(jitter(1.0) > 0.5, "sparkle should add jitter: {}", jitter(1.0));
+        assert!((jitter(2.0) - 2.0 * jitter(1.0)).abs() < 1e-4);
+
+        // Frequency changes how fast the sparkle evolves over time
+        let slow = AudioParams { sparkle_frequency: 0.5, ..treble };
+        let (slow, _) = compute_audio_force(pos, desired, 0.5, 0.3, 1.1, &slow);
+        let (fast, _) = compute_audio_force(pos, desired, 0.5, 0.3, 0.55, &treble);
+        assert!((slow - fast).length() < 1e-5);
+    }
 }
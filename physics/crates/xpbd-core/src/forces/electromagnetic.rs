@@ -1,5 +1,7 @@
 use glam::Vec3;
 
+use crate::forces::Softening;
+
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
@@ -22,11 +24,10 @@ pub fn apply_electromagnetic_forces(
     count: usize,
     coulomb_k: f32,
     magnetic_field: Vec3,
-    softening: f32,
+    softening: Softening,
     max_range: f32,
     dt: f32,
 ) {
-    let softening_sq = softening * softening;
     let max_range_sq = max_range * max_range;
     let has_magnetic = magnetic_field.length_squared() > 1e-10;
 
@@ -63,7 +64,7 @@ pub fn apply_electromagnetic_forces(
                 continue;
             }
 
-            let dist_sq_soft = dist_sq + softening_sq;
+            let dist_sq_soft = softening.softened_dist_sq(dist_sq);
             let dist = dist_sq_soft.sqrt();
 
             let force_mag = coulomb_k * q_i * q_j / (dist_sq_soft * dist);
@@ -107,7 +108,7 @@ mod tests {
 
         apply_electromagnetic_forces(
             &positions, &mut velocities, &charges, 2,
-            1.0, Vec3::ZERO, 0.01, 10.0, 1.0,
+            1.0, Vec3::ZERO, Softening::constant(0.01), 10.0, 1.0,
         );
 
         // Like charges should repel: particle 0 pushed left, particle 1 pushed right
@@ -123,7 +124,7 @@ mod tests {
 
         apply_electromagnetic_forces(
             &positions, &mut velocities, &charges, 2,
-            1.0, Vec3::ZERO, 0.01, 10.0, 1.0,
+            1.0, Vec3::ZERO, Softening::constant(0.01), 10.0, 1.0,
         );
 
         // Unlike charges should attract: particle 0 pulled right, particle 1 pulled left
@@ -141,7 +142,7 @@ mod tests {
 
         apply_electromagnetic_forces(
             &positions, &mut velocities, &charges, 1,
-            0.0, Vec3::new(0.0, 0.0, 1.0), 0.01, 10.0, 1.0,
+            0.0, Vec3::new(0.0, 0.0, 1.0), Softening::constant(0.01), 10.0, 1.0,
         );
 
         // v × B = (1,0,0) × (0,0,1) = (0*1 - 0*0, 0*0 - 1*1, 1*0 - 0*0) = (0, -1, 0)
@@ -166,7 +167,7 @@ mod tests {
 
         apply_electromagnetic_forces(
             &positions, &mut velocities, &charges, 2,
-            1.0, Vec3::new(0.0, 0.0, 1.0), 0.01, 10.0, 1.0,
+            1.0, Vec3::new(0.0, 0.0, 1.0), Softening::constant(0.01), 10.0, 1.0,
         );
 
         // Particle 0 has zero charge, should not be affected
@@ -181,7 +182,7 @@ mod tests {
         let charges = vec![1.0, 1.0];
         apply_electromagnetic_forces(
             &positions, &mut velocities, &charges, 2,
-            1.0, Vec3::ZERO, 0.01, 3.0, 1.0,
+            1.0, Vec3::ZERO, Softening::constant(0.01), 3.0, 1.0,
         );
         assert_eq!(velocities[0], Vec3::ZERO, "Beyond max_range should have zero force");
         assert_eq!(velocities[1], Vec3::ZERO);
@@ -198,7 +199,7 @@ mod tests {
         let charges = vec![1.0, 1.0, 1.0]; // all positive
         apply_electromagnetic_forces(
             &positions, &mut velocities, &charges, 3,
-            1.0, Vec3::ZERO, 0.01, 10.0, 1.0,
+            1.0, Vec3::ZERO, Softening::constant(0.01), 10.0, 1.0,
         );
         // Middle particle should have ~zero net force (symmetric)
         assert!(velocities[1].x.abs() < 0.01,
@@ -215,7 +216,7 @@ mod tests {
         let charges: Vec<f32> = vec![];
         apply_electromagnetic_forces(
             &positions, &mut velocities, &charges, 0,
-            1.0, Vec3::ZERO, 0.01, 10.0, 1.0,
+            1.0, Vec3::ZERO, Softening::constant(0.01), 10.0, 1.0,
         );
         // Should not crash
     }
@@ -228,7 +229,7 @@ mod tests {
         let charges = vec![1.0];
         apply_electromagnetic_forces(
             &positions, &mut velocities, &charges, 1,
-            1.0, Vec3::new(0.0, 0.0, 1.0), 0.01, 10.0, 1.0,
+            1.0, Vec3::new(0.0, 0.0, 1.0), Softening::constant(0.01), 10.0, 1.0,
         );
         // Should only get Lorentz force (no Coulomb partner)
         // v x B = (1,0,0) x (0,0,1) = (0,-1,0)
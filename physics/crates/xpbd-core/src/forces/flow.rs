@@ -16,6 +16,10 @@ use crate::math::{curl, noise};
 /// * `time` - Accumulated simulation time in seconds.
 /// * `calm_factor` - Smoothstepped shape strength in \[0,1\]; higher values
 ///   damp the flow toward gentle swirl.
+/// * `vortex_strength` - Multiplier on the wandering vortex (0.35 = original,
+///   0 = pure curl noise).
+/// * `z_wave` - `[amplitude, frequency]` of the travelling z-axis sine wave
+///   (`[0.35, 1.0]` = original; amplitude 0 leaves only the z curl noise).
 /// * `structure` - Smoothstepped shape strength used by the caller to
 ///   compute `flow_scale`; included here only for the `mix` between base
 ///   flow and damped flow (calm vs active).
@@ -24,6 +28,8 @@ pub fn compute_flow_force(
     id_hash: f32,
     time: f32,
     calm_factor: f32,
+    vortex_strength: f32,
+    z_wave: [f32; 2],
 ) -> Vec3 {
     // --- Curl noise at three octaves ---
     // Large scale
@@ -57,8 +63,8 @@ pub fn compute_flow_force(
     let rel_x = pos.x - vortex_cx;
     let rel_y = pos.y - vortex_cy;
     let r2 = (rel_x * rel_x + rel_y * rel_y).max(0.15);
-    let vortex_x = -rel_y / r2 * 0.35;
-    let vortex_y = rel_x / r2 * 0.35;
+    let vortex_x = -rel_y / r2 * vortex_strength;
+    let vortex_y = rel_x / r2 * vortex_strength;
 
     // --- Blend base flow ---
     let base_flow_x = swirl_x * 0.55 + vortex_x * 0.35;
@@ -69,7 +75,8 @@ pub fn compute_flow_force(
 
     // --- Z-axis wave ---
     let mut flow_z = curl_z * 0.4;
-    flow_z += (time * 0.25 + pos.x * 1.2 + pos.y * 0.8).sin() * 0.35;
+    let [wave_amplitude, wave_frequency] = z_wave;
+    flow_z += ((time * 0.25 + pos.x * 1.2 + pos.y * 0.8) * wave_frequency).sin() * wave_amplitude;
 
     Vec3::new(damped_flow_x, damped_flow_y, flow_z)
 }
@@ -84,6 +91,8 @@ fn mix_f32(a: f32, b: f32, t: f32) -> f32 {
 mod tests {
     use super::*;
 
+    const Z_WAVE: [f32; 2] = [0.35, 1.0];
+
     #[test]
     fn test_flow_force_nonzero() {
         let force = compute_flow_force(
@@ -91,14 +100,16 @@ mod tests {
             0.5,
             1.0,
             0.0,
+            0.35,
+            Z_WAVE,
         );
         assert!(force.length() > 0.0, "flow force should be nonzero");
     }
 
     #[test]
     fn test_flow_force_calm_reduces_magnitude() {
-        let force_active = compute_flow_force(Vec3::new(1.0, 0.5, 0.3), 0.5, 1.0, 0.0);
-        let force_calm = compute_flow_force(Vec3::new(1.0, 0.5, 0.3), 0.5, 1.0, 1.0);
+        let force_active = compute_flow_force(Vec3::new(1.0, 0.5, 0.3), 0.5, 1.0, 0.0, 0.35, Z_WAVE);
+        let force_calm = compute_flow_force(Vec3::new(1.0, 0.5, 0.3), 0.5, 1.0, 1.0, 0.35, Z_WAVE);
         // Calm factor should reduce the flow force magnitude
         // (not necessarily strictly less due to wave component, but generally)
         assert!(
@@ -106,4 +117,50 @@ mod tests {
             "calm force should not be much larger"
         );
     }
+
+    #[test]
+    fn test_vortex_strength_scales_tangential_bias() {
+        // Ring around the vortex center at t = 0, which sits at (0, 0.4)
+        let center = Vec3::new(0.0, 0.4, 0.0);
+        let tangential_bias = |strength: f32| {
+            let mut sum = 0.0;
+            for k in 0..64 {
+                let angle = k as f32 / 64.0 * std::f32::consts::TAU;
+                let offset = Vec3::new(angle.cos(), angle.sin(), 0.0) * 0.3;
+                let tangent = Vec3::new(-angle.sin(), angle.cos(), 0.0);
+                sum += compute_flow_force(center + offset, 0.0, 0.0, 0.0, strength, Z_WAVE).dot(tangent);
+            }
+            sum / 64.0
+        };
+
+        let swirl = tangential_bias(0.35);
+        let none = tangential_bias(0.0);
+        assert!(swirl > 0.2, "default vortex should swirl counter-clockwise: {}", swirl);
+        assert!(none.abs() < 0.1, "strength 0 should leave no vortex bias: {}", none);
+
+        // Curl noise remains, and the default adds exactly the original vortex term
+        let pos = Vec3::new(0.3, 0.7, 0.2);
+        let pure = compute_flow_force(pos, 0.5, 0.0, 0.0, 0.0, Z_WAVE);
+        assert!(pure.truncate().length() > 0.0, "curl noise should remain");
+        let rel = pos.truncate() - center.truncate();
+        let r2 = rel.length_squared().max(0.15);
+        let expected_vortex = Vec3::new(-rel.y, rel.x, 0.0) / r2 * 0.35 * 0.35;
+        let diff = compute_flow_force(pos, 0.5, 0.0, 0.0, 0.35, Z_WAVE) - pure;
+        assert!((diff - expected_vortex).length() < 1e-5, "diff={:?}", diff);
+    }
+
+    #[test]
+    fn test_z_wave_amplitude_zero_leaves_curl_z() {
+        let pos = Vec3::new(0.4, -0.2, 0.1);
+        let time = 1.7;
+        let flat = compute_flow_force(pos, 0.5, time, 0.0, 0.35, [0.0, 1.0]);
+        let curl_z = (noise(pos.x * 1.5 + time * 0.15, pos.y * 1.5 + time * 0.15) - 0.5) * 0.4;
+        assert!((flat.z - curl_z).abs() < 1e-6, "only curl z should remain: {}", flat.z);
+        assert!(flat.z.abs() > 1e-4, "curl z should be nonzero here");
+
+        let wavy = compute_flow_force(pos, 0.5, time, 0.0, 0.35, Z_WAVE);
+        let wave = (time * 0.25 + pos.x * 1.2 + pos.y * 0.8).sin() * 0.35;
+        assert!((wavy.z - flat.z - wave).abs() < 1e-6);
+        assert_eq!(wavy.truncate(), flat.truncate(), "z-wave must not touch x/y");
+    }
 }
@@ -1,5 +1,7 @@
 use glam::Vec3;
 
+use crate::forces::Softening;
+
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
@@ -163,7 +165,7 @@ fn traverse_octree(
     pos: Vec3,
     particle_idx: u32,
     theta: f32,
-    softening_sq: f32,
+    softening: Softening,
     g: f32,
 ) -> Vec3 {
     let mut acc = Vec3::ZERO;
@@ -182,7 +184,7 @@ fn traverse_octree(
         }
 
         let diff = node.center_of_mass - pos;
-        let dist_sq = diff.length_squared() + softening_sq;
+        let dist_sq = softening.softened_dist_sq(diff.length_squared());
 
         // Check if this is a leaf or if the opening angle criterion is satisfied
         let is_leaf = node.particle_idx.is_some();
@@ -208,6 +210,32 @@ fn traverse_octree(
     acc
 }
 
+/// Collect the bounding box and total mass of every internal octree node.
+///
+/// Builds the same octree `apply_nbody_gravity` uses and returns
+/// `(bbox_min, bbox_max, total_mass)` in depth-first pre-order, so the root
+/// comes first and every cell appears before its descendants. Leaves (single
+/// particles) are omitted. Intended for debug visualization.
+pub fn octree_cells(positions: &[Vec3], count: usize) -> Vec<(Vec3, Vec3, f32)> {
+    let root = match build_octree(positions, count) {
+        Some(tree) => tree,
+        None => return Vec::new(),
+    };
+
+    let mut cells = Vec::new();
+    let mut stack: Vec<&OctreeNode> = vec![&root];
+    while let Some(node) = stack.pop() {
+        if node.particle_idx.is_some() {
+            continue;
+        }
+        cells.push((node.bbox_min, node.bbox_max, node.total_mass));
+        for child in node.children.iter().rev().flatten() {
+            stack.push(child);
+        }
+    }
+    cells
+}
+
 /// Apply Barnes-Hut N-body gravitational forces to all particles.
 ///
 /// Builds an octree from particle positions, then traverses it for each
@@ -215,14 +243,14 @@ fn traverse_octree(
 ///
 /// Parameters from config:
 /// - `g`: Gravitational constant
-/// - `softening`: Softening parameter (prevents singularity at r=0)
+/// - `softening`: Softening length, constant or distance-dependent (prevents singularity at r=0)
 /// - `theta`: Barnes-Hut opening angle (0.0 = exact, higher = faster but less accurate)
 pub fn apply_nbody_gravity(
     positions: &[Vec3],
     velocities: &mut [Vec3],
     count: usize,
     g: f32,
-    softening: f32,
+    softening: Softening,
     theta: f32,
     dt: f32,
 ) {
@@ -231,14 +259,12 @@ pub fn apply_nbody_gravity(
         None => return,
     };
 
-    let softening_sq = softening * softening;
-
     #[cfg(feature = "parallel")]
     {
         // Compute accelerations in parallel, then apply
         let accels: Vec<Vec3> = (0..count)
             .into_par_iter()
-            .map(|i| traverse_octree(&octree, positions[i], i as u32, theta, softening_sq, g))
+            .map(|i| traverse_octree(&octree, positions[i], i as u32, theta, softening, g))
             .collect();
         for i in 0..count {
             velocities[i] += accels[i] * dt;
@@ -248,7 +274,7 @@ pub fn apply_nbody_gravity(
     #[cfg(not(feature = "parallel"))]
     {
         for i in 0..count {
-            let acc = traverse_octree(&octree, positions[i], i as u32, theta, softening_sq, g);
+            let acc = traverse_octree(&octree, positions[i], i as u32, theta, softening, g);
             velocities[i] += acc * dt;
         }
     }
@@ -264,7 +290,7 @@ mod tests {
         let positions = vec![Vec3::new(-0.5, 0.0, 0.0), Vec3::new(0.5, 0.0, 0.0)];
         let mut velocities = vec![Vec3::ZERO; 2];
 
-        apply_nbody_gravity(&positions, &mut velocities, 2, 1.0, 0.01, 0.0, 1.0);
+        apply_nbody_gravity(&positions, &mut velocities, 2, 1.0, Softening::constant(0.01), 0.0, 1.0);
 
         // Particle 0 should move toward particle 1 (positive x)
         assert!(velocities[0].x > 0.0, "Particle 0 should be attracted rightward");
@@ -285,8 +311,8 @@ mod tests {
         let mut vel_near = vec![Vec3::ZERO; 2];
         let mut vel_far = vec![Vec3::ZERO; 2];
 
-        apply_nbody_gravity(&positions_near, &mut vel_near, 2, 1.0, 0.0, 0.0, 1.0);
-        apply_nbody_gravity(&positions_far, &mut vel_far, 2, 1.0, 0.0, 0.0, 1.0);
+        apply_nbody_gravity(&positions_near, &mut vel_near, 2, 1.0, Softening::constant(0.0), 0.0, 1.0);
+        apply_nbody_gravity(&positions_far, &mut vel_far, 2, 1.0, Softening::constant(0.0), 0.0, 1.0);
 
         let force_near = vel_near[0].x.abs();
         let force_far = vel_far[0].x.abs();
@@ -316,8 +342,8 @@ mod tests {
         let mut vel_exact = vec![Vec3::ZERO; 5];
         let mut vel_approx = vec![Vec3::ZERO; 5];
 
-        apply_nbody_gravity(&positions, &mut vel_exact, 5, 1.0, 0.01, 0.0, 1.0);
-        apply_nbody_gravity(&positions, &mut vel_approx, 5, 1.0, 0.01, 0.7, 1.0);
+        apply_nbody_gravity(&positions, &mut vel_exact, 5, 1.0, Softening::constant(0.01), 0.0, 1.0);
+        apply_nbody_gravity(&positions, &mut vel_approx, 5, 1.0, Softening::constant(0.01), 0.7, 1.0);
 
         // Results should be similar (within ~10% for this configuration)
         let exact_mag = vel_exact[0].length();
@@ -334,7 +360,7 @@ mod tests {
     fn test_zero_particles_no_crash() {
         let positions: Vec<Vec3> = vec![];
         let mut velocities: Vec<Vec3> = vec![];
-        apply_nbody_gravity(&positions, &mut velocities, 0, 1.0, 0.01, 0.7, 1.0);
+        apply_nbody_gravity(&positions, &mut velocities, 0, 1.0, Softening::constant(0.01), 0.7, 1.0);
         // Should not crash
     }
 
@@ -342,16 +368,78 @@ mod tests {
     fn test_single_particle_no_self_gravity() {
         let positions = vec![Vec3::new(1.0, 2.0, 3.0)];
         let mut velocities = vec![Vec3::ZERO];
-        apply_nbody_gravity(&positions, &mut velocities, 1, 1.0, 0.01, 0.7, 1.0);
+        apply_nbody_gravity(&positions, &mut velocities, 1, 1.0, Softening::constant(0.01), 0.7, 1.0);
         assert_eq!(velocities[0], Vec3::ZERO, "Single particle should have zero gravity");
     }
 
+    #[test]
+    fn test_octree_cells_form_hierarchy() {
+        let positions: Vec<Vec3> = (0..12)
+            .map(|i| {
+                let t = i as f32;
+                Vec3::new((t * 1.7).sin(), (t * 0.9).cos(), t * 0.1 - 0.6)
+            })
+            .collect();
+        let cells = octree_cells(&positions, positions.len());
+
+        assert!(cells.len() > 1, "cluster should subdivide");
+        let (_, _, root_mass) = cells[0];
+        assert!((root_mass - positions.len() as f32).abs() < 1e-4);
+
+        let contains = |outer: &(Vec3, Vec3, f32), inner: &(Vec3, Vec3, f32)| {
+            outer.0.cmple(inner.0).all() && inner.1.cmple(outer.1).all()
+        };
+        for (k, cell) in cells.iter().enumerate().skip(1) {
+            let parent = cells[..k]
+                .iter()
+                .rev()
+                .find(|c| contains(c, cell))
+                .expect("every cell should nest inside an earlier cell");
+            assert!(parent.2 >= cell.2, "parent mass must cover child mass");
+        }
+    }
+
+    #[test]
+    fn test_adaptive_softening_bounds_close_pairs() {
+        let pair_accel = |separation: f32, softening: Softening| {
+            let positions = vec![Vec3::ZERO, Vec3::new(separation, 0.0, 0.0)];
+            let mut velocities = vec![Vec3::ZERO; 2];
+            apply_nbody_gravity(&positions, &mut velocities, 2, 1.0, softening, 0.0, 1.0);
+            velocities[0].x
+        };
+        let constant = Softening::constant(0.01);
+        let adaptive = Softening::adaptive(0.01, 0.05);
+
+        // Close pair: constant softening lets the force blow up, adaptive keeps it bounded
+        let close_constant = pair_accel(1e-3, constant);
+        let close_adaptive = pair_accel(1e-3, adaptive);
+        assert!(close_adaptive.is_finite() && close_adaptive > 0.0);
+        assert!(
+            close_adaptive < close_constant * 0.01,
+            "adaptive softening should tame close encounters: adaptive={}, constant={}",
+            close_adaptive,
+            close_constant
+        );
+        let closer_adaptive = pair_accel(1e-5, adaptive);
+        assert!(closer_adaptive <= close_adaptive, "force must not grow as r -> 0");
+
+        // Distant pair: practically identical
+        let far_constant = pair_accel(2.0, constant);
+        let far_adaptive = pair_accel(2.0, adaptive);
+        assert!(
+            (far_constant - far_adaptive).abs() / far_constant < 1e-4,
+            "distant pairs should be unaffected: constant={}, adaptive={}",
+            far_constant,
+            far_adaptive
+        );
+    }
+
     #[test]
     fn test_coincident_particles_no_crash() {
         // Two particles at exact same position — tests max depth guard
         let positions = vec![Vec3::ZERO, Vec3::ZERO];
         let mut velocities = vec![Vec3::ZERO; 2];
-        apply_nbody_gravity(&positions, &mut velocities, 2, 1.0, 0.01, 0.7, 1.0);
+        apply_nbody_gravity(&positions, &mut velocities, 2, 1.0, Softening::constant(0.01), 0.7, 1.0);
         // Should not crash or produce NaN
         assert!(!velocities[0].x.is_nan(), "Coincident particles should not produce NaN");
     }
@@ -3,3 +3,42 @@ pub mod electromagnetic;
 pub mod flow;
 pub mod gravity;
 pub mod pointer;
+
+use glam::Vec3;
+
+/// User-supplied per-particle force field: `(position, velocity, time) -> acceleration`.
+pub type ForceField = Box<dyn Fn(Vec3, Vec3, f32) -> Vec3>;
+
+/// Softening length for pairwise inverse-square forces.
+///
+/// With `adaptive_k == 0` the softening is the constant `base`. Otherwise it
+/// grows at close range as `base * (1 + adaptive_k / r)`, keeping near
+/// encounters bounded while leaving distant pairs essentially untouched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Softening {
+    pub base: f32,
+    pub adaptive_k: f32,
+}
+
+impl Softening {
+    /// Constant softening `base` at every distance.
+    pub fn constant(base: f32) -> Self {
+        Self { base, adaptive_k: 0.0 }
+    }
+
+    /// Distance-dependent softening `base * (1 + k / r)`.
+    pub fn adaptive(base: f32, k: f32) -> Self {
+        Self { base, adaptive_k: k }
+    }
+
+    /// Softened squared distance `r^2 + softening(r)^2` for a pair at `r^2 = dist_sq`.
+    #[inline]
+    pub fn softened_dist_sq(&self, dist_sq: f32) -> f32 {
+        let s = if self.adaptive_k > 0.0 {
+            self.base * (1.0 + self.adaptive_k / dist_sq.sqrt().max(1e-6))
+        } else {
+            self.base
+        };
+        dist_sq + s * s
+    }
+}
@@ -11,6 +11,10 @@ pub struct PointerParams {
     pub pressing: bool,
     pub pulse: bool,
     pub view_dir: Vec3,
+    /// Tangential swirl magnitude added by the repel mode (mode 1).
+    pub repel_swirl: f32,
+    /// Velocity scale applied each step by the repel mode (1.0 = no damping).
+    pub repel_damping: f32,
 }
 
 impl Default for PointerParams {
@@ -24,6 +28,8 @@ impl Default for PointerParams {
             pressing: false,
             pulse: false,
             view_dir: Vec3::NEG_Z,
+            repel_swirl: 2.8,
+            repel_damping: 0.97,
         }
     }
 }
@@ -98,9 +104,9 @@ pub fn compute_pointer_force(
         1 => {
             // Repel
             acc -= dir_p * base * 4.5;
-            acc += Vec3::new(dir_p.y, -dir_p.x, 0.3) * base * 2.8;
+            acc += Vec3::new(dir_p.y, -dir_p.x, 0.3) * base * params.repel_swirl;
             vel_add -= dir_p * base * 1.2;
-            vel_scale = 0.97;
+            vel_scale = params.repel_damping;
         }
         2 | 3 => {
             // Vortex (2=left, 3=right)
@@ -1,5 +1,11 @@
 use glam::Vec3;
 
+/// `update` falls back to a full rebuild once more than `count / N` particles changed cell.
+const INCREMENTAL_REBUILD_DIVISOR: usize = 8;
+
+/// `particle_hashes` marker for particles left out of the last build.
+const EXCLUDED: u32 = u32::MAX;
+
 /// Uniform spatial hash grid for O(1) neighbor queries.
 ///
 /// Uses counting sort for O(N) construction: count particles per cell -> prefix sum -> scatter.
@@ -16,6 +22,14 @@ pub struct SpatialHashGrid {
     sorted_indices: Vec<u32>,
     /// Cell hash per particle (used during build)
     particle_hashes: Vec<u32>,
+    /// Particle count indexed by the last `build` (`update` requires it to match)
+    indexed_count: usize,
+    /// Entries of `sorted_indices` filled by the last `build`/`update`
+    sorted_len: usize,
+    /// Half extents of the periodic domain (None = unbounded, no wrapping)
+    periodic_half_extents: Option<Vec3>,
+    /// Number of cells per axis across the periodic domain
+    periodic_cells: [i32; 3],
 }
 
 impl SpatialHashGrid {
@@ -31,12 +45,106 @@ impl SpatialHashGrid {
             cell_start: vec![0u32; table_size],
             sorted_indices: vec![0u32; max_particles],
             particle_hashes: vec![0u32; max_particles],
+            indexed_count: 0,
+            sorted_len: 0,
+            periodic_half_extents: None,
+            periodic_cells: [1; 3],
+        }
+    }
+
+    /// Resize the per-particle buffers for a new maximum particle count.
+    ///
+    /// The next `update` falls back to a full `build`.
+    pub fn resize(&mut self, max_particles: usize) {
+        self.sorted_indices.resize(max_particles, 0);
+        self.particle_hashes.resize(max_particles, 0);
+        self.indexed_count = usize::MAX;
+    }
+
+    /// Reallocate the hash table with `table_size` buckets (at least 1).
+    ///
+    /// Larger tables mean fewer unrelated cells sharing a bucket in dense
+    /// scenes. Stored hashes are stale afterwards: call `build` before querying
+    /// (the next `update` falls back to a full `build`).
+    pub fn set_table_size(&mut self, table_size: usize) {
+        let table_size = table_size.max(1);
+        self.table_size = table_size;
+        self.cell_count = vec![0u32; table_size];
+        self.cell_start = vec![0u32; table_size];
+        self.indexed_count = usize::MAX;
+    }
+
+    /// Number of buckets in the hash table.
+    pub fn table_size(&self) -> usize {
+        self.table_size
+    }
+
+    /// Edge length of one grid cell.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Change the cell edge length (keeping the hash table). Like
+    /// `set_table_size`, stored hashes are stale afterwards: call `build`
+    /// before querying.
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        self.cell_size = cell_size;
+        self.inv_cell_size = 1.0 / cell_size;
+        self.indexed_count = usize::MAX;
+        self.set_periodic(self.periodic_half_extents);
+    }
+
+    /// Particle indices grouped by cell bucket, as of the last `build`/`update`.
+    pub fn sorted_indices(&self) -> &[u32] {
+        &self.sorted_indices[..self.sorted_len]
+    }
+
+    /// Enable or disable periodic (toroidal) wrapping of the grid.
+    ///
+    /// With `Some(half_extents)`, the domain `[-half_extents, half_extents]` is
+    /// split into whole cells at least `cell_size` wide and cell coordinates wrap
+    /// around, so particles near opposite faces are reported as neighbors.
+    /// Callers must then measure distances with `min_image_diff`.
+    pub fn set_periodic(&mut self, half_extents: Option<Vec3>) {
+        self.periodic_half_extents = half_extents;
+        if let Some(half) = half_extents {
+            let cells = |extent: f32| ((2.0 * extent * self.inv_cell_size).floor() as i32).max(1);
+            self.periodic_cells = [cells(half.x), cells(half.y), cells(half.z)];
+        }
+    }
+
+    /// Displacement `a - b`, using the minimum-image convention when periodic.
+    #[inline]
+    pub fn min_image_diff(&self, a: Vec3, b: Vec3) -> Vec3 {
+        let d = a - b;
+        match self.periodic_half_extents {
+            Some(half) => {
+                let period = half * 2.0;
+                d - period * (d / period).round()
+            }
+            None => d,
         }
     }
 
     /// Build the grid from current positions.
     /// O(N) using counting sort.
     pub fn build(&mut self, positions: &[Vec3], count: usize) {
+        self.build_filtered(positions, count, |_| true);
+    }
+
+    /// Build the grid from the particles whose `live` flag is set; the others
+    /// (e.g. killed particles) are never reported by queries.
+    ///
+    /// `update` indexes every particle, so after a build that left particles
+    /// out, the next `update` falls back to a full `build`.
+    pub fn build_masked(&mut self, positions: &[Vec3], count: usize, live: &[bool]) {
+        self.build_filtered(positions, count, |i| live[i]);
+        if self.sorted_len != count {
+            self.indexed_count = usize::MAX;
+        }
+    }
+
+    fn build_filtered<F: Fn(usize) -> bool>(&mut self, positions: &[Vec3], count: usize, include: F) {
         // 1. Clear cell_count
         for v in self.cell_count.iter_mut() {
             *v = 0;
@@ -44,6 +152,10 @@ impl SpatialHashGrid {
 
         // 2. For each particle, compute cell hash, store it, and increment count
         for i in 0..count {
+            if !include(i) {
+                self.particle_hashes[i] = EXCLUDED;
+                continue;
+            }
             let (cx, cy, cz) = self.cell_coords(positions[i]);
             let h = self.hash_cell(cx, cy, cz);
             self.particle_hashes[i] = h as u32;
@@ -62,31 +174,202 @@ impl SpatialHashGrid {
         }
 
         // 5. Scatter particles into sorted_indices
+        let mut filled = 0;
         for i in 0..count {
+            if self.particle_hashes[i] == EXCLUDED {
+                continue;
+            }
             let h = self.particle_hashes[i] as usize;
             let idx = self.cell_start[h] + self.cell_count[h];
             self.sorted_indices[idx as usize] = i as u32;
             self.cell_count[h] += 1;
+            filled += 1;
         }
+        self.indexed_count = count;
+        self.sorted_len = filled;
+    }
+
+    /// Incrementally refresh the grid after particles moved.
+    ///
+    /// Only particles whose cell hash changed since the last `build`/`update`
+    /// are relocated, so scenes where few particles cross cell borders avoid
+    /// the O(table_size) rebuild. Falls back to `build` when the particle
+    /// count differs, too many particles changed cell, or the moves would
+    /// slide across more buckets in total than a full build touches
+    /// (`table_size + count`). Queries return the same neighbor sets as after
+    /// a full `build` (order within a cell may differ).
+    pub fn update(&mut self, positions: &[Vec3], count: usize) {
+        if count != self.indexed_count {
+            self.build(positions, count);
+            return;
+        }
+
+        let limit = count / INCREMENTAL_REBUILD_DIVISOR;
+        let budget = self.table_size + count;
+        let mut slide = 0;
+        let mut moved: Vec<(u32, usize, usize)> = Vec::new();
+        for i in 0..count {
+            let (cx, cy, cz) = self.cell_coords(positions[i]);
+            let h = self.hash_cell(cx, cy, cz);
+            let old = self.particle_hashes[i] as usize;
+            if h != old {
+                // Each move costs one step per bucket between `old` and `h`
+                slide += old.abs_diff(h);
+                if moved.len() >= limit || slide > budget {
+                    self.build(positions, count);
+                    return;
+                }
+                moved.push((i as u32, old, h));
+            }
+        }
+
+        for (i, from, to) in moved {
+            self.move_particle(i, from, to);
+            self.particle_hashes[i as usize] = to as u32;
+        }
+    }
+
+    /// Move particle `i` from bucket `from` to bucket `to`, keeping buckets
+    /// contiguous in `sorted_indices` by sliding the freed slot across the
+    /// buckets in between (one element per non-empty bucket).
+    fn move_particle(&mut self, i: u32, from: usize, to: usize) {
+        let start = self.cell_start[from] as usize;
+        let end = start + self.cell_count[from] as usize;
+        let k = match self.sorted_indices[start..end].iter().position(|&p| p == i) {
+            Some(k) => start + k,
+            None => return,
+        };
+
+        if from < to {
+            // Free the last slot of `from`, then slide it forward to the front of `to`
+            self.sorted_indices.swap(k, end - 1);
+            self.cell_count[from] -= 1;
+            let mut hole = end - 1;
+            for b in from + 1..to {
+                let n = self.cell_count[b] as usize;
+                if n > 0 {
+                    let last = self.cell_start[b] as usize + n - 1;
+                    self.sorted_indices[hole] = self.sorted_indices[last];
+                    hole = last;
+                }
+                self.cell_start[b] -= 1;
+            }
+            self.cell_start[to] -= 1;
+            self.sorted_indices[hole] = i;
+        } else {
+            // Free the first slot of `from`, then slide it backward to the end of `to`
+            self.sorted_indices.swap(k, start);
+            self.cell_start[from] += 1;
+            self.cell_count[from] -= 1;
+            let mut hole = start;
+            for b in (to + 1..from).rev() {
+                let first = self.cell_start[b] as usize;
+                if self.cell_count[b] > 0 {
+                    self.sorted_indices[hole] = self.sorted_indices[first];
+                    hole = first;
+                }
+                self.cell_start[b] += 1;
+            }
+            self.sorted_indices[hole] = i;
+        }
+        self.cell_count[to] += 1;
     }
 
     /// Query all neighbors within the given position's cell and its 26 neighbors (3x3x3).
     /// Calls `callback(particle_index)` for each particle found in those cells.
     /// The caller is responsible for distance checks.
+    /// When periodic, neighbor cells wrap across the domain faces.
+    ///
+    /// Each particle is reported at most once, even when several of the 27
+    /// cells share a hash bucket, so kernel sums don't depend on the table size.
     pub fn query_neighbors<F: FnMut(u32)>(&self, pos: Vec3, mut callback: F) {
         let (cx, cy, cz) = self.cell_coords(pos);
-        for dx in -1..=1_i32 {
-            for dy in -1..=1_i32 {
-                for dz in -1..=1_i32 {
-                    let h = self.hash_cell(cx + dx, cy + dy, cz + dz);
-                    let start = self.cell_start[h] as usize;
-                    let end = start + self.cell_count[h] as usize;
-                    for idx in start..end {
-                        callback(self.sorted_indices[idx]);
+        let mut buckets = [0usize; 27];
+        let mut len = 0;
+        let mut add = |x: i32, y: i32, z: i32| {
+            let h = self.hash_cell(x, y, z);
+            if !buckets[..len].contains(&h) {
+                buckets[len] = h;
+                len += 1;
+            }
+        };
+        if self.periodic_half_extents.is_some() {
+            let [nx, ny, nz] = self.periodic_cells;
+            for x in Self::wrapped_range(cx, nx) {
+                for y in Self::wrapped_range(cy, ny) {
+                    for z in Self::wrapped_range(cz, nz) {
+                        add(x, y, z);
+                    }
+                }
+            }
+        } else {
+            for dx in -1..=1_i32 {
+                for dy in -1..=1_i32 {
+                    for dz in -1..=1_i32 {
+                        add(cx + dx, cy + dy, cz + dz);
                     }
                 }
             }
         }
+        for &h in &buckets[..len] {
+            self.visit_bucket(h, &mut callback);
+        }
+    }
+
+    /// Query every particle in the cells overlapping a sphere of `radius` around `pos`.
+    ///
+    /// Like `query_neighbors`, but the cell range grows with `radius`, so it also
+    /// works for radii larger than a cell. The caller is responsible for distance
+    /// checks; a particle may be reported more than once if cells share a hash bucket.
+    pub fn query_radius<F: FnMut(u32)>(&self, pos: Vec3, radius: f32, mut callback: F) {
+        let (cx, cy, cz) = self.cell_coords(pos);
+        let reach = ((radius * self.inv_cell_size).ceil() as i32).max(1);
+        if self.periodic_half_extents.is_some() {
+            let [nx, ny, nz] = self.periodic_cells;
+            let axis = |c: i32, n: i32| {
+                let (lo, hi) = if 2 * reach + 1 < n { (c - reach, c + reach) } else { (0, n - 1) };
+                (lo..=hi).map(move |k| k.rem_euclid(n))
+            };
+            for x in axis(cx, nx) {
+                for y in axis(cy, ny) {
+                    for z in axis(cz, nz) {
+                        self.visit_cell(x, y, z, &mut callback);
+                    }
+                }
+            }
+            return;
+        }
+        for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                for dz in -reach..=reach {
+                    self.visit_cell(cx + dx, cy + dy, cz + dz, &mut callback);
+                }
+            }
+        }
+    }
+
+    /// Call `callback` for every particle stored in cell `(cx, cy, cz)`.
+    #[inline]
+    fn visit_cell<F: FnMut(u32)>(&self, cx: i32, cy: i32, cz: i32, callback: &mut F) {
+        self.visit_bucket(self.hash_cell(cx, cy, cz), callback);
+    }
+
+    /// Report every particle stored in hash bucket `h`.
+    #[inline]
+    fn visit_bucket<F: FnMut(u32)>(&self, h: usize, callback: &mut F) {
+        let start = self.cell_start[h] as usize;
+        let end = start + self.cell_count[h] as usize;
+        for idx in start..end {
+            callback(self.sorted_indices[idx]);
+        }
+    }
+
+    /// Distinct wrapped cell coordinates within one cell of `c` along an axis
+    /// with `n` periodic cells (fewer than 3 cells means every cell is a neighbor).
+    #[inline]
+    fn wrapped_range(c: i32, n: i32) -> impl Iterator<Item = i32> {
+        let (lo, hi) = if n >= 3 { (c - 1, c + 1) } else { (0, n - 1) };
+        (lo..=hi).map(move |k| k.rem_euclid(n))
     }
 
     /// Hash function: cell coords -> table index
@@ -99,9 +382,16 @@ impl SpatialHashGrid {
         (h as usize) % self.table_size
     }
 
-    /// Convert world position to cell coordinates
+    /// Convert world position to cell coordinates (wrapped when periodic)
     #[inline]
     fn cell_coords(&self, pos: Vec3) -> (i32, i32, i32) {
+        if let Some(half) = self.periodic_half_extents {
+            let [nx, ny, nz] = self.periodic_cells;
+            let wrap = |p: f32, h: f32, n: i32| {
+                (((p + h) / (2.0 * h) * n as f32).floor() as i32).rem_euclid(n)
+            };
+            return (wrap(pos.x, half.x, nx), wrap(pos.y, half.y, ny), wrap(pos.z, half.z, nz));
+        }
         (
             (pos.x * self.inv_cell_size).floor() as i32,
             (pos.y * self.inv_cell_size).floor() as i32,
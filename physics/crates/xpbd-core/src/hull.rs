@@ -0,0 +1,131 @@
+use glam::Vec3;
+
+/// 3D convex hull of `points` as `(vertices, triangles)`.
+///
+/// Triangles index into `vertices` and wind counter-clockwise seen from
+/// outside, so their normals point outward. Only points on the hull surface
+/// are returned; coplanar faces (e.g. the sides of a cube) are split into
+/// triangles. Degenerate inputs -- fewer than four points not all in one
+/// plane -- give an empty hull.
+///
+/// Incremental construction, O(n^2) in the worst case; meant for the particle
+/// counts of individual rigid bodies.
+pub fn convex_hull(points: &[Vec3]) -> (Vec<Vec3>, Vec<[u32; 3]>) {
+    let empty = (Vec::new(), Vec::new());
+    let Some((min, max)) = bounds(points) else {
+        return empty;
+    };
+    let eps = (max - min).max_element() * 1e-5;
+    if eps <= 0.0 {
+        return empty;
+    }
+    let Some(tetra) = initial_tetrahedron(points, eps) else {
+        return empty;
+    };
+
+    let interior = tetra.iter().map(|&i| points[i]).sum::<Vec3>() / 4.0;
+    let mut faces: Vec<[usize; 3]> = Vec::new();
+    for [a, b, c] in [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]] {
+        faces.push(oriented_face(points, [tetra[a], tetra[b], tetra[c]], interior));
+    }
+
+    for (p, &point) in points.iter().enumerate() {
+        if tetra.contains(&p) {
+            continue;
+        }
+        let visible: Vec<bool> = faces.iter().map(|&f| sees_face(points, f, point, eps)).collect();
+        if !visible.contains(&true) {
+            continue; // inside (or on) the current hull
+        }
+
+        // Horizon: edges of visible faces whose neighbor across the edge is hidden
+        let visible_edges: Vec<(usize, usize)> = faces
+            .iter()
+            .zip(&visible)
+            .filter(|&(_, &v)| v)
+            .flat_map(|(&[a, b, c], _)| [(a, b), (b, c), (c, a)])
+            .collect();
+        let mut kept: Vec<[usize; 3]> = faces
+            .iter()
+            .zip(&visible)
+            .filter(|&(_, &v)| !v)
+            .map(|(&f, _)| f)
+            .collect();
+        for &(a, b) in &visible_edges {
+            if !visible_edges.contains(&(b, a)) {
+                kept.push([a, b, p]);
+            }
+        }
+        faces = kept;
+    }
+
+    // Compact to the points the hull uses
+    let mut remap = vec![u32::MAX; points.len()];
+    let mut vertices = Vec::new();
+    let triangles = faces
+        .iter()
+        .map(|face| {
+            face.map(|i| {
+                if remap[i] == u32::MAX {
+                    remap[i] = vertices.len() as u32;
+                    vertices.push(points[i]);
+                }
+                remap[i]
+            })
+        })
+        .collect();
+    (vertices, triangles)
+}
+
+/// Axis-aligned bounds of `points`, or `None` if empty.
+fn bounds(points: &[Vec3]) -> Option<(Vec3, Vec3)> {
+    let first = *points.first()?;
+    Some(points.iter().fold((first, first), |(lo, hi), &p| (lo.min(p), hi.max(p))))
+}
+
+/// Indices of four points spanning a non-degenerate tetrahedron: the extreme
+/// point along x, the point farthest from it, the point farthest from that
+/// line, and the point farthest from that plane.
+fn initial_tetrahedron(points: &[Vec3], eps: f32) -> Option<[usize; 4]> {
+    let farthest = |score: &dyn Fn(Vec3) -> f32| {
+        let (i, best) = points
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (i, score(p)))
+            .fold((0, f32::MIN), |acc, cur| if cur.1 > acc.1 { cur } else { acc });
+        (i, best)
+    };
+
+    let (a, _) = farthest(&|p| -p.x);
+    let pa = points[a];
+    let (b, dist_ab) = farthest(&|p| p.distance(pa));
+    if dist_ab <= eps {
+        return None;
+    }
+    let axis = (points[b] - pa).normalize();
+    let (c, dist_line) = farthest(&|p| (p - pa).cross(axis).length());
+    if dist_line <= eps {
+        return None;
+    }
+    let normal = axis.cross(points[c] - pa).normalize();
+    let (d, dist_plane) = farthest(&|p| (p - pa).dot(normal).abs());
+    if dist_plane <= eps {
+        return None;
+    }
+    Some([a, b, c, d])
+}
+
+/// `face` wound so its normal points away from `interior`.
+fn oriented_face(points: &[Vec3], [a, b, c]: [usize; 3], interior: Vec3) -> [usize; 3] {
+    let normal = (points[b] - points[a]).cross(points[c] - points[a]);
+    if normal.dot(interior - points[a]) > 0.0 { [a, c, b] } else { [a, b, c] }
+}
+
+/// Whether `point` lies strictly in front of `face`'s plane. Points within
+/// `eps` of the plane count as on the hull, which keeps coplanar faces (e.g.
+/// a cube side) from being re-split by points already on them.
+fn sees_face(points: &[Vec3], [a, b, c]: [usize; 3], point: Vec3, eps: f32) -> bool {
+    let (pa, pb, pc) = (points[a], points[b], points[c]);
+    let normal = (pb - pa).cross(pc - pa).normalize_or_zero();
+    (point - pa).dot(normal) > eps
+}
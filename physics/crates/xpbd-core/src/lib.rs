@@ -3,6 +3,7 @@ pub mod constraints;
 pub mod fluids;
 pub mod forces;
 pub mod grid;
+pub mod hull;
 pub mod math;
 pub mod particle;
 pub mod quality;
@@ -133,3 +133,60 @@ pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
     let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
     t * t * (3.0 - 2.0 * t)
 }
+
+/// Point on the segment `a..b` closest to `p` (`a` itself if the segment is degenerate).
+#[inline]
+pub fn closest_point_on_segment(p: glam::Vec3, a: glam::Vec3, b: glam::Vec3) -> glam::Vec3 {
+    let ab = b - a;
+    let len_sq = ab.length_squared();
+    if len_sq < 1e-12 {
+        return a;
+    }
+    a + ab * ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0)
+}
+
+/// Small seedable PRNG (PCG-XSH-RR, 64-bit state, 32-bit output) for
+/// reproducible scene setup. Not for cryptographic use.
+#[derive(Clone, Debug)]
+pub struct Pcg32 {
+    state: u64,
+    inc: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6364136223846793005;
+    /// Fixed stream selector (any odd increment gives a full-period stream).
+    const INCREMENT: u64 = 1442695040888963407;
+
+    /// Generator whose sequence is fully determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0, inc: Self::INCREMENT };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// Next uniformly distributed `u32`.
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(Self::MULTIPLIER).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// Next value in \[0,1) (24 bits of precision, so 1.0 is never returned).
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Next direction uniformly distributed on the unit sphere.
+    pub fn next_unit_vec3(&mut self) -> glam::Vec3 {
+        let z = self.next_f32() * 2.0 - 1.0;
+        let (sin, cos) = (self.next_f32() * std::f32::consts::TAU).sin_cos();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        glam::Vec3::new(r * cos, r * sin, z)
+    }
+}
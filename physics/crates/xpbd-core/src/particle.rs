@@ -13,6 +13,10 @@ pub enum Phase {
     Static   = 6, // Infinite mass, immovable (boundary)
 }
 
+/// Floats per particle in `ParticleSet::capture_frame`:
+/// `[pos.x, pos.y, pos.z, vel.x, vel.y, vel.z, radius, phase, charge]`.
+pub const FRAME_STRIDE: usize = 9;
+
 /// SoA particle storage
 pub struct ParticleSet {
     pub count: usize,
@@ -21,6 +25,25 @@ pub struct ParticleSet {
     pub radius: Vec<f32>,
     /// Inverse mass (0.0 = static/infinite mass, 1.0 = unit mass).
     pub inv_mass: Vec<f32>,
+    /// Inverse mass saved when radius-driven mass was turned on, restored when it
+    /// is turned off again.
+    pub uncoupled_inv_mass: Vec<f32>,
+    /// False for killed particles: their slot stays but they no longer move or collide.
+    pub alive: Vec<bool>,
+    /// Embedder-assigned stable id (defaults to the initial slot index). Moves with
+    /// the particle when slots are reordered, so hosts can track logical particles.
+    pub user_id: Vec<u32>,
+    /// Index that picks the particle's shape target (and custom target point);
+    /// starts as the slot index and moves with the particle when slots are reordered.
+    pub shape_index: Vec<u32>,
+    /// Simulated seconds since the particle was created or last spawned.
+    pub age: Vec<f32>,
+    /// Age at which `Solver::step` kills the particle (0 = immortal).
+    pub lifetime: Vec<f32>,
+    /// Material friction multiplier on `config.friction` (combined per contact pair).
+    pub friction: Vec<f32>,
+    /// Material restitution multiplier on `config.restitution` (combined per contact pair).
+    pub restitution: Vec<f32>,
     pub hash: Vec<f32>,
     /// Morphing target position (computed each step from shape generators)
     pub target_pos: Vec<Vec3>,
@@ -37,12 +60,31 @@ pub struct ParticleSet {
     pub phase: Vec<Phase>,
     /// PBF Lagrange multiplier (fluid solver)
     pub lambda: Vec<f32>,
+    /// Per-particle fluid rest density (0 = use `config.fluid_rest_density`).
+    /// Fluids with different rest densities are treated as immiscible.
+    pub rest_density: Vec<f32>,
     /// Current SPH density estimate
     pub density: Vec<f32>,
+    /// SPH pressure from the equation of state (filled by `Solver::compute_pressure`)
+    pub pressure: Vec<f32>,
+    /// SPH velocity divergence (filled by `Solver::compute_velocity_divergence`)
+    pub divergence: Vec<f32>,
+    /// Normalized 0..1 speed/density heat for shaders (filled by `Solver::compute_heat`)
+    pub heat: Vec<f32>,
     /// Vorticity vector for vorticity confinement
     pub vorticity: Vec<Vec3>,
     /// Per-particle electric charge for electromagnetic forces
     pub charge: Vec<f32>,
+    /// Per-particle temperature (drives melt/freeze phase transitions)
+    pub temperature: Vec<f32>,
+    /// Acceleration from the last force pass, before velocity integration
+    /// (excludes damping and the direct velocity kicks of N-body/EM forces)
+    pub acceleration: Vec<Vec3>,
+    /// Host-injected external force, accumulated between steps and cleared after each step
+    pub external_force: Vec<Vec3>,
+    /// Next automatically assigned `user_id`; only ever increases, so ids handed
+    /// to new slots never repeat one already given out
+    next_user_id: u32,
 }
 
 impl ParticleSet {
@@ -53,6 +95,14 @@ impl ParticleSet {
             velocity: vec![Vec3::ZERO; count],
             radius: vec![0.05; count],
             inv_mass: vec![1.0; count],
+            uncoupled_inv_mass: vec![1.0; count],
+            alive: vec![true; count],
+            user_id: (0..count as u32).collect(),
+            shape_index: (0..count as u32).collect(),
+            age: vec![0.0; count],
+            lifetime: vec![0.0; count],
+            friction: vec![1.0; count],
+            restitution: vec![1.0; count],
             hash: vec![0.0; count],
             target_pos: vec![Vec3::ZERO; count],
             target_weight: vec![0.0; count],
@@ -61,9 +111,185 @@ impl ParticleSet {
             correction_counts: vec![0u32; count],
             phase: vec![Phase::Free; count],
             lambda: vec![0.0; count],
+            rest_density: vec![0.0; count],
             density: vec![0.0; count],
+            pressure: vec![0.0; count],
+            divergence: vec![0.0; count],
+            heat: vec![0.0; count],
             vorticity: vec![Vec3::ZERO; count],
             charge: vec![0.0; count],
+            temperature: vec![0.0; count],
+            acceleration: vec![Vec3::ZERO; count],
+            external_force: vec![Vec3::ZERO; count],
+            next_user_id: count as u32,
+        }
+    }
+
+    /// Hand out a `user_id` that no slot has been given automatically before.
+    pub fn fresh_user_id(&mut self) -> u32 {
+        let id = self.next_user_id;
+        self.next_user_id += 1;
+        id
+    }
+
+    /// Grow or shrink every per-particle array to `new_count`.
+    ///
+    /// Existing particles below `new_count` keep their data; new slots get the
+    /// same defaults as `ParticleSet::new`, except that a new slot gets a fresh
+    /// `user_id` (see `fresh_user_id`) and its own index as `shape_index`.
+    pub fn resize(&mut self, new_count: usize) {
+        self.count = new_count;
+        self.position.resize(new_count, Vec3::ZERO);
+        self.velocity.resize(new_count, Vec3::ZERO);
+        self.radius.resize(new_count, 0.05);
+        self.inv_mass.resize(new_count, 1.0);
+        self.uncoupled_inv_mass.resize(new_count, 1.0);
+        self.alive.resize(new_count, true);
+        let old_count = self.user_id.len();
+        self.user_id.truncate(new_count);
+        for _ in old_count..new_count {
+            let id = self.fresh_user_id();
+            self.user_id.push(id);
+        }
+        self.shape_index.truncate(new_count);
+        self.shape_index.extend(old_count as u32..new_count as u32);
+        self.age.resize(new_count, 0.0);
+        self.lifetime.resize(new_count, 0.0);
+        self.friction.resize(new_count, 1.0);
+        self.restitution.resize(new_count, 1.0);
+        self.hash.resize(new_count, 0.0);
+        self.target_pos.resize(new_count, Vec3::ZERO);
+        self.target_weight.resize(new_count, 0.0);
+        self.predicted.resize(new_count, Vec3::ZERO);
+        self.corrections.resize(new_count, Vec3::ZERO);
+        self.correction_counts.resize(new_count, 0);
+        self.phase.resize(new_count, Phase::Free);
+        self.lambda.resize(new_count, 0.0);
+        self.rest_density.resize(new_count, 0.0);
+        self.density.resize(new_count, 0.0);
+        self.pressure.resize(new_count, 0.0);
+        self.divergence.resize(new_count, 0.0);
+        self.heat.resize(new_count, 0.0);
+        self.vorticity.resize(new_count, Vec3::ZERO);
+        self.charge.resize(new_count, 0.0);
+        self.temperature.resize(new_count, 0.0);
+        self.acceleration.resize(new_count, Vec3::ZERO);
+        self.external_force.resize(new_count, Vec3::ZERO);
+    }
+
+    /// Reorder every per-particle array so that slot `k` receives the particle
+    /// previously in slot `order[k]`. `order` must be a permutation of `0..count`.
+    pub fn permute(&mut self, order: &[usize]) {
+        debug_assert_eq!(order.len(), self.count);
+        self.position = order.iter().map(|&i| self.position[i]).collect();
+        self.velocity = order.iter().map(|&i| self.velocity[i]).collect();
+        self.radius = order.iter().map(|&i| self.radius[i]).collect();
+        self.inv_mass = order.iter().map(|&i| self.inv_mass[i]).collect();
+        self.uncoupled_inv_mass = order.iter().map(|&i| self.uncoupled_inv_mass[i]).collect();
+        self.alive = order.iter().map(|&i| self.alive[i]).collect();
+        self.user_id = order.iter().map(|&i| self.user_id[i]).collect();
+        self.shape_index = order.iter().map(|&i| self.shape_index[i]).collect();
+        self.age = order.iter().map(|&i| self.age[i]).collect();
+        self.lifetime = order.iter().map(|&i| self.lifetime[i]).collect();
+        self.friction = order.iter().map(|&i| self.friction[i]).collect();
+        self.restitution = order.iter().map(|&i| self.restitution[i]).collect();
+        self.hash = order.iter().map(|&i| self.hash[i]).collect();
+        self.target_pos = order.iter().map(|&i| self.target_pos[i]).collect();
+        self.target_weight = order.iter().map(|&i| self.target_weight[i]).collect();
+        self.predicted = order.iter().map(|&i| self.predicted[i]).collect();
+        self.corrections = order.iter().map(|&i| self.corrections[i]).collect();
+        self.correction_counts = order.iter().map(|&i| self.correction_counts[i]).collect();
+        self.phase = order.iter().map(|&i| self.phase[i]).collect();
+        self.lambda = order.iter().map(|&i| self.lambda[i]).collect();
+        self.rest_density = order.iter().map(|&i| self.rest_density[i]).collect();
+        self.density = order.iter().map(|&i| self.density[i]).collect();
+        self.pressure = order.iter().map(|&i| self.pressure[i]).collect();
+        self.divergence = order.iter().map(|&i| self.divergence[i]).collect();
+        self.heat = order.iter().map(|&i| self.heat[i]).collect();
+        self.vorticity = order.iter().map(|&i| self.vorticity[i]).collect();
+        self.charge = order.iter().map(|&i| self.charge[i]).collect();
+        self.temperature = order.iter().map(|&i| self.temperature[i]).collect();
+        self.acceleration = order.iter().map(|&i| self.acceleration[i]).collect();
+        self.external_force = order.iter().map(|&i| self.external_force[i]).collect();
+    }
+
+    /// Return slot `i` to the `ParticleSet::new` defaults, for reuse by a new
+    /// particle. The slot keeps its seeded `radius` and `hash`, gets a fresh
+    /// `user_id` and its own index as `shape_index`.
+    pub fn reset_particle(&mut self, i: usize) {
+        self.position[i] = Vec3::ZERO;
+        self.velocity[i] = Vec3::ZERO;
+        self.inv_mass[i] = 1.0;
+        self.uncoupled_inv_mass[i] = 1.0;
+        self.alive[i] = true;
+        self.user_id[i] = self.fresh_user_id();
+        self.shape_index[i] = i as u32;
+        self.age[i] = 0.0;
+        self.lifetime[i] = 0.0;
+        self.friction[i] = 1.0;
+        self.restitution[i] = 1.0;
+        self.target_pos[i] = Vec3::ZERO;
+        self.target_weight[i] = 0.0;
+        self.predicted[i] = Vec3::ZERO;
+        self.corrections[i] = Vec3::ZERO;
+        self.correction_counts[i] = 0;
+        self.phase[i] = Phase::Free;
+        self.lambda[i] = 0.0;
+        self.rest_density[i] = 0.0;
+        self.density[i] = 0.0;
+        self.pressure[i] = 0.0;
+        self.divergence[i] = 0.0;
+        self.heat[i] = 0.0;
+        self.vorticity[i] = Vec3::ZERO;
+        self.charge[i] = 0.0;
+        self.temperature[i] = 0.0;
+        self.acceleration[i] = Vec3::ZERO;
+        self.external_force[i] = Vec3::ZERO;
+    }
+
+    /// Copy every per-particle value of `other`'s particle `src` into slot `dst`,
+    /// except `user_id`, which stays with the slot.
+    pub fn copy_particle_from(&mut self, dst: usize, other: &ParticleSet, src: usize) {
+        self.position[dst] = other.position[src];
+        self.velocity[dst] = other.velocity[src];
+        self.radius[dst] = other.radius[src];
+        self.inv_mass[dst] = other.inv_mass[src];
+        self.uncoupled_inv_mass[dst] = other.uncoupled_inv_mass[src];
+        self.alive[dst] = other.alive[src];
+        self.age[dst] = other.age[src];
+        self.lifetime[dst] = other.lifetime[src];
+        self.friction[dst] = other.friction[src];
+        self.restitution[dst] = other.restitution[src];
+        self.hash[dst] = other.hash[src];
+        self.shape_index[dst] = other.shape_index[src];
+        self.target_pos[dst] = other.target_pos[src];
+        self.target_weight[dst] = other.target_weight[src];
+        self.predicted[dst] = other.predicted[src];
+        self.corrections[dst] = other.corrections[src];
+        self.correction_counts[dst] = other.correction_counts[src];
+        self.phase[dst] = other.phase[src];
+        self.lambda[dst] = other.lambda[src];
+        self.rest_density[dst] = other.rest_density[src];
+        self.density[dst] = other.density[src];
+        self.pressure[dst] = other.pressure[src];
+        self.divergence[dst] = other.divergence[src];
+        self.heat[dst] = other.heat[src];
+        self.vorticity[dst] = other.vorticity[src];
+        self.charge[dst] = other.charge[src];
+        self.temperature[dst] = other.temperature[src];
+        self.acceleration[dst] = other.acceleration[src];
+        self.external_force[dst] = other.external_force[src];
+    }
+
+    /// Interleave position, velocity, radius, phase (as its `u8` value) and
+    /// charge into one flat array, `FRAME_STRIDE` floats per particle.
+    pub fn capture_frame(&self) -> Vec<f32> {
+        let mut frame = Vec::with_capacity(self.count * FRAME_STRIDE);
+        for i in 0..self.count {
+            let (p, v) = (self.position[i], self.velocity[i]);
+            frame.extend_from_slice(&[p.x, p.y, p.z, v.x, v.y, v.z]);
+            frame.extend_from_slice(&[self.radius[i], self.phase[i] as u8 as f32, self.charge[i]]);
         }
+        frame
     }
 }
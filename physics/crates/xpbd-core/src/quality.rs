@@ -117,10 +117,69 @@ pub struct StepStats {
     pub substeps: u32,
     /// Number of solver iterations per substep.
     pub iterations: u32,
+    /// Most iterations any substep actually ran; below `iterations` when the
+    /// solve converged early (see `PhysicsConfig::convergence_epsilon`).
+    pub iterations_used: u32,
     /// Number of active particles.
     pub particle_count: u32,
     /// Number of contact constraints detected.
     pub contact_count: u32,
+    /// Time spent on force accumulation, integration and per-step bookkeeping.
+    pub forces_ms: f32,
+    /// Time spent building the spatial hash grid.
+    pub grid_ms: f32,
+    /// Time spent detecting and solving contacts and the boundary.
+    pub contacts_ms: f32,
+    /// Time spent in the constraint iterations and the velocity update.
+    pub constraints_ms: f32,
+    /// Time spent on fluid density passes and post-velocity fluid effects.
+    pub fluid_ms: f32,
+}
+
+/// Millisecond clock used for the `StepStats` phase timings.
+pub type Clock = fn() -> f64;
+
+/// Default clock, backed by `std::time::Instant`.
+///
+/// `Instant` is unavailable on `wasm32-unknown-unknown`, where this reads 0
+/// until the embedder installs a clock with `Solver::set_clock`.
+pub fn default_clock() -> f64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static EPOCH: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        EPOCH.get_or_init(std::time::Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        0.0
+    }
+}
+
+/// Splits the time of one step into consecutive phases.
+pub(crate) struct PhaseTimer {
+    clock: Clock,
+    start: f64,
+    lap: f64,
+}
+
+impl PhaseTimer {
+    pub(crate) fn start(clock: Clock) -> Self {
+        let now = clock();
+        Self { clock, start: now, lap: now }
+    }
+
+    /// Milliseconds since the previous split (or the start).
+    pub(crate) fn split(&mut self) -> f32 {
+        let now = (self.clock)();
+        let elapsed = now - self.lap;
+        self.lap = now;
+        elapsed as f32
+    }
+
+    /// Milliseconds since the start.
+    pub(crate) fn total(&self) -> f32 {
+        ((self.clock)() - self.start) as f32
+    }
 }
 
 #[cfg(test)]
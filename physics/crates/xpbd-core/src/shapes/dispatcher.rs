@@ -1,6 +1,6 @@
 //! Shape dispatcher ported from GLSL (`shapes-dispatcher.ts`).
 //!
-//! Selects one of 13 shapes by `sid` and returns the target position for a
+//! Selects one of 14 shapes by `sid` and returns the target position for a
 //! particle identified by `(id_x, id_y)`.
 
 use std::f32::consts::TAU;
@@ -8,17 +8,51 @@ use std::f32::consts::TAU;
 use glam::{Mat3, Vec3};
 
 use crate::math::{curl, fract, noise};
-use crate::shapes::fractal::fractal_flow;
+use crate::shapes::fractal::{fractal_flow_with_iter, DEFAULT_FRACTAL_ITERATIONS};
 use crate::shapes::primitives::*;
 
+/// Shape id for the user-supplied point cloud (`Solver::set_custom_shape_targets`).
+/// `target_for` treats it like any unknown id; the solver resolves it itself.
+pub const CUSTOM_SHAPE: u32 = 14;
+
+/// Frame-wide inputs to `target_for` shared by every particle and shape.
+#[derive(Clone, Copy, Debug)]
+pub struct TargetParams {
+    /// Audio energy bands for the equalizer.
+    pub audio_bass: f32,
+    pub audio_mid: f32,
+    pub audio_treble: f32,
+    /// Equalizer bass/treble split points (see `shape_equalizer`).
+    pub eq_zones: [f32; 2],
+    /// Escape-time iteration cap for the fractal shape.
+    pub fractal_iterations: u32,
+    /// Scale of the noise z displacement of the 2D-lifted shapes
+    /// (superformula, rose, polygon); 0 = flat, 1 = the original depth.
+    pub thickness: f32,
+}
+
+impl Default for TargetParams {
+    fn default() -> Self {
+        Self {
+            audio_bass: 0.0,
+            audio_mid: 0.0,
+            audio_treble: 0.0,
+            eq_zones: DEFAULT_EQ_ZONES,
+            fractal_iterations: DEFAULT_FRACTAL_ITERATIONS,
+            thickness: 1.0,
+        }
+    }
+}
+
 /// Compute the target position for particle `(id_x, id_y)` on shape `sid`.
 ///
-/// * `sid` -- shape index (0..=12).
+/// * `sid` -- shape index (0..=13).
 /// * `id_x`, `id_y` -- normalised particle UV coordinates.
 /// * `time` -- animation time in seconds.
 /// * `rot` -- pre-computed rotation matrix (applied to the shape).
-/// * `fractal_seed` -- four-component seed forwarded to `fractal_flow`.
-/// * `audio_bass`, `audio_mid`, `audio_treble` -- audio energy bands for the equalizer.
+/// * `fractal_seed` -- four-component seed forwarded to `fractal_flow_with_iter`
+///   (`fractal_seed[0]` doubles as the blob roughness).
+/// * `params` -- audio bands, equalizer zones, fractal iterations and thickness.
 pub fn target_for(
     sid: u32,
     id_x: f32,
@@ -26,10 +60,17 @@ pub fn target_for(
     time: f32,
     rot: &Mat3,
     fractal_seed: &[f32; 4],
-    audio_bass: f32,
-    audio_mid: f32,
-    audio_treble: f32,
+    params: &TargetParams,
 ) -> Vec3 {
+    let TargetParams {
+        audio_bass,
+        audio_mid,
+        audio_treble,
+        eq_zones,
+        fractal_iterations,
+        thickness,
+    } = *params;
+
     // Derive pseudo-random s and angle from particle id, matching the GLSL
     let s = fract(id_x + id_y * 1.618 + noise(id_x * 17.0, id_y * 17.0));
     let angle = (id_x + noise(id_x * 3.1, id_y * 3.1)) * TAU;
@@ -64,7 +105,7 @@ pub fn target_for(
             let n3 = 1.7 + 0.7 * (time * 0.11).cos();
             let (px, py) = shape_superformula(angle, m, n1, n2, n3);
             let scale = 0.3 + 0.7 * s.sqrt();
-            let pz = (noise(id_x * 9.0, id_y * 9.0) - 0.5) * 0.6;
+            let pz = (noise(id_x * 9.0, id_y * 9.0) - 0.5) * 0.6 * thickness;
             *rot * Vec3::new(px * scale, py * scale, pz)
         }
         6 => {
@@ -72,7 +113,7 @@ pub fn target_for(
             let k = 5.0 + ((time * 0.15) % 3.0).floor();
             let (px, py) = shape_rose(angle, k);
             let scale = 0.3 + 0.7 * s.sqrt();
-            let pz = (noise(id_x * 7.3, id_y * 7.3) - 0.5) * 0.8;
+            let pz = (noise(id_x * 7.3, id_y * 7.3) - 0.5) * 0.8 * thickness;
             *rot * Vec3::new(px * scale, py * scale, pz)
         }
         7 => {
@@ -92,12 +133,12 @@ pub fn target_for(
             let n = 5.0 + ((time * 0.2) % 4.0).floor();
             let (px, py) = shape_polygon(angle, n);
             let scale = 0.5 + 0.5 * s.sqrt();
-            let pz = (noise(id_x * 4.7, id_y * 4.7) - 0.5) * 0.4;
+            let pz = (noise(id_x * 4.7, id_y * 4.7) - 0.5) * 0.4 * thickness;
             *rot * Vec3::new(px * scale, py * scale, pz)
         }
         11 => {
             // Fractal
-            let f = fractal_flow(id_x, id_y, time, *fractal_seed);
+            let f = fractal_flow_with_iter(id_x, id_y, time, *fractal_seed, fractal_iterations);
             let shells = 0.4 + 0.35 * (time * 0.3 + fractal_seed[3]).sin();
             let mut p = *rot * (f * (0.9 + shells));
             let (cx, cy) = curl(
@@ -111,14 +152,18 @@ pub fn target_for(
         }
         12 => {
             // Equalizer
-            shape_equalizer(id_x, id_y, audio_bass, audio_mid, audio_treble, time)
+            shape_equalizer(id_x, id_y, audio_bass, audio_mid, audio_treble, time, eq_zones)
+        }
+        13 => {
+            // Blob (noise-displaced sphere)
+            *rot * shape_blob(t, s, time, fractal_seed[0])
         }
         _ => {
             // Fallback: polygon / star (same as sid == 10)
             let n = 5.0 + ((time * 0.2) % 4.0).floor();
             let (px, py) = shape_polygon(angle, n);
             let scale = 0.5 + 0.5 * s.sqrt();
-            let pz = (noise(id_x * 4.7, id_y * 4.7) - 0.5) * 0.4;
+            let pz = (noise(id_x * 4.7, id_y * 4.7) - 0.5) * 0.4 * thickness;
             *rot * Vec3::new(px * scale, py * scale, pz)
         }
     }
@@ -6,7 +6,8 @@
 
 use glam::Vec3;
 
-const MAX_ITER: u32 = 48;
+/// Escape-time iteration cap used by `fractal_flow`.
+pub const DEFAULT_FRACTAL_ITERATIONS: u32 = 48;
 
 /// Compute the fractal flow position for a particle.
 ///
@@ -16,6 +17,22 @@ const MAX_ITER: u32 = 48;
 ///
 /// The fractal type is determined by `(seed[3] * 10.0) mod 10`.
 pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
+    fractal_flow_with_iter(id_x, id_y, time, seed, DEFAULT_FRACTAL_ITERATIONS)
+}
+
+/// `fractal_flow` with an explicit escape-time iteration cap (at least 1).
+///
+/// Escape times are normalised against `DEFAULT_FRACTAL_ITERATIONS`, so points
+/// that escape quickly (and interior points that settle) keep the same
+/// position; extra iterations only resolve points near the set boundary.
+pub fn fractal_flow_with_iter(
+    id_x: f32,
+    id_y: f32,
+    time: f32,
+    seed: [f32; 4],
+    max_iter: u32,
+) -> Vec3 {
+    let max_iter = max_iter.max(1);
     let px = id_x * 2.0 - 1.0;
     let py = id_y * 2.0 - 1.0;
 
@@ -42,7 +59,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             let cy = py * zoom + center_y;
             zx = 0.0;
             zy = 0.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -65,7 +82,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             zy = py * zoom * 1.5;
             let cx = julia_cx;
             let cy = julia_cy;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -85,7 +102,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             let cy = py * zoom - 0.5;
             zx = 0.0;
             zy = 0.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -105,7 +122,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             let cy = py * zoom + center_y;
             zx = 0.0;
             zy = 0.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -124,7 +141,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             _color_mod = 0.0;
             zx = px * zoom * 2.0;
             zy = py * zoom * 2.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 // z^2
                 let z2x = zx * zx - zy * zy;
                 let z2y = 2.0 * zx * zy;
@@ -171,7 +188,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             let cy = py * zoom;
             zx = 0.0;
             zy = 0.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -194,7 +211,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             let cy = py * zoom * 0.8 + center_y;
             zx = 0.0;
             zy = 0.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -219,7 +236,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             let cy = py * zoom;
             zx = 0.0;
             zy = 0.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -240,7 +257,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             let cy = py * zoom + center_y;
             zx = 0.0;
             zy = 0.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -261,7 +278,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
             let cy = py * zoom - 0.3;
             zx = 0.0;
             zy = 0.0;
-            for i in 0..MAX_ITER {
+            for i in 0..max_iter {
                 let z_len = zx * zx + zy * zy;
                 if z_len > 4.0 {
                     smooth_iter =
@@ -279,7 +296,7 @@ pub fn fractal_flow(id_x: f32, id_y: f32, time: f32, seed: [f32; 4]) -> Vec3 {
     }
 
     // Normalise
-    let max_iter_f = MAX_ITER as f32;
+    let max_iter_f = DEFAULT_FRACTAL_ITERATIONS as f32;
     let mut fractal_val = (smooth_iter / max_iter_f).clamp(0.0, 1.0);
 
     // Interior points
@@ -7,7 +7,7 @@ use std::f32::consts::TAU;
 
 use glam::Vec3;
 
-use crate::math::{fract, smoothstep};
+use crate::math::{fbm, fract, smoothstep};
 
 // ---------- 3D shapes ----------
 
@@ -152,14 +152,43 @@ pub fn shape_icosahedron(t: f32, s: f32) -> Vec3 {
     Vec3::new(r * angle.cos(), (s - 0.5) * 1.4, r * angle.sin())
 }
 
+/// Organic blob: `shape_sphere` with its radius modulated by animated fbm noise.
+///
+/// The radius is scaled by `1 + roughness * fbm(...)`, sampled from
+/// direction-derived coordinates so nearby points bulge together.
+/// `roughness = 0` gives the plain radius-0.7 sphere.
+pub fn shape_blob(t: f32, s: f32, time: f32, roughness: f32) -> Vec3 {
+    let p = shape_sphere(t, s);
+    let dir = p / 0.7;
+    let n = fbm(
+        dir.x * 2.3 + dir.z * 1.1 + time * 0.25,
+        dir.y * 2.3 - dir.z * 0.9 - time * 0.2,
+    );
+    p * (1.0 + roughness * n)
+}
+
 // ---------- Audio-reactive equalizer ----------
 
+/// Default equalizer zone split points: bass fades out by 0.45 of the bar
+/// range, treble fades in from 0.55.
+pub const DEFAULT_EQ_ZONES: [f32; 2] = [0.45, 0.55];
+
 /// Audio-reactive equalizer bars.
 ///
 /// Creates 16 frequency-distributed bars with wireframe edges and animation.
 /// `bass`, `mid`, `treble` are audio energy bands in `[0, 1]`; `time` drives
-/// the demo animation when no audio is present.
-pub fn shape_equalizer(t: f32, s: f32, bass: f32, mid: f32, treble: f32, time: f32) -> Vec3 {
+/// the demo animation when no audio is present. `zones` are the normalized
+/// bar positions where the bass zone ends and the treble zone starts; the mid
+/// zone is centered between them.
+pub fn shape_equalizer(
+    t: f32,
+    s: f32,
+    bass: f32,
+    mid: f32,
+    treble: f32,
+    time: f32,
+    zones: [f32; 2],
+) -> Vec3 {
     let num_bars = 16.0_f32;
     let bar_width = 3.0 / num_bars;
     let gap_ratio = 0.15_f32;
@@ -193,9 +222,11 @@ pub fn shape_equalizer(t: f32, s: f32, bass: f32, mid: f32, treble: f32, time: f
 
     let norm_bar = bar_index / (num_bars - 1.0);
 
-    let mut bass_zone = smoothstep(0.45, 0.0, norm_bar);
-    let mut mid_zone = (1.0 - (norm_bar - 0.5).abs() * 3.0).max(0.0);
-    let mut treble_zone = smoothstep(0.55, 1.0, norm_bar);
+    let [bass_end, treble_start] = zones;
+    let mid_center = (bass_end + treble_start) * 0.5;
+    let mut bass_zone = smoothstep(bass_end, 0.0, norm_bar);
+    let mut mid_zone = (1.0 - (norm_bar - mid_center).abs() * 3.0).max(0.0);
+    let mut treble_zone = smoothstep(treble_start, 1.0, norm_bar);
 
     let total_zone = bass_zone + mid_zone + treble_zone;
     if total_zone > 0.01 {
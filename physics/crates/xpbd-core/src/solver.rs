@@ -1,20 +1,109 @@
-use crate::config::PhysicsConfig;
+use crate::config::{
+    BoundaryShape, PhysicsConfig, MAX_SOLVER_ITERATIONS, MAX_SUBSTEPS, MIN_PERIODIC_HALF_EXTENT,
+};
 use crate::constraints::bending::{self, BendingConstraint};
-use crate::constraints::contact::{detect_contacts, solve_contacts, ContactConstraint};
+use crate::constraints::capsule_contact::{
+    capsule_segments, detect_capsule_contacts, solve_capsule_contacts, CapsuleContact,
+};
+use crate::constraints::contact::{
+    apply_contact_restitution, cap_contacts_per_particle, contact_normal_velocities,
+    detect_contacts, detect_speculative_contacts, solve_contacts, ContactConstraint,
+    ContactParams, GranularFriction,
+};
+use crate::constraints::density::compute_densities;
 use crate::constraints::distance::{self, DistanceConstraint};
 use crate::constraints::shape_matching::{ShapeMatchGroup, solve_shape_matching};
-use crate::forces::audio::{audio_boost_factor, compute_audio_force};
+use crate::fluids::divergence::compute_velocity_divergence;
+use crate::fluids::poly6_kernel;
+use crate::forces::audio::{audio_boost_factor, compute_audio_force, AudioParams};
 use crate::forces::electromagnetic::apply_electromagnetic_forces;
 use crate::forces::flow::compute_flow_force;
-use crate::forces::gravity::apply_nbody_gravity;
+use crate::forces::gravity::{apply_nbody_gravity, octree_cells};
 use crate::forces::pointer::{compute_pointer_force, PointerParams};
+use crate::forces::{ForceField, Softening};
 use crate::grid::SpatialHashGrid;
-use crate::math::{curl, ease_in_out_cubic, hash12, noise, smoothstep};
+use crate::hull::convex_hull;
+use crate::math::{
+    closest_point_on_segment, curl, ease_in_out_cubic, hash12, noise, smoothstep, Pcg32,
+};
 use crate::particle::{ParticleSet, Phase};
-use crate::quality::{AdaptiveQuality, StepStats};
-use crate::shapes::dispatcher::target_for;
+use crate::quality::{default_clock, AdaptiveQuality, Clock, PhaseTimer, StepStats};
+use crate::shapes::dispatcher::{target_for, TargetParams, CUSTOM_SHAPE};
 use crate::shapes::morph::solve_shape_targets;
-use glam::Vec3;
+use crate::shapes::fractal::DEFAULT_FRACTAL_ITERATIONS;
+use crate::shapes::primitives::DEFAULT_EQ_ZONES;
+use glam::{Quat, Vec3};
+use std::collections::HashMap;
+
+/// Integration path taken by `Solver::step`, selected by `config.collisions_enabled`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegrationPath {
+    /// XPBD substeps with position prediction and constraint solving.
+    Xpbd,
+    /// Single-pass Euler integration (forces only).
+    Euler,
+}
+
+/// Particle positions captured by `Solver::snapshot`, used to interpolate
+/// rendered output between fixed physics steps.
+#[derive(Clone, Debug, Default)]
+pub struct StateSnapshot {
+    pub position: Vec<Vec3>,
+    /// Slot layout the positions were captured in (see `Solver::reorder_by_cell`)
+    pub layout: u32,
+}
+
+/// Why `Solver::append` refused to merge two worlds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppendError {
+    /// The worlds use different boundary shapes, so the appended positions
+    /// would not be valid in this domain.
+    BoundaryMismatch,
+    /// The merged particle count does not fit the `u32` constraint indices.
+    TooManyParticles,
+}
+
+impl std::fmt::Display for AppendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppendError::BoundaryMismatch => write!(f, "worlds use different boundary shapes"),
+            AppendError::TooManyParticles => write!(f, "merged particle count exceeds u32 indices"),
+        }
+    }
+}
+
+impl std::error::Error for AppendError {}
+
+/// In-progress scatter-then-reform transition (see `Solver::trigger_scatter_reform`).
+struct ScatterReform {
+    /// `shape_strength` to restore once the transition ends
+    original_strength: f32,
+    /// Seconds over which shape attraction ramps back
+    duration: f32,
+    /// Simulated seconds since the trigger
+    elapsed: f32,
+}
+
+/// In-progress `shape_strength` ramp (see `Solver::animate_shape_strength`).
+struct ShapeStrengthRamp {
+    /// `shape_strength` when the ramp started
+    from: f32,
+    /// `shape_strength` once the ramp ends
+    target: f32,
+    /// Seconds the ramp takes
+    duration: f32,
+    /// Simulated seconds since the ramp started
+    elapsed: f32,
+}
+
+/// Fading damping of the particles a deactivated pointer was acting on
+/// (see `PhysicsConfig::pointer_release_damping`).
+struct PointerRelease {
+    /// Particles within the pointer radius when it deactivated
+    particles: Vec<u32>,
+    /// Simulated seconds since the pointer deactivated
+    elapsed: f32,
+}
 
 /// Parameters controlling shape morphing, rotation, fractals, and audio.
 pub struct ShapeParams {
@@ -23,6 +112,11 @@ pub struct ShapeParams {
     pub morph: f32,
     pub rot_a: glam::Mat3,
     pub rot_b: glam::Mat3,
+    /// Angular velocity of `rot_a` as axis * radians per simulated second;
+    /// the solver spins the rotation each step.
+    pub rot_a_velocity: Vec3,
+    /// Angular velocity of `rot_b` (see `rot_a_velocity`).
+    pub rot_b_velocity: Vec3,
     pub fractal_a: [f32; 4],
     pub fractal_b: [f32; 4],
     pub audio_bass: f32,
@@ -30,6 +124,16 @@ pub struct ShapeParams {
     pub audio_treble: f32,
     pub audio_energy: f32,
     pub speed_multiplier: f32,
+    /// Equalizer bass/treble zone split points along the bar range.
+    pub eq_zones: [f32; 2],
+    /// Escape-time iteration cap for the fractal shape (more = finer detail, slower).
+    pub fractal_iterations: u32,
+    /// Speed multiplier for the equalizer treble sparkle (1 = default).
+    pub sparkle_frequency: f32,
+    /// Strength multiplier for the equalizer treble sparkle (0 = no jitter).
+    pub sparkle_amount: f32,
+    /// Depth multiplier for the 2D-lifted shapes' noise z (0 = flat, 1 = default).
+    pub shape_thickness: f32,
 }
 
 impl Default for ShapeParams {
@@ -40,6 +144,8 @@ impl Default for ShapeParams {
             morph: 0.0,
             rot_a: glam::Mat3::IDENTITY,
             rot_b: glam::Mat3::IDENTITY,
+            rot_a_velocity: Vec3::ZERO,
+            rot_b_velocity: Vec3::ZERO,
             fractal_a: [0.5, 0.0, 0.0, 0.0],
             fractal_b: [0.5, 0.0, 0.0, 0.0],
             audio_bass: 0.0,
@@ -47,6 +153,11 @@ impl Default for ShapeParams {
             audio_treble: 0.0,
             audio_energy: 0.0,
             speed_multiplier: 1.0,
+            eq_zones: DEFAULT_EQ_ZONES,
+            fractal_iterations: DEFAULT_FRACTAL_ITERATIONS,
+            sparkle_frequency: 1.0,
+            sparkle_amount: 1.0,
+            shape_thickness: 1.0,
         }
     }
 }
@@ -63,32 +174,64 @@ pub struct Solver {
     pub last_stats: StepStats,
     grid: SpatialHashGrid,
     contacts: Vec<ContactConstraint>,
+    /// Bond-segment contacts of the current substep
+    capsule_contacts: Vec<CapsuleContact>,
+    /// Contact corrections of one collision pass (scratch, limited at the boundary)
+    contact_corrections: Vec<Vec3>,
+    /// Original inverse masses of particles held static by `freeze_particles`
+    frozen_inv_mass: HashMap<u32, f32>,
+    /// Indices of live (not killed) particles, ascending
+    active: Vec<u32>,
+    /// Path taken by the most recent step
+    last_integration_path: Option<IntegrationPath>,
+    /// Set when a step ran on a different path than the step before it
+    integration_path_switched: bool,
+    /// Steps of the velocity cross-fade still to run after a path switch
+    path_blend_remaining: u32,
+    /// Velocities at the start of a blended step (scratch for the cross-fade)
+    path_blend_velocity: Vec<Vec3>,
+    /// Positions at the start of a step (scratch for `max_particle_displacement`)
+    frame_start_position: Vec<Vec3>,
+    /// Consecutive steps whose max speed stayed below `settle_velocity_threshold`
+    settled_frames: u32,
+    /// Active scatter/reform timer, if any
+    scatter_reform: Option<ScatterReform>,
+    /// Active `shape_strength` ramp, if any
+    shape_strength_ramp: Option<ShapeStrengthRamp>,
+    /// Embedder-registered force fields, summed in registration order
+    force_fields: Vec<ForceField>,
+    /// Point cloud for shape id `CUSTOM_SHAPE`, matched to particles by index
+    custom_targets: Vec<Vec3>,
+    /// Pointer position and effective radius as of the last step it was active
+    last_active_pointer: Option<(Vec3, f32)>,
+    /// Active pointer release damping, if any
+    pointer_release: Option<PointerRelease>,
+    /// Clock for the phase timings in `last_stats`
+    clock: Clock,
+    /// Bumped by every `reorder_by_cell`, so snapshots of the old slot order are detected
+    slot_layout: u32,
 }
 
 impl Solver {
     pub fn new(particle_count: usize) -> Self {
         let mut particles = ParticleSet::new(particle_count);
+        let config = PhysicsConfig::default();
 
         // Initialize with spiral ring (matches existing init in index-webgpu.ts)
         let tex_size = (particle_count as f32).sqrt().ceil() as usize;
         for i in 0..particle_count {
             let t = i as f32 / particle_count as f32;
-            let angle = t * std::f32::consts::TAU * 20.0;
-            let r = 0.5 + t * 1.5;
-            particles.position[i] = Vec3::new(
-                angle.cos() * r,
-                (t - 0.5) * 2.0,
-                angle.sin() * r,
-            );
+            particles.position[i] =
+                spiral_position(t, config.init_spiral_turns, config.init_spiral_radius);
             let ux = (i % tex_size) as f32 / tex_size as f32;
             let uy = (i / tex_size) as f32 / tex_size as f32;
-            particles.radius[i] = 0.05 + hash12(ux, uy) * 0.05;
             particles.hash[i] = hash12(ux, uy);
+            particles.radius[i] = hashed_radius(&config, particles.hash[i]);
         }
 
         Self {
             particles,
-            config: PhysicsConfig::default(),
+            config,
             shape_params: ShapeParams::default(),
             pointer_params: PointerParams::default(),
             distance_constraints: Vec::new(),
@@ -96,11 +239,48 @@ impl Solver {
             shape_match_groups: Vec::new(),
             adaptive_quality: AdaptiveQuality::new(4, 3),
             last_stats: StepStats::default(),
-            grid: SpatialHashGrid::new(0.2, 131072, particle_count),
+            // Allocated on first use by `ensure_grid`
+            grid: SpatialHashGrid::new(0.2, 0, 0),
             contacts: Vec::new(),
+            capsule_contacts: Vec::new(),
+            contact_corrections: Vec::new(),
+            frozen_inv_mass: HashMap::new(),
+            active: (0..particle_count as u32).collect(),
+            last_integration_path: None,
+            integration_path_switched: false,
+            path_blend_remaining: 0,
+            path_blend_velocity: Vec::new(),
+            frame_start_position: Vec::new(),
+            settled_frames: 0,
+            scatter_reform: None,
+            shape_strength_ramp: None,
+            force_fields: Vec::new(),
+            custom_targets: Vec::new(),
+            last_active_pointer: None,
+            pointer_release: None,
+            clock: default_clock,
+            slot_layout: 0,
+        }
+    }
+
+    /// Integration path the next `step` will take.
+    pub fn active_integration_path(&self) -> IntegrationPath {
+        if self.config.collisions_enabled {
+            IntegrationPath::Xpbd
+        } else {
+            IntegrationPath::Euler
         }
     }
 
+    /// Returns true (once) if a step switched integration paths since the last call.
+    ///
+    /// Switching mid-run is allowed but velocities are not re-predicted, so
+    /// the first steps on the new path can jump unless `config.path_blend_frames`
+    /// cross-fades them.
+    pub fn take_integration_path_switch(&mut self) -> bool {
+        std::mem::take(&mut self.integration_path_switched)
+    }
+
     /// Returns true if any particle has Phase::Fluid or Phase::Gas.
     fn has_fluid_particles(&self) -> bool {
         self.particles.phase.iter().any(|p| matches!(p, Phase::Fluid | Phase::Gas))
@@ -110,20 +290,69 @@ impl Solver {
     ///
     /// `dt` is the frame delta time in seconds. `time` is the accumulated
     /// simulation time used for animated noise and shape parameters.
+    ///
+    /// Stepping is deterministic: two solvers built from the same inputs
+    /// produce bit-identical positions and velocities. Particles are visited
+    /// in index order, contacts in `(i, j)` order regardless of the hash table
+    /// size, shape-match groups in creation order, and Jacobi corrections are
+    /// summed in that fixed order before averaging. Any future parallel path
+    /// must preserve this reduction order.
     pub fn step(&mut self, dt: f32, time: f32) {
+        let tex_size = (self.particles.count as f32).sqrt().ceil() as usize;
+        self.step_with_tex_size(dt, time, tex_size);
+    }
+
+    /// Run `n` consecutive steps of `dt`, starting at `start_time` and
+    /// advancing time by `dt` per step.
+    ///
+    /// Equivalent to calling `step(dt, start_time + i * dt)` for `i in 0..n`,
+    /// but per-call setup is done once.
+    pub fn step_n(&mut self, n: u32, dt: f32, start_time: f32) {
+        let tex_size = (self.particles.count as f32).sqrt().ceil() as usize;
+        for i in 0..n {
+            self.step_with_tex_size(dt, start_time + i as f32 * dt, tex_size);
+        }
+    }
+
+    fn step_with_tex_size(&mut self, dt: f32, time: f32, tex_size: usize) {
         let sp = &self.shape_params;
-        let speed_multiplier = sp.speed_multiplier;
+        let audio_speed = 1.0 + self.config.audio_speed_coupling * sp.audio_energy;
+        let speed_multiplier = sp.speed_multiplier * audio_speed.max(0.0);
         let sim_dt = dt * speed_multiplier;
 
         if sim_dt.abs() < 1.0e-9 {
             return;
         }
 
+        let mut timer = PhaseTimer::start(self.clock);
+        let stats = &mut self.last_stats;
+        stats.forces_ms = 0.0;
+        stats.grid_ms = 0.0;
+        stats.contacts_ms = 0.0;
+        stats.constraints_ms = 0.0;
+        stats.fluid_ms = 0.0;
+
+        let path = self.active_integration_path();
+        if self.last_integration_path.is_some_and(|last| last != path) {
+            self.integration_path_switched = true;
+            self.path_blend_remaining = self.config.path_blend_frames;
+        }
+        self.last_integration_path = Some(path);
+
         let count = self.particles.count;
-        let tex_size = (count as f32).sqrt().ceil() as usize;
+        if self.path_blend_remaining > 0 {
+            self.path_blend_velocity.clear();
+            self.path_blend_velocity.extend_from_slice(&self.particles.velocity[..count]);
+        }
+        if self.config.max_particle_displacement > 0.0 {
+            self.frame_start_position.clear();
+            self.frame_start_position.extend_from_slice(&self.particles.position[..count]);
+        }
+
+        self.damp_pointer_release(sim_dt);
 
         // Compute shape targets ONCE (they don't change within substeps)
-        self.compute_shape_targets(time, tex_size);
+        self.compute_shape_targets(time, sim_dt, tex_size);
 
         if self.config.collisions_enabled {
             // --- XPBD path: substeps with prediction + constraint solving ---
@@ -135,8 +364,21 @@ impl Solver {
             self.last_stats.substeps = substeps;
             self.last_stats.iterations = iterations;
             self.last_stats.particle_count = count as u32;
+            let eps = self.config.convergence_epsilon;
+            let convergence_epsilon_sq = if eps > 0.0 { eps * eps } else { -1.0 };
+            let mut iterations_run = 0;
+
+            let softening = |base: f32| {
+                if self.config.adaptive_softening {
+                    Softening::adaptive(base, self.config.adaptive_softening_k)
+                } else {
+                    Softening::constant(base)
+                }
+            };
+            let nbody_softening = softening(self.config.nbody_softening);
+            let em_softening = softening(0.01);
 
-            for _substep in 0..substeps {
+            for substep in 0..substeps {
                 // STEP 1: Apply forces -> update velocities
                 self.apply_forces(sub_dt, time, tex_size);
 
@@ -147,7 +389,7 @@ impl Solver {
                         &mut self.particles.velocity,
                         count,
                         self.config.nbody_g,
-                        self.config.nbody_softening,
+                        nbody_softening,
                         self.config.nbody_theta,
                         sub_dt,
                     );
@@ -162,7 +404,7 @@ impl Solver {
                         count,
                         self.config.em_coulomb_k,
                         self.config.em_magnetic_field,
-                        0.01, // softening
+                        em_softening,
                         self.config.boundary_radius * 2.0, // max range
                         sub_dt,
                     );
@@ -181,36 +423,32 @@ impl Solver {
                 // Reset XPBD Lagrange multipliers for this substep
                 distance::reset_lambdas(&mut self.distance_constraints);
                 bending::reset_lambdas(&mut self.bending_constraints);
+                self.last_stats.forces_ms += timer.split();
 
                 // STEP 3: Build grid and solve constraints
-                self.grid.build(&self.particles.predicted, count);
-
-                self.contacts = detect_contacts(
-                    &self.particles.predicted,
-                    &self.particles.radius,
-                    count,
-                    &self.grid,
-                );
-                self.last_stats.contact_count = self.contacts.len() as u32;
+                if substep == 0 || self.config.grid_rebuild_every_substep {
+                    self.ensure_grid();
+                    self.grid.set_periodic(self.periodic_half_extents());
+                    self.grid.build_masked(&self.particles.predicted, count, &self.particles.alive);
+                }
+                self.last_stats.grid_ms += timer.split();
 
-                for _iter in 0..iterations {
+                for iter in 0..iterations {
                     // Reset corrections
                     for i in 0..count {
                         self.particles.corrections[i] = Vec3::ZERO;
                         self.particles.correction_counts[i] = 0;
                     }
 
-                    // Solve contact constraints (with Coulomb friction)
-                    solve_contacts(
-                        &self.contacts,
-                        &self.particles.predicted,
-                        &self.particles.position,
-                        &self.particles.inv_mass,
-                        &mut self.particles.corrections,
-                        &mut self.particles.correction_counts,
-                        self.config.friction,
-                        sub_dt,
-                    );
+                    // Extra density-only passes, applied before the shared
+                    // contact + constraint pass below
+                    self.last_stats.constraints_ms += timer.split();
+                    if has_fluid {
+                        for _ in 1..self.config.fluid_inner_iterations {
+                            self.fluid_density_pass();
+                        }
+                    }
+                    self.last_stats.fluid_ms += timer.split();
 
                     // PBF density constraints for Fluid/Gas particles
                     if has_fluid {
@@ -220,8 +458,11 @@ impl Solver {
                             self.config.fluid_rest_density,
                             self.config.smoothing_radius,
                             self.config.tensile_correction,
+                            self.config.max_fluid_neighbors,
+                            self.config.free_contributes_to_density,
                         );
                     }
+                    self.last_stats.fluid_ms += timer.split();
 
                     // Distance constraints (cloth)
                     if !self.distance_constraints.is_empty() {
@@ -244,13 +485,13 @@ impl Solver {
                     // Shape matching (rigid bodies)
                     if !self.shape_match_groups.is_empty() {
                         solve_shape_matching(
-                            &self.shape_match_groups,
+                            &mut self.shape_match_groups,
                             &mut self.particles,
                         );
                     }
 
                     // Shape target constraints (morphing attraction as XPBD)
-                    if self.config.shape_strength > 0.001 {
+                    if self.config.artistic_forces_enabled && self.config.shape_strength > 0.001 {
                         solve_shape_targets(
                             &mut self.particles,
                             self.config.shape_strength,
@@ -260,25 +501,62 @@ impl Solver {
                         );
                     }
 
-                    // Solve boundary constraint
-                    self.solve_boundary_constraint();
+                    // Particle contacts, capsule contacts and the boundary
+                    self.last_stats.constraints_ms += timer.split();
+                    self.solve_collision_constraints(iter, sub_dt);
+                    self.last_stats.contacts_ms += timer.split();
 
                     // Apply averaged corrections
+                    let mut max_correction_sq = 0.0_f32;
                     for i in 0..count {
                         if self.particles.correction_counts[i] > 0 {
-                            self.particles.predicted[i] += self.particles.corrections[i]
+                            let correction = self.particles.corrections[i]
                                 / self.particles.correction_counts[i] as f32;
+                            self.particles.predicted[i] += correction;
+                            max_correction_sq = max_correction_sq.max(correction.length_squared());
                         }
                     }
+
+                    // Early out once the largest correction is below the tolerance
+                    iterations_run = iterations_run.max(iter + 1);
+                    if max_correction_sq < convergence_epsilon_sq {
+                        break;
+                    }
                 }
 
                 // STEP 4: Update velocities from position change and finalize
+                let fluid_splash = has_fluid && self.config.boundary_shape == BoundaryShape::Sphere;
+                let bounce = self.config.contact_restitution
+                    && self.config.restitution > 0.0
+                    && !self.contacts.is_empty();
+                let pre_normal_velocity = if bounce {
+                    contact_normal_velocities(&self.contacts, &self.particles.velocity)
+                } else {
+                    Vec::new()
+                };
                 for i in 0..count {
                     if self.particles.inv_mass[i] == 0.0 { continue; }
-                    self.particles.velocity[i] =
+                    let mut vel =
                         (self.particles.predicted[i] - self.particles.position[i]) / sub_dt;
+                    if fluid_splash && self.particles.phase[i] == Phase::Fluid {
+                        vel = self.fluid_boundary_splash(i, vel, sub_dt, time);
+                    }
+                    self.particles.velocity[i] = vel;
                     self.particles.position[i] = self.particles.predicted[i];
                 }
+                if bounce {
+                    let config = &self.config;
+                    let (phase, material) = (&self.particles.phase, &self.particles.restitution);
+                    apply_contact_restitution(
+                        &self.contacts,
+                        &pre_normal_velocity,
+                        &mut self.particles.velocity,
+                        &self.particles.inv_mass,
+                        |i, j| pair_restitution(config, phase, material, i, j),
+                    );
+                }
+                self.wrap_periodic_positions();
+                self.last_stats.constraints_ms += timer.split();
 
                 // Post-velocity corrections (fluid only)
                 if has_fluid {
@@ -286,6 +564,7 @@ impl Solver {
                         &mut self.particles,
                         &self.grid,
                         self.config.fluid_vorticity,
+                        self.config.fluid_vorticity_clamp,
                         self.config.smoothing_radius,
                         sub_dt,
                     );
@@ -295,8 +574,22 @@ impl Solver {
                         self.config.fluid_viscosity,
                         self.config.smoothing_radius,
                     );
+                    if self.config.fluid_surface_tension != 0.0
+                        || self.config.fluid_interface_tension != 0.0
+                    {
+                        crate::fluids::interface_tension::apply_interface_tension(
+                            &mut self.particles,
+                            &self.grid,
+                            self.config.fluid_rest_density,
+                            self.config.fluid_surface_tension,
+                            self.config.fluid_interface_tension,
+                            self.config.smoothing_radius,
+                        );
+                    }
                 }
+                self.last_stats.fluid_ms += timer.split();
             }
+            self.last_stats.iterations_used = iterations_run;
         } else {
             // --- Original path: single-pass integration (preserves exact behavior) ---
             self.apply_forces(sim_dt, time, tex_size);
@@ -305,14 +598,332 @@ impl Solver {
                 self.particles.position[i] +=
                     self.particles.velocity[i] * sim_dt;
             }
+            if self.config.euler_boundary_clamp {
+                self.clamp_to_boundary_sphere();
+            }
+            self.wrap_periodic_positions();
+        }
+
+        if self.path_blend_remaining > 0 {
+            self.blend_path_velocities();
+        }
+        if self.config.max_particle_displacement > 0.0 {
+            self.clamp_frame_displacement();
+        }
+
+        // External forces only last for the step they were added before
+        for f in self.particles.external_force.iter_mut() {
+            *f = Vec3::ZERO;
+        }
+
+        self.advance_scatter_reform(sim_dt);
+        self.advance_shape_strength_ramp(sim_dt);
+        self.age_particles(sim_dt);
+
+        if self.is_settled(self.config.settle_velocity_threshold) {
+            self.settled_frames = self.settled_frames.saturating_add(1);
+        } else {
+            self.settled_frames = 0;
+        }
+        self.last_stats.forces_ms += timer.split();
+        self.last_stats.total_ms = timer.total();
+    }
+
+    /// Replace the millisecond clock behind the `last_stats` phase timings
+    /// (see `quality::default_clock`).
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+    }
+
+    /// Track pointer deactivation and damp the particles it was acting on.
+    ///
+    /// When the pointer goes inactive, the particles within its radius are
+    /// recorded; each following step scales their velocity by
+    /// `1 - pointer_release_damping * fade`, with `fade` falling linearly from 1
+    /// to 0 over `pointer_release_duration`. Reactivating the pointer cancels it.
+    fn damp_pointer_release(&mut self, dt: f32) {
+        let pointer = &self.pointer_params;
+        if pointer.active {
+            self.last_active_pointer = Some((pointer.position, pointer.radius.max(0.15)));
+            self.pointer_release = None;
+            return;
+        }
+        if let Some((center, radius)) = self.last_active_pointer.take() {
+            if self.config.pointer_release_damping > 0.0 {
+                let radius_sq = radius * radius;
+                let particles = self
+                    .active
+                    .iter()
+                    .copied()
+                    .filter(|&i| self.particles.position[i as usize].distance_squared(center) <= radius_sq)
+                    .collect();
+                self.pointer_release = Some(PointerRelease { particles, elapsed: 0.0 });
+            }
+        }
+
+        let Some(release) = &mut self.pointer_release else {
+            return;
+        };
+        let duration = self.config.pointer_release_duration;
+        let fade = if duration > 0.0 { 1.0 - release.elapsed / duration } else { 0.0 };
+        if fade <= 0.0 {
+            self.pointer_release = None;
+            return;
+        }
+        let scale = (1.0 - self.config.pointer_release_damping * fade).clamp(0.0, 1.0);
+        for &idx in &release.particles {
+            if let Some(vel) = self.particles.velocity.get_mut(idx as usize) {
+                *vel *= scale;
+            }
+        }
+        release.elapsed += dt;
+    }
+
+    /// Cross-fade one step after an integration path switch: mix the velocity
+    /// carried into the step with the new path's result, the new path's weight
+    /// rising from `1 / (frames + 1)` on the switch step toward 1.
+    fn blend_path_velocities(&mut self) {
+        let frames = self.config.path_blend_frames.max(self.path_blend_remaining);
+        let alpha = 1.0 - self.path_blend_remaining as f32 / (frames + 1) as f32;
+        for ((vel, &carried), &inv_mass) in self
+            .particles
+            .velocity
+            .iter_mut()
+            .zip(&self.path_blend_velocity)
+            .zip(&self.particles.inv_mass)
+        {
+            if inv_mass != 0.0 {
+                *vel = carried.lerp(*vel, alpha);
+            }
+        }
+        self.path_blend_remaining -= 1;
+    }
+
+    /// Explode the particles outward, then let them reassemble into the shape.
+    ///
+    /// Adds a radial velocity kick of `scatter_strength` away from the particle
+    /// centroid and drops `shape_strength` to 0; `step` then ramps it back to
+    /// its prior value over `reform_after` seconds of simulated time. Changes to
+    /// `shape_strength` during the ramp are overwritten. Re-triggering while a
+    /// transition runs restarts the ramp toward the original strength.
+    pub fn trigger_scatter_reform(&mut self, scatter_strength: f32, reform_after: f32) {
+        let count = self.particles.count;
+        if count > 0 {
+            let centroid =
+                self.particles.position[..count].iter().copied().sum::<Vec3>() / count as f32;
+            for i in 0..count {
+                if self.particles.inv_mass[i] == 0.0 {
+                    continue;
+                }
+                let outward = (self.particles.position[i] - centroid).normalize_or_zero();
+                self.particles.velocity[i] += outward * scatter_strength;
+            }
+        }
+
+        let original_strength = match (&self.scatter_reform, self.shape_strength_ramp.take()) {
+            (Some(active), _) => active.original_strength,
+            (None, Some(ramp)) => ramp.target,
+            (None, None) => self.config.shape_strength,
+        };
+        self.config.shape_strength = 0.0;
+        self.scatter_reform = Some(ScatterReform {
+            original_strength,
+            duration: reform_after.max(0.0),
+            elapsed: 0.0,
+        });
+    }
+
+    /// Advance the scatter/reform timer and ramp `shape_strength` back up.
+    fn advance_scatter_reform(&mut self, dt: f32) {
+        let Some(transition) = &mut self.scatter_reform else {
+            return;
+        };
+        transition.elapsed += dt;
+        if transition.elapsed >= transition.duration {
+            self.config.shape_strength = transition.original_strength;
+            self.scatter_reform = None;
+        } else {
+            let ramp = smoothstep(0.0, 1.0, transition.elapsed / transition.duration);
+            self.config.shape_strength = transition.original_strength * ramp;
+        }
+    }
+
+    /// Ease `config.shape_strength` from its current value to `target` over
+    /// `duration` seconds of simulated time, advanced by `step`.
+    ///
+    /// Avoids the pop between free-flight and formed shapes that an abrupt
+    /// change causes. A non-positive `duration` applies `target` at once.
+    /// Cancels a running scatter/reform transition; a later
+    /// `trigger_scatter_reform` reforms toward `target`.
+    pub fn animate_shape_strength(&mut self, target: f32, duration: f32) {
+        self.scatter_reform = None;
+        if duration <= 0.0 {
+            self.config.shape_strength = target;
+            self.shape_strength_ramp = None;
+            return;
+        }
+        self.shape_strength_ramp = Some(ShapeStrengthRamp {
+            from: self.config.shape_strength,
+            target,
+            duration,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Advance the `shape_strength` ramp, if one is running.
+    fn advance_shape_strength_ramp(&mut self, dt: f32) {
+        let Some(ramp) = &mut self.shape_strength_ramp else {
+            return;
+        };
+        ramp.elapsed += dt;
+        if ramp.elapsed >= ramp.duration {
+            self.config.shape_strength = ramp.target;
+            self.shape_strength_ramp = None;
+        } else {
+            let t = smoothstep(0.0, 1.0, ramp.elapsed / ramp.duration);
+            self.config.shape_strength = ramp.from + (ramp.target - ramp.from) * t;
+        }
+    }
+
+    /// Multiply every particle's velocity by `factor`, clamped to `[0, 1]`
+    /// (0 stops everything), e.g. for a "calm down" button. Positions are untouched.
+    pub fn damp_all_velocities(&mut self, factor: f32) {
+        let factor = factor.clamp(0.0, 1.0);
+        for vel in &mut self.particles.velocity {
+            *vel *= factor;
+        }
+    }
+
+    /// True when every dynamic particle moves slower than `velocity_threshold`.
+    pub fn is_settled(&self, velocity_threshold: f32) -> bool {
+        let threshold_sq = velocity_threshold * velocity_threshold;
+        (0..self.particles.count).all(|i| {
+            self.particles.inv_mass[i] == 0.0
+                || self.particles.velocity[i].length_squared() < threshold_sq
+        })
+    }
+
+    /// Friction coefficient used for a contact between particles `i` and `j`:
+    /// `config.friction` times their `particles.friction` multipliers combined
+    /// by `config.material_combine` (Granular contacts may raise it further).
+    pub fn contact_friction(&self, i: usize, j: usize) -> f32 {
+        pair_friction(&self.config, &self.particles.friction, i, j)
+    }
+
+    /// Restitution used for a contact between particles `i` and `j`:
+    /// `config.restitution` times the smaller phase multiplier and the
+    /// `particles.restitution` multipliers combined by `config.material_combine`.
+    pub fn contact_restitution(&self, i: usize, j: usize) -> f32 {
+        pair_restitution(&self.config, &self.particles.phase, &self.particles.restitution, i, j)
+    }
+
+    /// Bounding sphere of the live particle positions for camera auto-fit: the
+    /// centroid and the largest distance from it (particle radii not included).
+    /// Killed particles are ignored. Returns `(Vec3::ZERO, 0.0)` if none is alive.
+    pub fn bounding_sphere(&self) -> (Vec3, f32) {
+        let live = || self.active.iter().map(|&i| self.particles.position[i as usize]);
+        if self.active.is_empty() {
+            return (Vec3::ZERO, 0.0);
+        }
+        let center = live().sum::<Vec3>() / self.active.len() as f32;
+        let radius_sq = live()
+            .map(|p| p.distance_squared(center))
+            .fold(0.0, f32::max);
+        (center, radius_sq.sqrt())
+    }
+
+    /// Number of consecutive steps (up to the latest) that ended settled under
+    /// `config.settle_velocity_threshold`. Smooths out single quiet frames.
+    pub fn settled_frames(&self) -> u32 {
+        self.settled_frames
+    }
+
+    /// Set the collision radius of particle `index`.
+    ///
+    /// With `mass_from_radius` on, a dynamic particle's inverse mass follows the
+    /// new radius; static particles keep zero inverse mass.
+    pub fn set_particle_radius(&mut self, index: usize, radius: f32) {
+        if index >= self.particles.count {
+            return;
+        }
+        self.particles.radius[index] = radius;
+        if self.config.mass_from_radius && self.particles.inv_mass[index] != 0.0 {
+            self.particles.inv_mass[index] =
+                radius_inv_mass(radius, self.config.mass_radius_density);
+        }
+    }
+
+    /// Turn radius-driven mass on or off for all dynamic particles.
+    ///
+    /// Enabling saves each particle's mass in `uncoupled_inv_mass` and sets
+    /// `inv_mass = 1 / (density * 4/3 pi r^3)`; disabling restores the saved
+    /// masses (unit mass for particles added while coupled). Static particles
+    /// are left alone either way.
+    pub fn couple_mass_to_radius(&mut self, enabled: bool, density: f32) {
+        let was_enabled = self.config.mass_from_radius;
+        self.config.mass_from_radius = enabled;
+        self.config.mass_radius_density = density;
+        let particles = &mut self.particles;
+        for i in 0..particles.count {
+            if particles.inv_mass[i] == 0.0 {
+                continue;
+            }
+            if enabled {
+                if !was_enabled {
+                    particles.uncoupled_inv_mass[i] = particles.inv_mass[i];
+                }
+                particles.inv_mass[i] = radius_inv_mass(particles.radius[i], density);
+            } else if was_enabled {
+                particles.inv_mass[i] = particles.uncoupled_inv_mass[i];
+            }
+        }
+    }
+
+    /// Register a force field evaluated for every dynamic particle in each
+    /// force pass, as `field(position, velocity, time) -> acceleration`.
+    ///
+    /// Multiple fields add up. Intended for Rust embedders; not exposed to WASM.
+    pub fn add_force_field(&mut self, field: ForceField) {
+        self.force_fields.push(field);
+    }
+
+    /// Remove all registered force fields.
+    pub fn clear_force_fields(&mut self) {
+        self.force_fields.clear();
+    }
+
+    /// Accumulate an external force on particle `index` for the next step.
+    ///
+    /// Forces added between steps are summed, applied during every substep
+    /// of the following `step()`, and then cleared.
+    pub fn add_external_force(&mut self, index: usize, force: Vec3) {
+        if index < self.particles.count {
+            self.particles.external_force[index] += force;
+        }
+    }
+
+    /// Overwrite every particle's velocity from interleaved `[x, y, z, ...]`.
+    ///
+    /// Returns `false` and leaves velocities untouched unless `xyz` holds
+    /// exactly `count * 3` values.
+    pub fn set_velocities(&mut self, xyz: &[f32]) -> bool {
+        if xyz.len() != self.particles.count * 3 {
+            return false;
         }
+        for (vel, v) in self.particles.velocity.iter_mut().zip(xyz.chunks_exact(3)) {
+            *vel = Vec3::from_slice(v);
+        }
+        true
     }
 
     /// Compute shape targets for all particles (Phase 1).
     ///
     /// This is called once per step (not per substep) since shape targets
     /// don't change within a frame.
-    fn compute_shape_targets(&mut self, time: f32, tex_size: usize) {
+    fn compute_shape_targets(&mut self, time: f32, dt: f32, tex_size: usize) {
+        let sp = &mut self.shape_params;
+        sp.rot_a = spin_rotation(sp.rot_a, sp.rot_a_velocity, dt);
+        sp.rot_b = spin_rotation(sp.rot_b, sp.rot_b_velocity, dt);
         let sp = &self.shape_params;
         let morph_blend = ease_in_out_cubic(sp.morph);
         let shape_a = sp.shape_a;
@@ -321,32 +932,55 @@ impl Solver {
         let rot_b = sp.rot_b;
         let fractal_a = sp.fractal_a;
         let fractal_b = sp.fractal_b;
-        let audio_bass = sp.audio_bass;
-        let audio_mid = sp.audio_mid;
-        let audio_treble = sp.audio_treble;
+        let params = TargetParams {
+            audio_bass: sp.audio_bass,
+            audio_mid: sp.audio_mid,
+            audio_treble: sp.audio_treble,
+            eq_zones: sp.eq_zones,
+            fractal_iterations: sp.fractal_iterations,
+            thickness: sp.shape_thickness,
+        };
+        let attract_phases = self.config.shape_attract_phases;
         let count = self.particles.count;
+        let custom = &self.custom_targets;
+        let custom_target = |sid: u32, i: usize, rot: &glam::Mat3| {
+            (sid == CUSTOM_SHAPE && !custom.is_empty()).then(|| *rot * custom[i % custom.len()])
+        };
 
         for i in 0..count {
-            let id_x = (i % tex_size) as f32 / tex_size as f32;
-            let id_y = (i / tex_size) as f32 / tex_size as f32;
-
-            let target_a = target_for(
-                shape_a, id_x, id_y,
-                time * 0.55,
-                &rot_a, &fractal_a,
-                audio_bass, audio_mid, audio_treble,
-            );
-            let target_b = target_for(
-                shape_b, id_x, id_y,
-                time * 0.58 + 2.5,
-                &rot_b, &fractal_b,
-                audio_bass, audio_mid, audio_treble,
-            );
+            // Targets follow the particle's own shape index, not its slot
+            let k = self.particles.shape_index[i] as usize;
+            let id_x = (k % tex_size) as f32 / tex_size as f32;
+            let id_y = (k / tex_size) as f32 / tex_size as f32;
+
+            let target_a = custom_target(shape_a, k, &rot_a).unwrap_or_else(|| {
+                target_for(shape_a, id_x, id_y, time * 0.55, &rot_a, &fractal_a, &params)
+            });
+            let target_b = custom_target(shape_b, k, &rot_b).unwrap_or_else(|| {
+                target_for(shape_b, id_x, id_y, time * 0.58 + 2.5, &rot_b, &fractal_b, &params)
+            });
             self.particles.target_pos[i] = target_a.lerp(target_b, morph_blend);
-            self.particles.target_weight[i] = smoothstep(0.03, 0.9, self.particles.hash[i]);
+            // Phases masked out of shape attraction get zero affinity
+            self.particles.target_weight[i] = if attract_phases[self.particles.phase[i] as usize] {
+                smoothstep(0.03, 0.9, self.particles.hash[i])
+            } else {
+                0.0
+            };
         }
     }
 
+    /// Set the point cloud used as shape `CUSTOM_SHAPE` (e.g. a logo or scanned mesh).
+    ///
+    /// Particle `i` targets `points[k % points.len()]` with `k` its
+    /// `particles.shape_index` (initially `i`), rotated like the built-in
+    /// shapes, so supply at least as many points as particles for full coverage.
+    /// Order the points spatially to avoid crossing paths while morphing. With an
+    /// empty cloud the custom shape falls back to the star used for unknown ids.
+    pub fn set_custom_shape_targets(&mut self, points: &[Vec3]) {
+        self.custom_targets.clear();
+        self.custom_targets.extend_from_slice(points);
+    }
+
     /// Apply all forces to particle velocities (Phase 2).
     ///
     /// This computes flow forces, shape attraction, pointer interaction,
@@ -359,24 +993,71 @@ impl Solver {
         let shape_strength = self.config.shape_strength;
         let speed_multiplier = self.shape_params.speed_multiplier;
         let roam_radius = self.config.boundary_radius;
-        let count = self.particles.count;
+        let boundary_return = self.config.boundary_return_stiffness;
 
         // Snapshot audio params to avoid borrow issues
-        let audio_bass = self.shape_params.audio_bass;
-        let audio_mid = self.shape_params.audio_mid;
-        let audio_treble = self.shape_params.audio_treble;
-        let audio_energy = self.shape_params.audio_energy;
+        let audio = AudioParams {
+            bass: self.shape_params.audio_bass,
+            mid: self.shape_params.audio_mid,
+            treble: self.shape_params.audio_treble,
+            energy: self.shape_params.audio_energy,
+            sparkle_frequency: self.shape_params.sparkle_frequency,
+            sparkle_amount: self.shape_params.sparkle_amount,
+        };
         let shape_a = self.shape_params.shape_a;
         let shape_b = self.shape_params.shape_b;
 
         // Derived constants from shape_strength
         let structure = smoothstep(0.1, 0.9, shape_strength);
         let calm_factor = smoothstep(0.5, 1.0, shape_strength);
+        let vortex_strength = self.config.flow_vortex_strength;
+        let z_wave = [self.config.flow_z_wave_amplitude, self.config.flow_z_wave_frequency];
+        let flow_gravity = self.config.flow_gravity;
+        let damping_min = self.config.linear_damping_min;
+        let damping_max = self.config.linear_damping_max;
+        let quadratic_drag = if self.config.quadratic_drag_enabled {
+            self.config.quadratic_drag
+        } else {
+            0.0
+        };
+        let phase_drag = self.config.phase_drag;
+        let close_distance = self.config.shape_close_distance;
+        let snap_distance = self.config.shape_snap_distance;
+        // 1 at the target, fading to 0 at `edge` (a zero edge disables the term)
+        let falloff = |edge: f32, dist: f32| {
+            if edge > 0.0 { smoothstep(edge, 0.0, dist) } else { 0.0 }
+        };
 
-        let is_equalizer_mode = shape_a == 12 || shape_b == 12;
-        let is_free_flight = shape_strength < 0.05;
+        let shape_as_force = !(self.config.shape_as_constraint && self.config.collisions_enabled);
+        let artistic = self.config.artistic_forces_enabled;
+        let is_equalizer_mode = artistic && (shape_a == 12 || shape_b == 12);
+        let is_free_flight = artistic && shape_strength < 0.05;
+        let is_sphere_boundary = self.config.boundary_shape == BoundaryShape::Sphere;
+        let attractor_gm = if self.config.central_attractor_enabled {
+            self.config.nbody_g * self.config.central_attractor_mass
+        } else {
+            0.0
+        };
+        let attractor_pos = self.config.central_attractor_pos;
+        let gravity_dir = if self.config.gravity_follows_pointer {
+            self.pointer_params.position.try_normalize().unwrap_or(Vec3::NEG_Y)
+        } else {
+            Vec3::NEG_Y
+        };
+        let physics_gravity = if self.config.gravity_follows_pointer {
+            gravity_dir * self.config.gravity.length()
+        } else {
+            self.config.gravity
+        };
+        let attractor_soft_sq = self.config.nbody_softening * self.config.nbody_softening;
+        let beam = if self.config.beam_enabled && self.config.beam_strength != 0.0 {
+            Some((self.config.beam_start, self.config.beam_end, self.config.beam_strength))
+        } else {
+            None
+        };
 
-        for i in 0..count {
+        for &idx in &self.active {
+            let i = idx as usize;
             if self.particles.inv_mass[i] == 0.0 { continue; }
             let pos = self.particles.position[i];
             let mut vel = self.particles.velocity[i];
@@ -385,51 +1066,64 @@ impl Solver {
             let id_y = (i / tex_size) as f32 / tex_size as f32;
             let layer_hash = hash12(id_x * 23.7, id_y * 23.7);
 
-            // ==== 1. FLOW FORCES ====
-            let flow_raw = compute_flow_force(pos, id_hash, time, calm_factor);
-            let flow_scale = mix_f32(0.35, 0.55, 1.0 - structure);
-            let mut acc = flow_raw * flow_scale;
-            acc.y -= 0.04; // gravity
-
-            let vel_mag = vel.length();
-            acc -= vel * vel_mag * 0.018; // quadratic drag
-
-            let drag = mix_f32(0.93, 0.965, calm_factor);
-            vel *= drag;
-
-            // ==== 2. SHAPE ATTRACTION ====
-            let desired = self.particles.target_pos[i];
-            let affinity = self.particles.target_weight[i];
-            let shape_weight = shape_strength * affinity;
-
-            let to_shape = desired - pos;
-            let dist = to_shape.length().max(0.005);
-            let dir_to_shape = to_shape / dist;
-
-            let spring_strength = 15.0 + 10.0 * calm_factor;
-            let damping_factor = (-dist * 0.4_f32).exp();
-            let mut shape_force = to_shape * spring_strength * shape_weight * damping_factor;
-
-            // Close-range corrections
-            let close_range = smoothstep(0.5, 0.0, dist);
-            shape_force += dir_to_shape * 6.0 * shape_weight * close_range;
-
-            let near_target = smoothstep(0.15, 0.0, dist);
-            shape_force += dir_to_shape * 3.0 * shape_weight * near_target;
-            vel *= mix_f32(1.0, 0.85, near_target * shape_weight);
-
-            let cohesion = smoothstep(0.0, 0.55, shape_weight);
-            acc = Vec3::lerp(acc, shape_force * 2.2, cohesion * 0.92);
-            acc += shape_force * 0.6;
-            vel *= mix_f32(0.96, 0.87, cohesion * calm_factor);
+            let mut acc = if artistic {
+                // ==== 1. FLOW FORCES ====
+                let mut flow_acc = scaled_flow_force(
+                    pos, id_hash, time, structure, calm_factor, vortex_strength, z_wave,
+                );
+                flow_acc += gravity_dir * flow_gravity;
+
+                let vel_mag = vel.length();
+                let drag_scale = phase_drag[self.particles.phase[i] as usize];
+                flow_acc -= vel * vel_mag * quadratic_drag * drag_scale;
+
+                let drag = mix_f32(damping_min, damping_max, calm_factor).powf(drag_scale);
+                vel *= drag;
+
+                // ==== 2. SHAPE ATTRACTION ====
+                let desired = self.particles.target_pos[i];
+                let affinity = self.particles.target_weight[i];
+                let shape_weight = shape_strength * affinity;
+
+                let to_shape = desired - pos;
+                let dist = to_shape.length().max(0.005);
+                let dir_to_shape = to_shape / dist;
+
+                let spring_strength = 15.0 + 10.0 * calm_factor;
+                let damping_factor = (-dist * 0.4_f32).exp();
+                let mut shape_force = to_shape * spring_strength * shape_weight * damping_factor;
+
+                // Close-range corrections
+                let close_range = falloff(close_distance, dist);
+                shape_force += dir_to_shape * 6.0 * shape_weight * close_range;
+
+                let near_target = falloff(snap_distance, dist);
+                shape_force += dir_to_shape * 3.0 * shape_weight * near_target;
+                vel *= mix_f32(1.0, 0.85, near_target * shape_weight);
+
+                // With `shape_as_constraint` the XPBD target constraint does the pulling;
+                // the flow is still quieted near the shape and the settling damping kept
+                let cohesion = smoothstep(0.0, 0.55, shape_weight);
+                if shape_as_force {
+                    flow_acc = Vec3::lerp(flow_acc, shape_force * 2.2, cohesion * 0.92);
+                    flow_acc += shape_force * 0.6;
+                } else {
+                    flow_acc *= 1.0 - cohesion * 0.92;
+                }
+                vel *= mix_f32(0.96, 0.87, cohesion * calm_factor);
+                flow_acc
+            } else {
+                physics_gravity
+            };
 
             // ==== POINTER INTERACTION ====
             if self.pointer_params.active {
                 let result = compute_pointer_force(
                     pos, vel, id_hash, time, &self.pointer_params,
                 );
-                acc += result.acc;
-                vel += result.vel_add;
+                let falloff = self.pointer_falloff(pos);
+                acc += result.acc * falloff;
+                vel += result.vel_add * falloff;
                 vel *= result.vel_scale;
                 if let Some(cap) = result.speed_cap {
                     let speed = vel.length();
@@ -439,19 +1133,33 @@ impl Solver {
                 }
             }
 
+            // ==== CENTRAL ATTRACTOR ====
+            if attractor_gm != 0.0 {
+                let to_particle = pos - attractor_pos;
+                let dist_sq = to_particle.length_squared();
+                if dist_sq > 1e-12 {
+                    let dir = to_particle / dist_sq.sqrt();
+                    acc -= dir * attractor_gm / (dist_sq + attractor_soft_sq);
+                }
+            }
+
+            // ==== BEAM ====
+            if let Some((start, end, strength)) = beam {
+                acc += (closest_point_on_segment(pos, start, end) - pos) * strength;
+            }
+
             // ==== 3. BOUNDARY ====
             let dist_center = pos.length();
-            if dist_center > roam_radius {
-                acc -= pos / dist_center * (dist_center - roam_radius) * 0.6;
+            if is_sphere_boundary && dist_center > roam_radius {
+                acc -= pos / dist_center * (dist_center - roam_radius) * boundary_return;
             }
 
             // ==== 4. AUDIO REACTIVITY (equalizer mode) ====
             if is_equalizer_mode {
-                acc *= audio_boost_factor(audio_energy);
-                let (audio_acc, audio_vel) = compute_audio_force(
-                    pos, desired, id_hash, layer_hash, time,
-                    audio_bass, audio_mid, audio_treble, audio_energy,
-                );
+                acc *= audio_boost_factor(audio.energy);
+                let desired = self.particles.target_pos[i];
+                let (audio_acc, audio_vel) =
+                    compute_audio_force(pos, desired, id_hash, layer_hash, time, &audio);
                 acc += audio_acc;
                 vel += audio_vel;
             }
@@ -532,10 +1240,20 @@ impl Solver {
                 );
             }
 
-            // ==== 6. INTEGRATION (velocity only) ====
+            // ==== 6. EXTERNAL FORCE (host-injected) ====
+            acc += self.particles.external_force[i];
+            for field in &self.force_fields {
+                acc += field(pos, vel, time);
+            }
+
+            // ==== 7. INTEGRATION (velocity only) ====
+            self.particles.acceleration[i] = acc;
             vel += acc * sub_dt;
-            // Additional damping when speed multiplier is active
-            vel *= mix_f32(1.0, 0.915, step_f32(0.0001, speed_multiplier));
+            // Additional damping when speed multiplier is active; physics sandboxes
+            // keep their momentum so orbits hold
+            if artistic {
+                vel *= mix_f32(1.0, 0.915, step_f32(0.0001, speed_multiplier));
+            }
             // Speed cap
             let speed = vel.length();
             if speed > 18.0 {
@@ -546,61 +1264,484 @@ impl Solver {
         }
     }
 
-    /// Solve boundary constraint for XPBD mode.
+    /// Sample the flow field acceleration at an arbitrary point.
     ///
-    /// Pushes predicted positions back inside the boundary sphere.
-    fn solve_boundary_constraint(&mut self) {
-        let boundary = self.config.boundary_radius;
-        for i in 0..self.particles.count {
-            let pos = self.particles.predicted[i];
-            let dist = pos.length();
-            if dist > boundary {
-                let correction = pos / dist * (boundary - dist);
-                self.particles.corrections[i] += correction;
-                self.particles.correction_counts[i] += 1;
-            }
-        }
+    /// Returns the same flow contribution `apply_forces` gives a particle at
+    /// `pos` (curl noise octaves, wandering vortex, z-wave, scaled by the
+    /// current `shape_strength`), using a zero per-particle hash.
+    pub fn sample_flow(&self, pos: Vec3, time: f32) -> Vec3 {
+        let shape_strength = self.config.shape_strength;
+        let structure = smoothstep(0.1, 0.9, shape_strength);
+        let calm_factor = smoothstep(0.5, 1.0, shape_strength);
+        let z_wave = [self.config.flow_z_wave_amplitude, self.config.flow_z_wave_frequency];
+        scaled_flow_force(
+            pos, 0.0, time, structure, calm_factor, self.config.flow_vortex_strength, z_wave,
+        )
     }
 
-    /// Create a cloth patch from particles [start_idx .. start_idx + width*height].
-    ///
-    /// Sets particles to Phase::Cloth and creates distance constraints between
-    /// grid neighbors (horizontal, vertical, diagonal) and bending constraints
-    /// between opposite vertices of quads.
+    /// Smoothed particle velocity at an arbitrary point.
     ///
-    /// `spacing` is the rest distance between adjacent particles.
-    /// `stiffness` is the distance constraint compliance (lower = stiffer).
-    /// `bending_stiffness` is the bending constraint compliance.
-    pub fn create_cloth(
-        &mut self,
-        start_idx: usize,
-        width: usize,
-        height: usize,
-        spacing: f32,
-        stiffness: f32,
-        bending_stiffness: f32,
-    ) {
-        let total = width * height;
-        if start_idx + total > self.particles.count {
-            return;
+    /// Averages the velocities of particles within `radius` of `pos`, weighted
+    /// by the poly6 kernel. Returns zero when no particle is in range.
+    /// Rebuilds the grid from the current positions; use `sample_velocities`
+    /// to sample many points with a single rebuild.
+    pub fn sample_velocity(&mut self, pos: Vec3, radius: f32) -> Vec3 {
+        self.sample_velocities(&[pos], radius)[0]
+    }
+
+    /// `sample_velocity` at each of `points`, rebuilding the grid once.
+    pub fn sample_velocities(&mut self, points: &[Vec3], radius: f32) -> Vec<Vec3> {
+        if radius <= 0.0 {
+            return vec![Vec3::ZERO; points.len()];
         }
+        self.rebuild_grid_from_positions();
+        let mut nearby = Vec::new();
+        points.iter().map(|&pos| self.velocity_near(pos, radius, &mut nearby)).collect()
+    }
 
-        // Position particles in a grid and set phase
-        for row in 0..height {
-            for col in 0..width {
-                let idx = start_idx + row * width + col;
-                self.particles.position[idx] = Vec3::new(
-                    (col as f32 - width as f32 / 2.0) * spacing,
-                    2.0, // start elevated
-                    (row as f32 - height as f32 / 2.0) * spacing,
-                );
-                self.particles.velocity[idx] = Vec3::ZERO;
-                self.particles.phase[idx] = Phase::Cloth;
-            }
+    /// Poly6-weighted mean velocity around `pos`, from the current grid.
+    /// `nearby` is scratch space reused across calls.
+    fn velocity_near(&self, pos: Vec3, radius: f32, nearby: &mut Vec<usize>) -> Vec3 {
+        nearby.clear();
+        self.grid.query_radius(pos, radius, |j| nearby.push(j as usize));
+        nearby.sort_unstable();
+        nearby.dedup();
+
+        let mut weighted = Vec3::ZERO;
+        let mut total = 0.0;
+        for &j in nearby.iter() {
+            let r = self.grid.min_image_diff(self.particles.position[j], pos).length();
+            let w = poly6_kernel(r, radius);
+            weighted += self.particles.velocity[j] * w;
+            total += w;
         }
+        if total > 0.0 { weighted / total } else { Vec3::ZERO }
+    }
 
-        // Helper to get particle index from grid coordinates
-        let idx = |row: usize, col: usize| -> u32 {
+    /// Metaball field at an arbitrary point: the poly6 kernel summed over
+    /// Fluid and Gas particles within `h` of `pos` (unit mass, like `density`).
+    ///
+    /// Smooth in `pos` and zero farther than `h` from every fluid particle, so
+    /// it can be isosurfaced directly. Rebuilds the grid from the current
+    /// positions; use `sample_fields` to sample many points with one rebuild.
+    pub fn sample_field(&mut self, pos: Vec3, h: f32) -> f32 {
+        self.sample_fields(&[pos], h)[0]
+    }
+
+    /// `sample_field` at each of `points`, rebuilding the grid once.
+    pub fn sample_fields(&mut self, points: &[Vec3], h: f32) -> Vec<f32> {
+        if h <= 0.0 {
+            return vec![0.0; points.len()];
+        }
+        self.rebuild_grid_from_positions();
+        let mut nearby = Vec::new();
+        points.iter().map(|&pos| self.field_near(pos, h, &mut nearby)).collect()
+    }
+
+    /// Metaball field at `pos` from the current grid. `nearby` is scratch
+    /// space reused across calls.
+    fn field_near(&self, pos: Vec3, h: f32, nearby: &mut Vec<usize>) -> f32 {
+        nearby.clear();
+        self.grid.query_radius(pos, h, |j| nearby.push(j as usize));
+        nearby.sort_unstable();
+        nearby.dedup();
+
+        nearby
+            .iter()
+            .copied()
+            .filter(|&j| matches!(self.particles.phase[j], Phase::Fluid | Phase::Gas))
+            .map(|j| {
+                let r = self.grid.min_image_diff(self.particles.position[j], pos).length();
+                poly6_kernel(r, h)
+            })
+            .sum()
+    }
+
+    /// Sample the pointer acceleration at an arbitrary point.
+    ///
+    /// Uses the current `pointer_params` with a zero per-particle hash;
+    /// returns zero when the pointer is inactive.
+    pub fn sample_pointer_force(&self, pos: Vec3, time: f32) -> Vec3 {
+        compute_pointer_force(pos, Vec3::ZERO, 0.0, time, &self.pointer_params).acc
+            * self.pointer_falloff(pos)
+    }
+
+    /// Scale applied to the pointer force at `pos`: fades from 1 to 0 inside
+    /// `config.pointer_min_distance` of the pointer.
+    fn pointer_falloff(&self, pos: Vec3) -> f32 {
+        let min_distance = self.config.pointer_min_distance;
+        if min_distance <= 0.0 {
+            return 1.0;
+        }
+        let dist = (self.pointer_params.position - pos).length();
+        smoothstep(0.0, min_distance, dist)
+    }
+
+    /// Solve the collision constraints of solver iteration `iteration` of the
+    /// current XPBD substep (`sub_dt` long) into the shared correction buffers,
+    /// in a fixed order: particle contacts (with Coulomb friction), capsule
+    /// contacts, then the boundary.
+    ///
+    /// Iteration 0 first detects the substep's particle and capsule contacts
+    /// from the predicted positions, using the grid `step` built for them;
+    /// later iterations reuse that contact set. `step` runs this pass after the
+    /// other constraints of each iteration, before the corrections are applied.
+    ///
+    /// Contacts never push a particle outward past the spherical boundary:
+    /// the outward part of its contact correction is limited to the room left
+    /// between its predicted position and the wall. A particle squeezed
+    /// between a static particle and the wall therefore rests against the wall
+    /// instead of being handed back and forth between the two constraints.
+    pub fn solve_collision_constraints(&mut self, iteration: u32, sub_dt: f32) {
+        if iteration == 0 {
+            self.detect_collisions();
+        }
+        let count = self.particles.count;
+        let mut contact_corrections = std::mem::take(&mut self.contact_corrections);
+        contact_corrections.clear();
+        contact_corrections.resize(count, Vec3::ZERO);
+
+        let granular = GranularFriction {
+            friction: self.config.granular_friction,
+            static_velocity: self.config.granular_static_velocity,
+        };
+        solve_contacts(
+            &self.contacts,
+            &self.particles.predicted,
+            &self.particles.position,
+            &self.particles.inv_mass,
+            &mut contact_corrections,
+            &mut self.particles.correction_counts,
+            ContactParams {
+                phase: &self.particles.phase,
+                friction: |i, j| pair_friction(&self.config, &self.particles.friction, i, j),
+                granular,
+                contact_sor: self.config.contact_relaxation,
+                max_correction: self.config.max_penetration_correction,
+                dt: sub_dt,
+            },
+        );
+
+        // Capsule (bond segment) contacts for ropes and hair
+        solve_capsule_contacts(
+            &self.capsule_contacts,
+            &self.particles.inv_mass,
+            &mut contact_corrections,
+            &mut self.particles.correction_counts,
+        );
+
+        let wall = (self.config.boundary_shape == BoundaryShape::Sphere)
+            .then_some(self.config.boundary_radius);
+        for (i, correction) in contact_corrections.iter_mut().enumerate() {
+            if let Some(boundary) = wall {
+                let pos = self.particles.predicted[i];
+                let dist = pos.length();
+                if dist > 1e-8 {
+                    let normal = pos / dist;
+                    let room = (boundary - dist).max(0.0);
+                    let outward = correction.dot(normal);
+                    if outward > room {
+                        *correction -= normal * (outward - room);
+                    }
+                }
+            }
+            self.particles.corrections[i] += *correction;
+        }
+        self.contact_corrections = contact_corrections;
+
+        self.solve_boundary_constraint();
+    }
+
+    /// Detect this substep's particle and capsule contacts from the predicted
+    /// positions, using the grid `step` built for them (built here if the
+    /// solver has never collided yet).
+    fn detect_collisions(&mut self) {
+        let count = self.particles.count;
+        if self.grid.table_size() == 0 {
+            self.ensure_grid();
+            self.grid.set_periodic(self.periodic_half_extents());
+            self.grid.build_masked(&self.particles.predicted, count, &self.particles.alive);
+        }
+        let max_contacts = self.config.max_contacts_per_particle;
+        if self.config.speculative_margin > 0.0 {
+            self.contacts = detect_speculative_contacts(
+                &self.particles.predicted,
+                &self.particles.position,
+                &self.particles.radius,
+                &self.particles.inv_mass,
+                count,
+                &self.grid,
+                self.config.speculative_margin,
+            );
+            if max_contacts > 0 {
+                cap_contacts_per_particle(&mut self.contacts, count, max_contacts);
+            }
+        } else {
+            self.contacts = detect_contacts(
+                &self.particles.predicted,
+                &self.particles.radius,
+                &self.particles.inv_mass,
+                count,
+                &self.grid,
+                max_contacts,
+            );
+        }
+        if self.active.len() < count {
+            let alive = &self.particles.alive;
+            self.contacts.retain(|c| alive[c.i as usize] && alive[c.j as usize]);
+        }
+        self.capsule_contacts = if self.config.capsule_contacts {
+            let segments = capsule_segments(&self.distance_constraints);
+            // A bonded pair is kept apart by its capsules; a sphere contact would push it twice
+            let mut bonded: Vec<(u32, u32)> =
+                segments.iter().map(|&[i, j]| (i.min(j), i.max(j))).collect();
+            bonded.sort_unstable();
+            self.contacts
+                .retain(|c| bonded.binary_search(&(c.i.min(c.j), c.i.max(c.j))).is_err());
+            detect_capsule_contacts(
+                &self.particles.predicted,
+                &self.particles.radius,
+                &self.particles.inv_mass,
+                &segments,
+                &self.grid,
+            )
+        } else {
+            Vec::new()
+        };
+        self.last_stats.contact_count = (self.contacts.len() + self.capsule_contacts.len()) as u32;
+    }
+
+    /// Solve boundary constraint for XPBD mode.
+    ///
+    /// Pushes predicted positions back inside the boundary sphere, weighted by
+    /// `config.boundary_stiffness` (infinite = full projection). Periodic
+    /// boundaries have no constraint; positions are wrapped after the substep.
+    fn solve_boundary_constraint(&mut self) {
+        if self.config.boundary_shape != BoundaryShape::Sphere {
+            return;
+        }
+        let boundary = self.config.boundary_radius;
+        // XPBD with compliance 1/stiffness on a unit-weight particle
+        let weight = 1.0 / (1.0 + 1.0 / self.config.boundary_stiffness.max(0.0));
+        for i in 0..self.particles.count {
+            let pos = self.particles.predicted[i];
+            let dist = pos.length();
+            if dist > boundary {
+                let correction = pos / dist * (boundary - dist) * weight;
+                self.particles.corrections[i] += correction;
+                self.particles.correction_counts[i] += 1;
+            }
+        }
+    }
+
+    /// Project particles outside the boundary sphere back onto it and reflect
+    /// their outward velocity, scaled by `config.restitution`.
+    fn clamp_to_boundary_sphere(&mut self) {
+        if self.config.boundary_shape != BoundaryShape::Sphere {
+            return;
+        }
+        let boundary = self.config.boundary_radius;
+        let restitution = self.config.restitution;
+        for i in 0..self.particles.count {
+            let pos = self.particles.position[i];
+            let dist = pos.length();
+            if dist <= boundary || self.particles.inv_mass[i] == 0.0 {
+                continue;
+            }
+            let normal = pos / dist;
+            self.particles.position[i] = normal * boundary;
+            let vn = self.particles.velocity[i].dot(normal);
+            if vn > 0.0 {
+                self.particles.velocity[i] -= normal * vn * (1.0 + restitution);
+            }
+        }
+    }
+
+    /// Scale back particles that moved further than
+    /// `config.max_particle_displacement` this step.
+    ///
+    /// The position delta (minimum image in a periodic box) and the velocity
+    /// are shrunk by the same factor, so the particle continues in the same
+    /// direction at a speed consistent with the clamped move.
+    fn clamp_frame_displacement(&mut self) {
+        let max_move = self.config.max_particle_displacement;
+        let period = self.periodic_half_extents().map(|half| half * 2.0);
+        let mut clamped = false;
+        for i in 0..self.particles.count {
+            let start = self.frame_start_position[i];
+            let mut delta = self.particles.position[i] - start;
+            if let Some(period) = period {
+                delta -= period * (delta / period).round();
+            }
+            let dist = delta.length();
+            if dist <= max_move {
+                continue;
+            }
+            let scale = max_move / dist;
+            self.particles.position[i] = start + delta * scale;
+            self.particles.velocity[i] *= scale;
+            clamped = true;
+        }
+        if clamped {
+            self.wrap_periodic_positions();
+        }
+    }
+
+    /// One standalone PBF density pass: solve, apply the averaged corrections
+    /// to `predicted`, and leave the correction buffers zeroed again.
+    fn fluid_density_pass(&mut self) {
+        crate::constraints::density::solve_density_constraints(
+            &mut self.particles,
+            &self.grid,
+            self.config.fluid_rest_density,
+            self.config.smoothing_radius,
+            self.config.tensile_correction,
+            self.config.max_fluid_neighbors,
+            self.config.free_contributes_to_density,
+        );
+        for i in 0..self.particles.count {
+            if self.particles.correction_counts[i] > 0 {
+                self.particles.predicted[i] += self.particles.corrections[i]
+                    / self.particles.correction_counts[i] as f32;
+            }
+            self.particles.corrections[i] = Vec3::ZERO;
+            self.particles.correction_counts[i] = 0;
+        }
+    }
+
+    /// Boundary response for a fluid particle whose unconstrained motion this
+    /// substep crossed the boundary sphere.
+    ///
+    /// `vel` is the velocity derived from the constrained position. Its normal
+    /// component is replaced by the incoming normal speed reflected and scaled
+    /// by `fluid_restitution` (0 = stick), and a hashed tangential spray
+    /// proportional to the rebound is added for a visual splash. Particles
+    /// that did not hit the boundary keep `vel`.
+    fn fluid_boundary_splash(&self, i: usize, vel: Vec3, sub_dt: f32, time: f32) -> Vec3 {
+        const SPLASH_SPRAY: f32 = 0.3;
+        let pos = self.particles.position[i];
+        let incoming = self.particles.velocity[i];
+        let unconstrained = pos + incoming * sub_dt;
+        let dist = unconstrained.length();
+        if dist <= self.config.boundary_radius {
+            return vel;
+        }
+        let normal = unconstrained / dist;
+        let incoming_normal = incoming.dot(normal);
+        if incoming_normal <= 0.0 {
+            return vel;
+        }
+
+        let rebound = incoming_normal * self.config.fluid_restitution.max(0.0);
+        let tangent_a = normal.any_orthonormal_vector();
+        let tangent_b = normal.cross(tangent_a);
+        let spray_angle = hash12(self.particles.hash[i] * 91.7 + time, time * 13.3)
+            * std::f32::consts::TAU;
+        let spray = (tangent_a * spray_angle.cos() + tangent_b * spray_angle.sin())
+            * rebound
+            * SPLASH_SPRAY;
+
+        vel - normal * (vel.dot(normal) + rebound) + spray
+    }
+
+    /// Half extents of the periodic domain (each at least
+    /// `MIN_PERIODIC_HALF_EXTENT`), or `None` for a spherical boundary.
+    fn periodic_half_extents(&self) -> Option<Vec3> {
+        match self.config.boundary_shape {
+            BoundaryShape::Periodic { half_extents } => {
+                Some(half_extents.max(Vec3::splat(MIN_PERIODIC_HALF_EXTENT)))
+            }
+            BoundaryShape::Sphere => None,
+        }
+    }
+
+    /// Capture the current particle positions.
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            position: self.particles.position[..self.particles.count].to_vec(),
+            layout: self.slot_layout,
+        }
+    }
+
+    /// Write positions interpolated between `prev` (alpha = 0) and the current
+    /// state (alpha = 1) into `out`, for rendering between fixed steps.
+    ///
+    /// In a periodic box particles that wrapped take the short way across the
+    /// seam. Particles missing from `prev` (added since) use their current
+    /// position, as do all particles when `prev` predates a `reorder_by_cell`.
+    pub fn lerp_output(&self, alpha: f32, prev: &StateSnapshot, out: &mut [Vec3]) {
+        let period = self.periodic_half_extents().map(|half| half * 2.0);
+        let count = self.particles.count.min(out.len());
+        let before = if prev.layout == self.slot_layout { &prev.position[..] } else { &[] };
+        for (i, slot) in out[..count].iter_mut().enumerate() {
+            let current = self.particles.position[i];
+            *slot = match before.get(i) {
+                Some(&before) => {
+                    let mut diff = current - before;
+                    if let Some(period) = period {
+                        diff -= period * (diff / period).round();
+                    }
+                    before + diff * alpha
+                }
+                None => current,
+            };
+        }
+    }
+
+    /// Wrap positions that left the periodic box back in from the opposite face.
+    ///
+    /// Velocities are untouched so motion stays continuous across the seam.
+    fn wrap_periodic_positions(&mut self) {
+        let half = match self.periodic_half_extents() {
+            Some(half) => half,
+            None => return,
+        };
+        let period = half * 2.0;
+        for i in 0..self.particles.count {
+            let p = self.particles.position[i];
+            self.particles.position[i] = p - period * ((p + half) / period).floor();
+        }
+    }
+
+    /// Create a cloth patch from particles [start_idx .. start_idx + width*height].
+    ///
+    /// Sets particles to Phase::Cloth and creates distance constraints between
+    /// grid neighbors (horizontal, vertical, diagonal) and bending constraints
+    /// between opposite vertices of quads.
+    ///
+    /// `spacing` is the rest distance between adjacent particles.
+    /// `stiffness` is the distance constraint compliance (lower = stiffer).
+    /// `bending_stiffness` is the bending constraint compliance.
+    pub fn create_cloth(
+        &mut self,
+        start_idx: usize,
+        width: usize,
+        height: usize,
+        spacing: f32,
+        stiffness: f32,
+        bending_stiffness: f32,
+    ) {
+        let total = width * height;
+        if start_idx + total > self.particles.count {
+            return;
+        }
+
+        // Position particles in a grid and set phase
+        for row in 0..height {
+            for col in 0..width {
+                let idx = start_idx + row * width + col;
+                self.particles.position[idx] = Vec3::new(
+                    (col as f32 - width as f32 / 2.0) * spacing,
+                    2.0, // start elevated
+                    (row as f32 - height as f32 / 2.0) * spacing,
+                );
+                self.particles.velocity[idx] = Vec3::ZERO;
+                self.particles.phase[idx] = Phase::Cloth;
+            }
+        }
+
+        // Helper to get particle index from grid coordinates
+        let idx = |row: usize, col: usize| -> u32 {
             (start_idx + row * width + col) as u32
         };
 
@@ -683,6 +1824,146 @@ impl Solver {
         }
     }
 
+    /// Fill `particles.pressure` from the SPH density using the equation of state
+    /// `p = stiffness * (density / rest_density - 1)`, clamped at zero, with each
+    /// particle's own rest density (see `particle_rest_density`).
+    ///
+    /// Density is re-estimated from the current positions (refreshing
+    /// `particles.density`). Non-fluid particles get zero pressure.
+    pub fn compute_pressure(&mut self, stiffness: f32) {
+        self.refresh_fluid_density();
+
+        for i in 0..self.particles.count {
+            if !matches!(self.particles.phase[i], Phase::Fluid | Phase::Gas) {
+                self.particles.pressure[i] = 0.0;
+                continue;
+            }
+            let p = stiffness * (self.particles.density[i] / self.particle_rest_density(i) - 1.0);
+            self.particles.pressure[i] = p.max(0.0);
+        }
+    }
+
+    /// Rest density of particle `i`: its own `particles.rest_density` when
+    /// non-zero, otherwise `config.fluid_rest_density` (at least 1).
+    pub fn particle_rest_density(&self, i: usize) -> f32 {
+        let own = self.particles.rest_density[i];
+        let rest = if own > 0.0 { own } else { self.config.fluid_rest_density };
+        rest.max(1.0)
+    }
+
+    /// Largest relative density overshoot `density / rest_density - 1` over
+    /// Fluid and Gas particles, from the densities of the last density solve.
+    ///
+    /// A tuning signal for PBF stiffness: 0.05 means the most compressed particle
+    /// was 5% over rest density. Under-dense fluid (and no fluid) reports 0.
+    pub fn max_density_overshoot(&self) -> f32 {
+        (0..self.particles.count)
+            .filter(|&i| matches!(self.particles.phase[i], Phase::Fluid | Phase::Gas))
+            .map(|i| self.particles.density[i] / self.particle_rest_density(i) - 1.0)
+            .fold(0.0, f32::max)
+    }
+
+    /// Fill `particles.divergence` with the SPH velocity divergence at the
+    /// current positions (an incompressibility diagnostic; ideally near zero).
+    ///
+    /// Density is re-estimated first, as in `compute_pressure`.
+    pub fn compute_velocity_divergence(&mut self) {
+        self.refresh_fluid_density();
+        compute_velocity_divergence(&mut self.particles, &self.grid, self.config.smoothing_radius);
+    }
+
+    /// Fill `particles.heat` with a 0..1 value for motion-aware shading:
+    /// `speed_weight * speed / max_velocity + density_weight * density / rest_density`,
+    /// clamped to [0, 1], with each particle's own rest density (see
+    /// `particle_rest_density`).
+    ///
+    /// Only fluid/gas particles have a density term; it is re-estimated from
+    /// the current positions when `density_weight` is non-zero.
+    pub fn compute_heat(&mut self, speed_weight: f32, density_weight: f32) {
+        if density_weight != 0.0 {
+            self.refresh_fluid_density();
+        }
+
+        let inv_max_speed = 1.0 / self.config.max_velocity.max(1e-6);
+        for i in 0..self.particles.count {
+            let mut heat = speed_weight * self.particles.velocity[i].length() * inv_max_speed;
+            if density_weight != 0.0 && matches!(self.particles.phase[i], Phase::Fluid | Phase::Gas) {
+                heat += density_weight * self.particles.density[i] / self.particle_rest_density(i);
+            }
+            self.particles.heat[i] = heat.clamp(0.0, 1.0);
+        }
+    }
+
+    /// Rebuild the grid from current positions and re-estimate fluid density.
+    ///
+    /// Copies `position` into the `predicted` scratch buffer the density
+    /// gather reads, which the next step overwrites anyway.
+    fn refresh_fluid_density(&mut self) {
+        let count = self.particles.count;
+        self.particles.predicted[..count].copy_from_slice(&self.particles.position[..count]);
+        self.rebuild_grid_from_positions();
+        compute_densities(
+            &mut self.particles,
+            &self.grid,
+            self.config.smoothing_radius,
+            self.config.max_fluid_neighbors,
+            self.config.free_contributes_to_density,
+        );
+    }
+
+    /// Allocate the spatial grid on first use, sized for the current particle count,
+    /// and keep its cell size at `grid_cell_size`.
+    ///
+    /// Solvers that never collide or query neighbors never pay for the table.
+    fn ensure_grid(&mut self) {
+        let cell_size = self.grid_cell_size();
+        if self.grid.table_size() == 0 {
+            let count = self.particles.count;
+            let table = grid_table_size_for(count, self.config.grid_table_size);
+            self.grid = SpatialHashGrid::new(cell_size, table, count);
+        } else if self.grid.cell_size() != cell_size {
+            self.grid.set_cell_size(cell_size);
+        }
+    }
+
+    /// Grid cell edge length: `config.grid_cell_size` when set, otherwise twice
+    /// the largest live particle radius (at least 0.2), so every touching pair
+    /// lies in neighboring cells.
+    pub fn grid_cell_size(&self) -> f32 {
+        if self.config.grid_cell_size > 0.0 {
+            return self.config.grid_cell_size;
+        }
+        let max_radius = self
+            .active
+            .iter()
+            .map(|&i| self.particles.radius[i as usize])
+            .fold(0.0, f32::max);
+        (2.0 * max_radius).max(0.2)
+    }
+
+    /// Buckets in the spatial hash table (0 = not allocated yet).
+    pub fn grid_table_size(&self) -> usize {
+        self.grid.table_size()
+    }
+
+    /// Rebuild the grid from current positions. Leaves `predicted` untouched.
+    fn rebuild_grid_from_positions(&mut self) {
+        self.ensure_grid();
+        self.grid.set_periodic(self.periodic_half_extents());
+        self.grid.build_masked(&self.particles.position, self.particles.count, &self.particles.alive);
+    }
+
+    /// Barnes-Hut octree cells for the current positions, as
+    /// `(bbox_min, bbox_max, total_mass)` per internal node (root first).
+    ///
+    /// Empty when n-body gravity is disabled.
+    pub fn debug_octree_cells(&self) -> Vec<(Vec3, Vec3, f32)> {
+        if !self.config.nbody_enabled {
+            return Vec::new();
+        }
+        octree_cells(&self.particles.position, self.particles.count)
+    }
+
     /// Create a rigid body from particles [start_idx .. start_idx + count].
     ///
     /// Sets particles to Phase::Rigid and creates a ShapeMatchGroup
@@ -695,10 +1976,663 @@ impl Solver {
             self.particles.phase[i] = Phase::Rigid;
         }
         let indices: Vec<u32> = (start_idx..start_idx + count).map(|i| i as u32).collect();
-        let group = ShapeMatchGroup::from_particles(indices, &self.particles.position, stiffness);
+        let group = ShapeMatchGroup::from_particles(
+            indices,
+            &self.particles.position,
+            &self.particles.inv_mass,
+            stiffness,
+        );
         self.shape_match_groups.push(group);
     }
 
+    /// Create a rigid body from an arbitrary (possibly non-contiguous) index list.
+    ///
+    /// Like `create_rigid_body`, the current positions become the rest shape.
+    /// The call is ignored if the list is empty, contains an out-of-range or
+    /// duplicate index, or shares any particle with an existing shape-match group.
+    pub fn create_rigid_body_from_indices(&mut self, indices: &[u32], stiffness: f32) {
+        if indices.is_empty() {
+            return;
+        }
+        let mut seen = vec![false; self.particles.count];
+        for &idx in indices {
+            let i = idx as usize;
+            if i >= self.particles.count || seen[i] {
+                return;
+            }
+            seen[i] = true;
+        }
+        let overlaps = self
+            .shape_match_groups
+            .iter()
+            .flat_map(|g| g.particle_indices.iter())
+            .any(|&idx| seen[idx as usize]);
+        if overlaps {
+            return;
+        }
+
+        for &idx in indices {
+            self.particles.phase[idx as usize] = Phase::Rigid;
+        }
+        let group = ShapeMatchGroup::from_particles(
+            indices.to_vec(),
+            &self.particles.position,
+            &self.particles.inv_mass,
+            stiffness,
+        );
+        self.shape_match_groups.push(group);
+    }
+
+    /// Distance constraints as a flat GPU buffer, `PACKED_DISTANCE_STRIDE` words
+    /// each: `[i, j, rest_length, compliance]`. Floats are stored as their bit
+    /// patterns (`f32::to_bits`, `bitcast<f32>` in WGSL).
+    pub fn pack_distance_constraints(&self) -> Vec<u32> {
+        self.distance_constraints
+            .iter()
+            .flat_map(|c| [c.i, c.j, c.rest_length.to_bits(), c.compliance.to_bits()])
+            .collect()
+    }
+
+    /// Bending constraints as a flat GPU buffer, `PACKED_BENDING_STRIDE` words
+    /// each: `[i, j, k, l, rest_angle, compliance]`, floats stored as bit patterns.
+    pub fn pack_bending_constraints(&self) -> Vec<u32> {
+        self.bending_constraints
+            .iter()
+            .flat_map(|c| [c.i, c.j, c.k, c.l, c.rest_angle.to_bits(), c.compliance.to_bits()])
+            .collect()
+    }
+
+    /// Shape-match group index of every particle, or -1 for particles in no group.
+    pub fn shape_group_membership(&self) -> Vec<i32> {
+        let mut membership = vec![-1; self.particles.count];
+        for (g, group) in self.shape_match_groups.iter().enumerate() {
+            for &idx in &group.particle_indices {
+                membership[idx as usize] = g as i32;
+            }
+        }
+        membership
+    }
+
+    /// Snap rigid body `group_idx` back to its undeformed rest shape.
+    ///
+    /// Members are placed at `com + rotation * rest_position`, where `com` is the
+    /// current mass-weighted center of mass and `rotation` the group's last
+    /// solved rotation (identity before the first solve). Velocities are zeroed;
+    /// static members stay put. Out-of-range indices are ignored.
+    pub fn reset_rigid_body(&mut self, group_idx: usize) {
+        let Some(com) = self.rigid_center_of_mass(group_idx) else {
+            return;
+        };
+        let group = &self.shape_match_groups[group_idx];
+
+        for (&idx, &rest) in group.particle_indices.iter().zip(&group.rest_positions) {
+            let i = idx as usize;
+            if self.particles.inv_mass[i] == 0.0 {
+                continue;
+            }
+            let pos = com + group.rotation * rest;
+            self.particles.position[i] = pos;
+            self.particles.predicted[i] = pos;
+            self.particles.velocity[i] = Vec3::ZERO;
+        }
+    }
+
+    /// Spin rigid body `group_idx` about `axis` through its center of mass.
+    ///
+    /// Adds the rigid-rotation velocity `omega x (x_i - com)` to each dynamic
+    /// member, where `omega = axis.normalize() * magnitude` (radians per second),
+    /// so members far from the center speed up most. Shape matching keeps the
+    /// body rigid as it turns. Out-of-range indices and a zero axis are ignored.
+    pub fn apply_rigid_torque(&mut self, group_idx: usize, axis: Vec3, magnitude: f32) {
+        let Some(com) = self.rigid_center_of_mass(group_idx) else {
+            return;
+        };
+        let omega = axis.normalize_or_zero() * magnitude;
+        for &idx in &self.shape_match_groups[group_idx].particle_indices {
+            let i = idx as usize;
+            if self.particles.inv_mass[i] == 0.0 {
+                continue;
+            }
+            self.particles.velocity[i] += omega.cross(self.particles.position[i] - com);
+        }
+    }
+
+    /// Orientation of rigid body `group_idx` as a unit quaternion `[x, y, z, w]`,
+    /// taken from the rotation of its last shape-matching solve (`w >= 0`).
+    ///
+    /// Identity before the first solve and for out-of-range indices.
+    pub fn rigid_body_rotation(&self, group_idx: usize) -> [f32; 4] {
+        let Some(group) = self.shape_match_groups.get(group_idx) else {
+            return Quat::IDENTITY.to_array();
+        };
+        let q = Quat::from_mat3(&group.rotation).normalize();
+        if q.w < 0.0 { (-q).to_array() } else { q.to_array() }
+    }
+
+    /// Center of mass and orientation (see `rigid_body_rotation`) of rigid body
+    /// `group_idx`, for attaching a rendered mesh. `None` if the index is out of
+    /// range or every member is static.
+    pub fn rigid_body_transform(&self, group_idx: usize) -> Option<(Vec3, [f32; 4])> {
+        let com = self.rigid_center_of_mass(group_idx)?;
+        Some((com, self.rigid_body_rotation(group_idx)))
+    }
+
+    /// Convex hull of rigid body `group_idx` at its members' current positions,
+    /// as `(vertices, triangles)` with outward-facing counter-clockwise triangles
+    /// (see `hull::convex_hull`). Empty for out-of-range indices and flat bodies.
+    pub fn rigid_body_hull(&self, group_idx: usize) -> (Vec<Vec3>, Vec<[u32; 3]>) {
+        let Some(group) = self.shape_match_groups.get(group_idx) else {
+            return (Vec::new(), Vec::new());
+        };
+        let points: Vec<Vec3> =
+            group.particle_indices.iter().map(|&i| self.particles.position[i as usize]).collect();
+        convex_hull(&points)
+    }
+
+    /// Mass-weighted center of mass of the dynamic members of rigid body
+    /// `group_idx`, or `None` if the index is out of range or all are static.
+    fn rigid_center_of_mass(&self, group_idx: usize) -> Option<Vec3> {
+        let group = self.shape_match_groups.get(group_idx)?;
+        let mut com = Vec3::ZERO;
+        let mut total_mass = 0.0_f32;
+        for &idx in &group.particle_indices {
+            let i = idx as usize;
+            if self.particles.inv_mass[i] == 0.0 {
+                continue;
+            }
+            let mass = 1.0 / self.particles.inv_mass[i];
+            com += self.particles.position[i] * mass;
+            total_mass += mass;
+        }
+        (total_mass >= 1e-10).then(|| com / total_mass)
+    }
+
+    /// Apply melt/freeze phase transitions from per-particle temperature.
+    ///
+    /// Rigid and Static particles above `config.melt_temperature` become Fluid
+    /// and leave their shape-match group (emptied groups are dropped); a
+    /// particle pinned by freezing gets the mass stashed by `freeze_particles`
+    /// back. Fluid particles below `config.freeze_temperature` become Static
+    /// (pinned with `freeze_particles`) when `config.freeze_to_static` is set,
+    /// otherwise each connected region of them
+    /// (particles within `config.smoothing_radius` of one another) becomes its
+    /// own rigid body shaped by their current positions.
+    pub fn update_phase_transitions(&mut self) {
+        let melt = self.config.melt_temperature;
+        let freeze = self.config.freeze_temperature;
+        let n = self.particles.count;
+
+        // ==== MELT ====
+        let mut melted = vec![false; n];
+        let mut any_melted = false;
+        for (i, m) in melted.iter_mut().enumerate() {
+            let solid = matches!(self.particles.phase[i], Phase::Rigid | Phase::Static);
+            if solid && self.particles.temperature[i] > melt {
+                self.particles.phase[i] = Phase::Fluid;
+                if self.particles.inv_mass[i] == 0.0 {
+                    let saved = self.frozen_inv_mass.remove(&(i as u32)).filter(|&m| m > 0.0);
+                    self.particles.inv_mass[i] = if let Some(saved) = saved {
+                        saved
+                    } else if self.config.mass_from_radius {
+                        radius_inv_mass(self.particles.radius[i], self.config.mass_radius_density)
+                    } else {
+                        1.0
+                    };
+                }
+                *m = true;
+                any_melted = true;
+            }
+        }
+        if any_melted {
+            for group in &mut self.shape_match_groups {
+                group.remove_particles(&melted);
+            }
+            self.shape_match_groups.retain(|g| !g.particle_indices.is_empty());
+        }
+
+        // ==== FREEZE ====
+        let frozen: Vec<u32> = (0..n)
+            .filter(|&i| {
+                self.particles.phase[i] == Phase::Fluid && self.particles.temperature[i] < freeze
+            })
+            .map(|i| i as u32)
+            .collect();
+        if frozen.is_empty() {
+            return;
+        }
+        if self.config.freeze_to_static {
+            for &idx in &frozen {
+                let i = idx as usize;
+                self.particles.phase[i] = Phase::Static;
+            }
+            self.freeze_particles(&frozen);
+        } else {
+            for body in self.connected_regions(&frozen, self.config.smoothing_radius) {
+                self.create_rigid_body_from_indices(&body, self.config.shape_matching_stiffness);
+            }
+        }
+    }
+
+    /// Split `indices` into groups of particles linked by chains of pairs less
+    /// than `link` apart. Groups keep ascending index order and are ordered by
+    /// their lowest index.
+    fn connected_regions(&mut self, indices: &[u32], link: f32) -> Vec<Vec<u32>> {
+        fn root(parent: &mut [usize], mut k: usize) -> usize {
+            while parent[k] != k {
+                parent[k] = parent[parent[k]];
+                k = parent[k];
+            }
+            k
+        }
+
+        self.rebuild_grid_from_positions();
+        let mut slot = vec![usize::MAX; self.particles.count];
+        for (k, &idx) in indices.iter().enumerate() {
+            slot[idx as usize] = k;
+        }
+        let mut parent: Vec<usize> = (0..indices.len()).collect();
+        let link_sq = link * link;
+        for (k, &idx) in indices.iter().enumerate() {
+            let pos = self.particles.position[idx as usize];
+            self.grid.query_radius(pos, link, |j| {
+                let m = slot[j as usize];
+                if m == usize::MAX
+                    || self.grid.min_image_diff(self.particles.position[j as usize], pos).length_squared()
+                        >= link_sq
+                {
+                    return;
+                }
+                let (a, b) = (root(&mut parent, k), root(&mut parent, m));
+                parent[a.max(b)] = a.min(b);
+            });
+        }
+
+        let mut regions: Vec<Vec<u32>> = Vec::new();
+        let mut region_of = vec![usize::MAX; indices.len()];
+        for (k, &idx) in indices.iter().enumerate() {
+            let r = root(&mut parent, k);
+            if region_of[r] == usize::MAX {
+                region_of[r] = regions.len();
+                regions.push(Vec::new());
+            }
+            regions[region_of[r]].push(idx);
+        }
+        regions
+    }
+
+    /// Brush `phase` onto every particle within `radius` of `center`.
+    ///
+    /// Painting Static pins particles (`inv_mass = 0`); painting any other phase
+    /// onto a Static particle gives it unit mass again. Killed particles are
+    /// never painted. With `group_rigid` and `phase == Rigid`, the painted
+    /// particles leave any shape-match group they belonged to and become one
+    /// new group. Returns the number of painted particles.
+    pub fn paint_phase(&mut self, center: Vec3, radius: f32, phase: Phase, group_rigid: bool) -> usize {
+        self.rebuild_grid_from_positions();
+        let mut painted = Vec::new();
+        let radius_sq = radius * radius;
+        self.grid.query_radius(center, radius, |j| {
+            let offset = self.grid.min_image_diff(self.particles.position[j as usize], center);
+            if self.particles.alive[j as usize] && offset.length_squared() <= radius_sq {
+                painted.push(j);
+            }
+        });
+        painted.sort_unstable();
+        painted.dedup();
+
+        for &idx in &painted {
+            let i = idx as usize;
+            self.particles.phase[i] = phase;
+            if phase == Phase::Static {
+                self.particles.inv_mass[i] = 0.0;
+            } else if self.particles.inv_mass[i] == 0.0 {
+                self.particles.inv_mass[i] = 1.0;
+            }
+        }
+        if group_rigid && phase == Phase::Rigid {
+            let mut regrouped = vec![false; self.particles.count];
+            for &idx in &painted {
+                regrouped[idx as usize] = true;
+            }
+            for group in &mut self.shape_match_groups {
+                group.remove_particles(&regrouped);
+            }
+            self.shape_match_groups.retain(|g| !g.particle_indices.is_empty());
+            self.create_rigid_body_from_indices(&painted, self.config.shape_matching_stiffness);
+        }
+        painted.len()
+    }
+
+    /// Indices of live particles in ascending order; killed slots are skipped.
+    ///
+    /// Force accumulation and GPU output iterate this list rather than every slot.
+    pub fn active_indices(&self) -> &[u32] {
+        &self.active
+    }
+
+    /// Kill particles: they keep their slot but stop moving and colliding.
+    ///
+    /// A killed particle becomes static (zero inverse mass and velocity), is
+    /// dropped from `active_indices`, left out of the spatial grid (so it adds
+    /// no contacts or fluid density), and removed from distance and bending
+    /// constraints and shape-match groups. Already dead and out-of-range
+    /// indices are ignored.
+    pub fn kill_particles(&mut self, indices: &[u32]) {
+        let mut killed = vec![false; self.particles.count];
+        let mut any = false;
+        for &idx in indices {
+            let i = idx as usize;
+            if i >= self.particles.count || !self.particles.alive[i] {
+                continue;
+            }
+            self.particles.alive[i] = false;
+            self.particles.inv_mass[i] = 0.0;
+            self.particles.velocity[i] = Vec3::ZERO;
+            self.frozen_inv_mass.remove(&idx);
+            killed[i] = true;
+            any = true;
+        }
+        if any {
+            self.detach_particles(&killed);
+            self.refresh_active_indices();
+        }
+    }
+
+    /// Remove the particles flagged in `removed` (indexed by slot) from distance
+    /// and bending constraints and shape-match groups; emptied groups are dropped.
+    fn detach_particles(&mut self, removed: &[bool]) {
+        for group in &mut self.shape_match_groups {
+            group.remove_particles(removed);
+        }
+        self.shape_match_groups.retain(|g| !g.particle_indices.is_empty());
+        let gone = |i: u32| removed.get(i as usize).copied().unwrap_or(false);
+        self.distance_constraints.retain(|c| !gone(c.i) && !gone(c.j));
+        self.bending_constraints.retain(|c| ![c.i, c.j, c.k, c.l].into_iter().any(gone));
+    }
+
+    /// Spawn a Free particle at `pos` with `velocity` and return its index.
+    ///
+    /// The lowest killed slot is reused if there is one: it is detached from
+    /// any constraint or shape-match group and every per-particle value is
+    /// reset to the `ParticleSet` defaults (see `ParticleSet::reset_particle`;
+    /// the slot keeps its radius and hash). Otherwise the set grows by one.
+    /// Mass follows the radius when `mass_from_radius` is on. With
+    /// `config.spawn_flow_inheritance > 0`, `sample_flow(pos, time)` times that
+    /// factor is added to the velocity. The particle starts at age 0 with
+    /// lifetime `config.spawn_lifetime`.
+    pub fn spawn_particle(&mut self, pos: Vec3, velocity: Vec3, time: f32) -> usize {
+        let i = match self.particles.alive.iter().position(|&alive| !alive) {
+            Some(dead) => {
+                let mut slot = vec![false; self.particles.count];
+                slot[dead] = true;
+                self.detach_particles(&slot);
+                self.particles.reset_particle(dead);
+                dead
+            }
+            None => {
+                let count = self.particles.count;
+                self.resize(count + 1);
+                count
+            }
+        };
+
+        let inherit = self.config.spawn_flow_inheritance;
+        let flow = if inherit != 0.0 { self.sample_flow(pos, time) * inherit } else { Vec3::ZERO };
+        self.set_particle_radius(i, self.particles.radius[i]);
+        self.particles.position[i] = pos;
+        self.particles.predicted[i] = pos;
+        self.particles.velocity[i] = velocity + flow;
+        self.particles.lifetime[i] = self.config.spawn_lifetime.max(0.0);
+        self.refresh_active_indices();
+        i
+    }
+
+    /// Age live particles by `dt` and kill those older than their lifetime.
+    fn age_particles(&mut self, dt: f32) {
+        let mut expired = Vec::new();
+        for &idx in &self.active {
+            let i = idx as usize;
+            self.particles.age[i] += dt;
+            let lifetime = self.particles.lifetime[i];
+            if lifetime > 0.0 && self.particles.age[i] > lifetime {
+                expired.push(idx);
+            }
+        }
+        if !expired.is_empty() {
+            self.kill_particles(&expired);
+        }
+    }
+
+    /// Rebuild the compact live-particle list from `particles.alive`.
+    fn refresh_active_indices(&mut self) {
+        self.active.clear();
+        self.active.extend(
+            (0..self.particles.count as u32).filter(|&i| self.particles.alive[i as usize]),
+        );
+    }
+
+    /// Reorder particle slots so particles sharing a grid cell are contiguous,
+    /// improving memory locality of neighbor loops in dense scenes.
+    ///
+    /// Every per-particle array moves together (`user_id` identifies a logical
+    /// particle afterwards, and `shape_index` keeps each particle on its shape
+    /// and custom target), and constraint, shape-group and frozen-particle
+    /// indices are remapped. `lerp_output` ignores snapshots taken before the
+    /// pass, since their slots no longer line up.
+    pub fn reorder_by_cell(&mut self) {
+        self.rebuild_grid_from_positions();
+        // Dead particles are not in the grid; they go to the end
+        let mut order: Vec<usize> = self.grid.sorted_indices().iter().map(|&i| i as usize).collect();
+        order.extend((0..self.particles.count).filter(|&i| !self.particles.alive[i]));
+        if order.len() != self.particles.count {
+            return;
+        }
+        let mut new_index = vec![0u32; order.len()];
+        for (k, &old) in order.iter().enumerate() {
+            new_index[old] = k as u32;
+        }
+        let remap = |i: &mut u32| *i = new_index[*i as usize];
+
+        self.particles.permute(&order);
+        for c in &mut self.distance_constraints {
+            remap(&mut c.i);
+            remap(&mut c.j);
+        }
+        for c in &mut self.bending_constraints {
+            remap(&mut c.i);
+            remap(&mut c.j);
+            remap(&mut c.k);
+            remap(&mut c.l);
+        }
+        for group in &mut self.shape_match_groups {
+            group.particle_indices.iter_mut().for_each(remap);
+        }
+        self.frozen_inv_mass =
+            self.frozen_inv_mass.drain().map(|(i, m)| (new_index[i as usize], m)).collect();
+        self.contacts.clear();
+        self.pointer_release = None;
+        self.slot_layout = self.slot_layout.wrapping_add(1);
+        self.refresh_active_indices();
+        // Grid entries refer to the old slots
+        self.rebuild_grid_from_positions();
+    }
+
+    /// Stir the particles within `radius` of `center`: add the rigid-rotation
+    /// velocity `angular_velocity x (x_i - center)` to each dynamic particle
+    /// in range, so they swirl about the center. Returns how many were stirred.
+    ///
+    /// Unlike the pointer vortex this is a one-off velocity injection with no
+    /// pointer state; call it every step to keep stirring a moving zone.
+    pub fn stir(&mut self, center: Vec3, radius: f32, angular_velocity: Vec3) -> usize {
+        self.rebuild_grid_from_positions();
+        let mut inside = Vec::new();
+        let radius_sq = radius * radius;
+        self.grid.query_radius(center, radius, |j| {
+            let offset = self.grid.min_image_diff(self.particles.position[j as usize], center);
+            if offset.length_squared() <= radius_sq {
+                inside.push(j as usize);
+            }
+        });
+        inside.sort_unstable();
+        inside.dedup();
+        inside.retain(|&i| self.particles.inv_mass[i] != 0.0);
+
+        for &i in &inside {
+            let offset = self.grid.min_image_diff(self.particles.position[i], center);
+            self.particles.velocity[i] += angular_velocity.cross(offset);
+        }
+        inside.len()
+    }
+
+    /// Temporarily pin particles in place (e.g. while grabbed).
+    ///
+    /// Each particle's inverse mass is stashed and set to 0; velocity is
+    /// zeroed. Already frozen and out-of-range indices are ignored.
+    pub fn freeze_particles(&mut self, indices: &[u32]) {
+        for &idx in indices {
+            let i = idx as usize;
+            if i >= self.particles.count || self.frozen_inv_mass.contains_key(&idx) {
+                continue;
+            }
+            self.frozen_inv_mass.insert(idx, self.particles.inv_mass[i]);
+            self.particles.inv_mass[i] = 0.0;
+            self.particles.velocity[i] = Vec3::ZERO;
+        }
+    }
+
+    /// Release particles pinned by `freeze_particles`, restoring their exact
+    /// original inverse mass. Indices that are not frozen are ignored.
+    pub fn unfreeze_particles(&mut self, indices: &[u32]) {
+        for &idx in indices {
+            if let Some(inv_mass) = self.frozen_inv_mass.remove(&idx) {
+                self.particles.inv_mass[idx as usize] = inv_mass;
+            }
+        }
+    }
+
+    /// Set substeps and solver iterations (also the adaptive quality maxima),
+    /// clamped to `1..=MAX_SUBSTEPS` and `1..=MAX_SOLVER_ITERATIONS`.
+    ///
+    /// Returns `true` if either value was out of range and got clamped.
+    pub fn set_solver_quality(&mut self, substeps: u32, iterations: u32) -> bool {
+        let clamped_substeps = substeps.clamp(1, MAX_SUBSTEPS);
+        let clamped_iterations = iterations.clamp(1, MAX_SOLVER_ITERATIONS);
+        self.config.substeps = clamped_substeps;
+        self.config.solver_iterations = clamped_iterations;
+        self.adaptive_quality.max_substeps = clamped_substeps;
+        self.adaptive_quality.max_iterations = clamped_iterations;
+        clamped_substeps != substeps || clamped_iterations != iterations
+    }
+
+    /// Change the spatial hash table size at runtime (see `config.grid_table_size`).
+    ///
+    /// An allocated grid gets exactly `size` buckets and is rehashed on its next
+    /// build, i.e. at the start of the next step; `size` also becomes the cap for
+    /// automatic sizing when the grid is first allocated or the set is resized.
+    pub fn set_grid_table_size(&mut self, size: usize) {
+        self.config.grid_table_size = size.max(1);
+        if self.grid.table_size() > 0 {
+            self.grid.set_table_size(self.config.grid_table_size);
+        }
+    }
+
+    /// Grow or shrink the simulation to `new_count` particles.
+    ///
+    /// Surviving particles keep their state. New slots start at the origin
+    /// with `ParticleSet` defaults plus the per-index radius and hash seeding
+    /// used by `Solver::new`. Constraints and shape-match group members that
+    /// reference removed particles are dropped.
+    pub fn resize(&mut self, new_count: usize) {
+        let old_count = self.particles.count;
+        if new_count < old_count {
+            let removed: Vec<bool> = (0..old_count).map(|i| i >= new_count).collect();
+            self.detach_particles(&removed);
+        }
+
+        self.frozen_inv_mass.retain(|&idx, _| (idx as usize) < new_count);
+        self.particles.resize(new_count);
+        if self.grid.table_size() > 0 {
+            self.grid.resize(new_count);
+            self.grid.set_table_size(grid_table_size_for(new_count, self.config.grid_table_size));
+        }
+        self.contacts.clear();
+        self.refresh_active_indices();
+
+        let tex_size = (new_count as f32).sqrt().ceil() as usize;
+        for i in old_count..new_count {
+            let ux = (i % tex_size) as f32 / tex_size as f32;
+            let uy = (i / tex_size) as f32 / tex_size as f32;
+            self.particles.hash[i] = hash12(ux, uy);
+            self.particles.radius[i] = hashed_radius(&self.config, self.particles.hash[i]);
+        }
+    }
+
+    /// Merge `other`'s particles and constraints into this world (scene composition).
+    ///
+    /// Each of `other`'s particles reuses the lowest killed slot, like
+    /// `spawn_particle`, or extends the set once none is left; slots keep their
+    /// own `user_id`. Reused slots are first detached from this world's
+    /// constraints and shape-match groups. Distance, bending and shape-match constraints and frozen
+    /// particles are copied with their indices remapped to the new slots. This
+    /// world's config, shape and pointer state are kept.
+    pub fn append(&mut self, other: &Solver) -> Result<(), AppendError> {
+        if self.config.boundary_shape != other.config.boundary_shape {
+            return Err(AppendError::BoundaryMismatch);
+        }
+        let old_count = self.particles.count;
+        let free: Vec<usize> = (0..old_count).filter(|&i| !self.particles.alive[i]).collect();
+        let extra = other.particles.count.saturating_sub(free.len());
+        let new_count = old_count
+            .checked_add(extra)
+            .filter(|&n| n <= u32::MAX as usize)
+            .ok_or(AppendError::TooManyParticles)?;
+
+        if extra > 0 {
+            self.resize(new_count);
+        }
+        let slots: Vec<u32> = free
+            .into_iter()
+            .chain(old_count..new_count)
+            .take(other.particles.count)
+            .map(|i| i as u32)
+            .collect();
+        let mut reused = vec![false; new_count];
+        for &dst in slots.iter().filter(|&&dst| (dst as usize) < old_count) {
+            reused[dst as usize] = true;
+        }
+        self.detach_particles(&reused);
+        for (src, &dst) in slots.iter().enumerate() {
+            self.particles.copy_particle_from(dst as usize, &other.particles, src);
+        }
+
+        let remap = |i: &mut u32| *i = slots[*i as usize];
+        for c in &other.distance_constraints {
+            let mut c = c.clone();
+            remap(&mut c.i);
+            remap(&mut c.j);
+            self.distance_constraints.push(c);
+        }
+        for c in &other.bending_constraints {
+            let mut c = c.clone();
+            remap(&mut c.i);
+            remap(&mut c.j);
+            remap(&mut c.k);
+            remap(&mut c.l);
+            self.bending_constraints.push(c);
+        }
+        for group in &other.shape_match_groups {
+            let mut group = group.clone();
+            group.particle_indices.iter_mut().for_each(remap);
+            self.shape_match_groups.push(group);
+        }
+        for (&idx, &inv_mass) in &other.frozen_inv_mass {
+            self.frozen_inv_mass.insert(slots[idx as usize], inv_mass);
+        }
+        self.contacts.clear();
+        self.refresh_active_indices();
+        Ok(())
+    }
+
     /// Clear all constraints and reset particles to Phase::Free.
     pub fn clear_constraints(&mut self) {
         self.distance_constraints.clear();
@@ -709,22 +2643,178 @@ impl Solver {
         }
     }
 
+    /// Lay particles out as a spiral galaxy disk for n-body demos.
+    ///
+    /// A tenth of the particles form a dense central bulge; the rest follow
+    /// `arms` logarithmic spiral arms out to `radius` in the XY plane, with
+    /// `thickness` of spread along Z. The central attractor is enabled at the
+    /// origin as the galaxy's central mass, sized so a circular orbit at
+    /// `radius` has speed `rotation_velocity`. Each particle gets the
+    /// counter-clockwise circular-orbit velocity around that mass, so inner
+    /// orbits are faster. The same `seed` reproduces the same layout.
+    pub fn init_galaxy(
+        &mut self,
+        arms: u32,
+        radius: f32,
+        thickness: f32,
+        rotation_velocity: f32,
+        seed: u32,
+    ) {
+        const PITCH: f32 = 0.3; // tan of the spiral pitch angle
+        let count = self.particles.count;
+        let arms = arms.max(1);
+        let bulge_count = (count / 10).max(1).min(count);
+        let bulge_radius = radius * 0.15;
+
+        // Central mass: GM / (r^2 + soft^2) * r = v^2 holds at `radius`
+        let soft_sq = self.config.nbody_softening * self.config.nbody_softening;
+        let gm = rotation_velocity * rotation_velocity * (radius * radius + soft_sq) / radius.max(1e-6);
+        self.config.central_attractor_enabled = true;
+        self.config.central_attractor_pos = Vec3::ZERO;
+        self.config.central_attractor_mass = gm / self.config.nbody_g.max(1e-12);
+
+        let mut rng = Pcg32::new(seed as u64);
+        for i in 0..count {
+            let rand = [rng.next_f32(), rng.next_f32(), rng.next_f32()];
+            let (r, angle) = if i < bulge_count {
+                (bulge_radius * rand[0].sqrt(), rand[1] * std::f32::consts::TAU)
+            } else {
+                let arm = (i % arms as usize) as f32;
+                let r = bulge_radius + (radius - bulge_radius) * rand[0].sqrt();
+                let winding = (r / bulge_radius).ln() / PITCH;
+                let jitter = (rand[1] - 0.5) * 0.5;
+                (r, arm * std::f32::consts::TAU / arms as f32 + winding + jitter)
+            };
+            let z = (rand[2] - 0.5) * thickness;
+            let (sin, cos) = angle.sin_cos();
+
+            let speed = (gm * r / (r * r + soft_sq)).sqrt();
+            self.particles.position[i] = Vec3::new(cos * r, sin * r, z);
+            self.particles.velocity[i] = Vec3::new(-sin, cos, 0.0) * speed;
+            self.particles.inv_mass[i] = 1.0;
+        }
+    }
+
+    /// Give every particle a random charge uniform in `[-magnitude, magnitude]`
+    /// for electromagnetic demos. The same `seed` reproduces the same charges.
+    pub fn randomize_charges(&mut self, magnitude: f32, seed: u64) {
+        let mut rng = Pcg32::new(seed);
+        for charge in &mut self.particles.charge {
+            *charge = (rng.next_f32() * 2.0 - 1.0) * magnitude;
+        }
+    }
+
+    /// Give every particle a random radius uniform in `[min, max]`, through
+    /// `set_particle_radius` so masses follow with `mass_from_radius`.
+    /// The same `seed` reproduces the same radii.
+    pub fn randomize_radii(&mut self, seed: u64, min: f32, max: f32) {
+        let (min, max) = (min.min(max), min.max(max));
+        let mut rng = Pcg32::new(seed);
+        for i in 0..self.particles.count {
+            self.set_particle_radius(i, min + rng.next_f32() * (max - min));
+        }
+    }
+
     /// Re-initialize particle positions in a spiral ring pattern.
-    pub fn reinitialize(&mut self, _seed: u32) {
+    ///
+    /// Uses `config.init_spiral_turns` and `config.init_spiral_radius`, with the
+    /// ring turned about the Y axis by a random angle drawn from `seed` (the
+    /// same seed reproduces the same layout). Radii are reassigned from
+    /// `config.radius_min`/`radius_max`.
+    pub fn reinitialize(&mut self, seed: u32) {
+        let turn = Quat::from_rotation_y(Pcg32::new(seed as u64).next_f32() * std::f32::consts::TAU);
         for i in 0..self.particles.count {
             let t = i as f32 / self.particles.count as f32;
-            let angle = t * std::f32::consts::TAU * 20.0;
-            let r = 0.5 + t * 1.5;
-            self.particles.position[i] =
-                Vec3::new(angle.cos() * r, (t - 0.5) * 2.0, angle.sin() * r);
+            self.particles.position[i] = turn
+                * spiral_position(t, self.config.init_spiral_turns, self.config.init_spiral_radius);
             self.particles.velocity[i] = Vec3::ZERO;
             self.particles.inv_mass[i] = 1.0;
+            self.particles.radius[i] = hashed_radius(&self.config, self.particles.hash[i]);
         }
     }
 }
 
 // ---------- helper functions ----------
 
+/// Smallest automatically sized hash table. Below this, distinct neighbor cells
+/// share buckets often enough to double-count neighbors in kernel sums.
+const MIN_GRID_TABLE_SIZE: usize = 16384;
+
+/// Radius for a particle with identity hash `hash` in `[0, 1)`, spread over
+/// `config.radius_min..config.radius_max`.
+fn hashed_radius(config: &PhysicsConfig, hash: f32) -> f32 {
+    config.radius_min + hash * (config.radius_max - config.radius_min)
+}
+
+/// Hash table size for `count` particles: the next power of two at or above
+/// `2 * count` (at least `MIN_GRID_TABLE_SIZE`), capped at `max`
+/// (`config.grid_table_size`).
+fn grid_table_size_for(count: usize, max: usize) -> usize {
+    (count * 2).next_power_of_two().max(MIN_GRID_TABLE_SIZE).min(max.max(1))
+}
+
+/// Combined Coulomb friction of a contact pair (see `Solver::contact_friction`).
+fn pair_friction(config: &PhysicsConfig, material: &[f32], i: usize, j: usize) -> f32 {
+    config.friction * config.material_combine.combine(material[i], material[j])
+}
+
+/// Combined restitution of a contact pair (see `Solver::contact_restitution`).
+fn pair_restitution(
+    config: &PhysicsConfig,
+    phase: &[Phase],
+    material: &[f32],
+    i: usize,
+    j: usize,
+) -> f32 {
+    let a = config.phase_restitution[phase[i] as usize];
+    let b = config.phase_restitution[phase[j] as usize];
+    config.restitution * a.min(b) * config.material_combine.combine(material[i], material[j])
+}
+
+/// Point `t` in [0, 1) along the initial spiral ring: `turns` full turns with
+/// radius growing linearly from `radius[0]` to `radius[1]`, rising from y = -1 to 1.
+#[inline]
+fn spiral_position(t: f32, turns: f32, radius: [f32; 2]) -> Vec3 {
+    let angle = t * std::f32::consts::TAU * turns;
+    let r = radius[0] + t * (radius[1] - radius[0]);
+    Vec3::new(angle.cos() * r, (t - 0.5) * 2.0, angle.sin() * r)
+}
+
+/// Flow acceleration scaled by shape structure (less flow as shapes firm up).
+#[inline]
+fn scaled_flow_force(
+    pos: Vec3,
+    id_hash: f32,
+    time: f32,
+    structure: f32,
+    calm_factor: f32,
+    vortex_strength: f32,
+    z_wave: [f32; 2],
+) -> Vec3 {
+    let flow_raw = compute_flow_force(pos, id_hash, time, calm_factor, vortex_strength, z_wave);
+    let flow_scale = mix_f32(0.35, 0.55, 1.0 - structure);
+    flow_raw * flow_scale
+}
+
+/// Inverse mass of a sphere of `radius` at `density`.
+#[inline]
+fn radius_inv_mass(radius: f32, density: f32) -> f32 {
+    let mass = density * 4.0 / 3.0 * std::f32::consts::PI * radius.powi(3);
+    1.0 / mass.max(1e-6)
+}
+
+/// Advance `rot` by the world-space angular velocity `omega` over `dt`.
+///
+/// Goes through a normalized quaternion so repeated spinning does not drift
+/// away from a pure rotation. A zero `omega` leaves `rot` untouched.
+fn spin_rotation(rot: glam::Mat3, omega: Vec3, dt: f32) -> glam::Mat3 {
+    if omega == Vec3::ZERO {
+        return rot;
+    }
+    let spun = Quat::from_scaled_axis(omega * dt) * Quat::from_mat3(&rot);
+    glam::Mat3::from_quat(spun.normalize())
+}
+
 /// GLSL-style `mix(a, b, t)` for scalars.
 #[inline]
 fn mix_f32(a: f32, b: f32, t: f32) -> f32 {
@@ -1,9 +1,10 @@
 use glam::Vec3;
 use xpbd_core::constraints::bending::{
     reset_lambdas as reset_bending_lambdas, solve_bending_constraints, BendingConstraint,
+    PACKED_BENDING_STRIDE,
 };
 use xpbd_core::constraints::distance::{
-    reset_lambdas, solve_distance_constraints, DistanceConstraint,
+    reset_lambdas, solve_distance_constraints, DistanceConstraint, PACKED_DISTANCE_STRIDE,
 };
 use xpbd_core::particle::{ParticleSet, Phase};
 use xpbd_core::solver::Solver;
@@ -325,3 +326,49 @@ fn test_bending_degenerate_edge() {
             "Degenerate edge should produce no corrections at {}", i);
     }
 }
+
+#[test]
+fn test_packed_constraints_decode_to_fields() {
+    let mut solver = Solver::new(30);
+    solver.create_cloth(0, 5, 5, 0.1, 0.001, 0.01);
+
+    let distance = solver.pack_distance_constraints();
+    assert_eq!(distance.len(), solver.distance_constraints.len() * PACKED_DISTANCE_STRIDE);
+    let first = &solver.distance_constraints[0];
+    assert_eq!(distance[0], first.i);
+    assert_eq!(distance[1], first.j);
+    assert_eq!(f32::from_bits(distance[2]), first.rest_length);
+    assert_eq!(f32::from_bits(distance[3]), first.compliance);
+
+    let bending = solver.pack_bending_constraints();
+    assert_eq!(bending.len(), solver.bending_constraints.len() * PACKED_BENDING_STRIDE);
+    let first = &solver.bending_constraints[0];
+    assert_eq!(bending[..4], [first.i, first.j, first.k, first.l]);
+    assert_eq!(f32::from_bits(bending[4]), first.rest_angle);
+    assert_eq!(f32::from_bits(bending[5]), first.compliance);
+}
+
+#[test]
+fn test_killed_cloth_vertex_detaches_from_cloth() {
+    let mut solver = Solver::new(9);
+    solver.config.collisions_enabled = true;
+    solver.config.artistic_forces_enabled = false;
+    solver.create_cloth(0, 3, 3, 0.1, 0.0, 0.01);
+    assert!(solver.distance_constraints.iter().any(|c| c.i == 4 || c.j == 4));
+
+    solver.kill_particles(&[4]);
+    assert!(solver.distance_constraints.iter().all(|c| c.i != 4 && c.j != 4));
+    assert!(solver.bending_constraints.iter().all(|c| ![c.i, c.j, c.k, c.l].contains(&4)));
+    assert!(!solver.distance_constraints.is_empty(), "the rest of the cloth stays connected");
+
+    // The dead vertex no longer holds the cloth up: the ring falls away from it
+    let dead = solver.particles.position[4];
+    for step in 0..30 {
+        solver.step(1.0 / 60.0, step as f32 / 60.0);
+    }
+    assert_eq!(solver.particles.position[4], dead);
+    for i in [0, 1, 2, 3, 5, 6, 7, 8] {
+        let drop = dead.y - solver.particles.position[i].y;
+        assert!(drop > 0.1, "vertex {i} should fall freely, dropped {drop}");
+    }
+}
@@ -1,15 +1,22 @@
 use glam::Vec3;
+use xpbd_core::config::{BoundaryShape, MaterialCombine};
 use xpbd_core::constraints::bending::{
     reset_lambdas as reset_bending_lambdas, solve_bending_constraints, BendingConstraint,
 };
-use xpbd_core::constraints::contact::{detect_contacts, solve_contacts, ContactConstraint};
+use xpbd_core::constraints::capsule_contact::{
+    capsule_segments, detect_capsule_contacts, solve_capsule_contacts,
+};
+use xpbd_core::constraints::contact::{
+    detect_contacts, detect_speculative_contacts, solve_contacts, ContactConstraint,
+    ContactParams, GranularFriction,
+};
 use xpbd_core::constraints::distance::{
     reset_lambdas, solve_distance_constraints, DistanceConstraint,
 };
 use xpbd_core::constraints::shape_matching::{ShapeMatchGroup, solve_shape_matching};
 use xpbd_core::grid::SpatialHashGrid;
 use xpbd_core::particle::{ParticleSet, Phase};
-use xpbd_core::solver::Solver;
+use xpbd_core::solver::{AppendError, Solver};
 
 #[test]
 fn test_detect_overlapping_particles() {
@@ -20,7 +27,7 @@ fn test_detect_overlapping_particles() {
     let radii = vec![0.1, 0.1];
 
     grid.build(&positions, 2);
-    let contacts = detect_contacts(&positions, &radii, 2, &grid);
+    let contacts = detect_contacts(&positions, &radii, &[1.0; 2], 2, &grid, 0);
 
     assert_eq!(contacts.len(), 1, "should detect one contact");
     assert!(
@@ -41,7 +48,7 @@ fn test_no_contact_when_apart() {
     let radii = vec![0.1, 0.1];
 
     grid.build(&positions, 2);
-    let contacts = detect_contacts(&positions, &radii, 2, &grid);
+    let contacts = detect_contacts(&positions, &radii, &[1.0; 2], 2, &grid, 0);
 
     assert_eq!(contacts.len(), 0, "should detect no contacts");
 }
@@ -61,7 +68,22 @@ fn test_solve_pushes_apart() {
     let mut counts = vec![0u32; 2];
 
     let inv_mass = vec![1.0f32; 2];
-    solve_contacts(&[contact], &positions, &previous, &inv_mass, &mut corrections, &mut counts, 0.0, 1.0 / 60.0);
+    solve_contacts(
+        &[contact],
+        &positions,
+        &previous,
+        &inv_mass,
+        &mut corrections,
+        &mut counts,
+        ContactParams {
+            phase: &[Phase::Free; 2],
+            friction: |_, _| 0.0,
+            granular: GranularFriction::default(),
+            contact_sor: 1.0,
+            max_correction: 0.0,
+            dt: 1.0 / 60.0,
+        },
+    );
 
     // Particle 0 should be pushed in -X, particle 1 in +X
     assert!(corrections[0].x < 0.0, "particle 0 should be pushed left");
@@ -87,7 +109,12 @@ fn test_shape_matching_preserves_rigid_shape() {
     }
 
     // Create shape match group
-    let group = ShapeMatchGroup::from_particles(vec![0, 1, 2, 3], &particles.position, 1.0);
+    let group = ShapeMatchGroup::from_particles(
+        vec![0, 1, 2, 3],
+        &particles.position,
+        &particles.inv_mass,
+        1.0,
+    );
 
     // Deform: move particle 2 away
     particles.predicted[2] = Vec3::new(2.0, 2.0, 0.0);
@@ -99,7 +126,7 @@ fn test_shape_matching_preserves_rigid_shape() {
     }
 
     // Solve
-    solve_shape_matching(&[group], &mut particles);
+    solve_shape_matching(&mut [group], &mut particles);
 
     // All particles should have corrections
     let mut any_corrected = false;
@@ -114,6 +141,47 @@ fn test_shape_matching_preserves_rigid_shape() {
     );
 }
 
+#[test]
+fn test_shape_matching_rest_com_is_mass_weighted() {
+    let mut particles = ParticleSet::new(4);
+    let rest_positions = [
+        Vec3::new(-0.5, -0.5, 0.0),
+        Vec3::new(0.5, -0.5, 0.0),
+        Vec3::new(0.5, 0.5, 0.0),
+        Vec3::new(-0.5, 0.5, 0.0),
+    ];
+    particles.position.copy_from_slice(&rest_positions);
+    particles.inv_mass[2] = 0.1; // ten times heavier than the rest
+
+    let group = ShapeMatchGroup::from_particles(
+        vec![0, 1, 2, 3],
+        &particles.position,
+        &particles.inv_mass,
+        1.0,
+    );
+    let expected_com = Vec3::new(0.5, 0.5, 0.0) * (9.0 / 13.0);
+    assert!(
+        (group.rest_com - expected_com).length() < 1e-5,
+        "rest CoM should lean toward the heavy particle: {:?}",
+        group.rest_com
+    );
+
+    // A pure rotation about the rest CoM is still a rigid transform of the rest shape
+    let rotation = glam::Mat3::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    for (predicted, &rest) in particles.predicted.iter_mut().zip(&rest_positions) {
+        *predicted = rotation * (rest - group.rest_com) + group.rest_com;
+    }
+    solve_shape_matching(&mut [group], &mut particles);
+    for i in 0..4 {
+        assert!(
+            particles.corrections[i].length() < 1e-4,
+            "particle {} corrected by {:?}",
+            i,
+            particles.corrections[i]
+        );
+    }
+}
+
 #[test]
 fn test_shape_matching_rotation_recovery() {
     // Create 4 particles forming a square, then rotate them 90 degrees
@@ -129,7 +197,12 @@ fn test_shape_matching_rotation_recovery() {
         particles.phase[i] = Phase::Rigid;
     }
 
-    let group = ShapeMatchGroup::from_particles(vec![0, 1, 2, 3], &particles.position, 1.0);
+    let group = ShapeMatchGroup::from_particles(
+        vec![0, 1, 2, 3],
+        &particles.position,
+        &particles.inv_mass,
+        1.0,
+    );
 
     // Rotate 90 degrees around Z axis for predicted
     for i in 0..4 {
@@ -142,7 +215,7 @@ fn test_shape_matching_rotation_recovery() {
         particles.corrections[i] = Vec3::ZERO;
         particles.correction_counts[i] = 0;
     }
-    solve_shape_matching(&[group], &mut particles);
+    solve_shape_matching(&mut [group], &mut particles);
 
     // After shape matching, corrections should be near zero because the
     // rotated configuration is a valid rigid transform of the rest shape
@@ -157,6 +230,40 @@ fn test_shape_matching_rotation_recovery() {
     }
 }
 
+/// Largest distance between shape-matching goals and `predicted` for a cube
+/// rotated 180 degrees about Z, solved with `iterations` polar steps.
+fn half_turn_goal_error(iterations: u32) -> f32 {
+    let mut particles = ParticleSet::new(8);
+    for i in 0..8 {
+        particles.position[i] = Vec3::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+        );
+        let p = particles.position[i];
+        particles.predicted[i] = Vec3::new(-p.x, -p.y, p.z);
+    }
+    let mut group = ShapeMatchGroup::from_particles(
+        (0..8).collect(),
+        &particles.position,
+        &particles.inv_mass,
+        1.0,
+    );
+    group.polar_iterations = iterations;
+
+    solve_shape_matching(&mut [group], &mut particles);
+    // With stiffness 1, corrections move each particle all the way to its goal
+    particles.corrections.iter().map(|c| c.length()).fold(0.0, f32::max)
+}
+
+#[test]
+fn test_polar_iterations_control_rotation_convergence() {
+    let converged = half_turn_goal_error(10);
+    let truncated = half_turn_goal_error(2);
+    assert!(converged < 1e-3, "10 iterations should recover the half turn: {}", converged);
+    assert!(truncated > 0.5, "2 iterations should leave a visible residual: {}", truncated);
+}
+
 #[test]
 fn test_rigid_body_creation() {
     let mut solver = Solver::new(10);
@@ -181,6 +288,128 @@ fn test_rigid_body_creation() {
     assert_eq!(solver.shape_match_groups[0].particle_indices.len(), 4);
 }
 
+#[test]
+fn test_rigid_body_from_indices_moves_rigidly() {
+    let mut solver = Solver::new(10);
+    solver.particles.position[0] = Vec3::new(0.0, 0.0, 0.0);
+    solver.particles.position[5] = Vec3::new(0.4, 0.0, 0.0);
+    solver.particles.position[9] = Vec3::new(0.0, 0.4, 0.1);
+    for i in 0..10 {
+        solver.particles.velocity[i] = Vec3::ZERO;
+    }
+
+    solver.create_rigid_body_from_indices(&[0, 5, 9], 1.0);
+
+    assert_eq!(solver.shape_match_groups.len(), 1);
+    assert_eq!(solver.shape_match_groups[0].particle_indices, vec![0, 5, 9]);
+    for i in 0..10 {
+        let expected = if matches!(i, 0 | 5 | 9) { Phase::Rigid } else { Phase::Free };
+        assert_eq!(solver.particles.phase[i], expected, "phase of particle {}", i);
+    }
+
+    // Overlapping, duplicate, and out-of-range selections are rejected
+    solver.create_rigid_body_from_indices(&[9, 3], 1.0);
+    solver.create_rigid_body_from_indices(&[2, 2], 1.0);
+    solver.create_rigid_body_from_indices(&[1, 10], 1.0);
+    assert_eq!(solver.shape_match_groups.len(), 1);
+
+    solver.config.collisions_enabled = true;
+    solver.config.shape_strength = 0.0;
+
+    let rest = [
+        solver.particles.position[0],
+        solver.particles.position[5],
+        solver.particles.position[9],
+    ];
+    for step in 0..30 {
+        solver.step(1.0 / 60.0, step as f32 / 60.0);
+    }
+
+    let now = [
+        solver.particles.position[0],
+        solver.particles.position[5],
+        solver.particles.position[9],
+    ];
+    let moved = (now[0] - rest[0]).length();
+    assert!(moved > 0.01, "rigid body should have moved: {}", moved);
+    for (a, b) in [(0, 1), (1, 2), (0, 2)] {
+        let d_rest = (rest[a] - rest[b]).length();
+        let d_now = (now[a] - now[b]).length();
+        assert!(
+            (d_rest - d_now).abs() < 0.02,
+            "pair ({}, {}) distance changed: rest={}, now={}",
+            a, b, d_rest, d_now
+        );
+    }
+}
+
+#[test]
+fn test_append_offsets_rigid_body_into_fluid_world() {
+    let mut fluid = Solver::new(10);
+    for i in 0..10 {
+        fluid.particles.phase[i] = Phase::Fluid;
+    }
+    let mut rigid = Solver::new(4);
+    let corners = [(0.0, 0.0, 0.0), (0.3, 0.0, 0.0), (0.3, 0.3, 0.0), (0.0, 0.3, 0.1)];
+    for (i, &(x, y, z)) in corners.iter().enumerate() {
+        rigid.particles.position[i] = Vec3::new(x, y + 1.5, z);
+        rigid.particles.velocity[i] = Vec3::new(0.5, 0.0, 0.0);
+    }
+    rigid.create_rigid_body(0, 4, 1.0);
+
+    fluid.append(&rigid).expect("same boundary shape");
+    assert_eq!(fluid.particles.count, 14);
+    assert_eq!(fluid.active_indices().len(), 14);
+    assert_eq!(fluid.shape_match_groups.len(), 1);
+    assert_eq!(fluid.shape_match_groups[0].particle_indices, vec![10, 11, 12, 13]);
+    for i in 10..14 {
+        assert_eq!(fluid.particles.phase[i], Phase::Rigid);
+        assert_eq!(fluid.particles.position[i], rigid.particles.position[i - 10]);
+    }
+
+    fluid.config.collisions_enabled = true;
+    fluid.config.shape_strength = 0.0;
+    let rest: Vec<Vec3> = fluid.particles.position[10..14].to_vec();
+    for step in 0..30 {
+        fluid.step(1.0 / 60.0, step as f32 / 60.0);
+    }
+    let now = &fluid.particles.position[10..14];
+    assert!((now[0] - rest[0]).length() > 0.01, "rigid body should have moved");
+    for (a, b) in [(0, 1), (1, 2), (2, 3), (0, 2), (1, 3)] {
+        let d_rest = (rest[a] - rest[b]).length();
+        let d_now = (now[a] - now[b]).length();
+        assert!((d_rest - d_now).abs() < 0.02, "pair ({a}, {b}): rest={d_rest}, now={d_now}");
+    }
+
+    let mut periodic = Solver::new(2);
+    periodic.config.boundary_shape = BoundaryShape::Periodic { half_extents: Vec3::ONE };
+    assert_eq!(fluid.append(&periodic), Err(AppendError::BoundaryMismatch));
+    assert_eq!(fluid.particles.count, 14);
+}
+
+#[test]
+fn test_append_into_killed_constrained_slot() {
+    let mut world = Solver::new(4);
+    world.distance_constraints.push(DistanceConstraint::new(0, 1, 0.1, 0.0));
+    world.distance_constraints.push(DistanceConstraint::new(1, 2, 0.1, 0.0));
+    world.kill_particles(&[1]);
+
+    let mut pair = Solver::new(2);
+    pair.particles.position[0] = Vec3::new(0.0, 0.5, 0.0);
+    pair.particles.position[1] = Vec3::new(0.2, 0.5, 0.0);
+    pair.distance_constraints.push(DistanceConstraint::new(0, 1, 0.2, 0.0));
+    world.append(&pair).expect("same boundary shape");
+
+    // The first appended particle took the killed slot; it is only bound to
+    // its own partner, never to the dead particle's old neighbours
+    assert_eq!(world.particles.count, 5);
+    assert!(world.particles.alive[1]);
+    assert_eq!(world.particles.position[1], pair.particles.position[0]);
+    assert_eq!(world.distance_constraints.len(), 1);
+    let c = &world.distance_constraints[0];
+    assert_eq!((c.i, c.j), (1, 4));
+}
+
 #[test]
 fn test_rigid_body_solver_integration() {
     let mut solver = Solver::new(4);
@@ -242,8 +471,14 @@ fn test_contact_friction_reduces_tangential_velocity() {
         &inv_mass,
         &mut corr_no_friction,
         &mut counts_no_friction,
-        0.0,
-        1.0 / 60.0,
+        ContactParams {
+            phase: &[Phase::Free; 2],
+            friction: |_, _| 0.0,
+            granular: GranularFriction::default(),
+            contact_sor: 1.0,
+            max_correction: 0.0,
+            dt: 1.0 / 60.0,
+        },
     );
 
     // With friction
@@ -256,8 +491,14 @@ fn test_contact_friction_reduces_tangential_velocity() {
         &inv_mass,
         &mut corr_friction,
         &mut counts_friction,
-        0.5,
-        1.0 / 60.0,
+        ContactParams {
+            phase: &[Phase::Free; 2],
+            friction: |_, _| 0.5,
+            granular: GranularFriction::default(),
+            contact_sor: 1.0,
+            max_correction: 0.0,
+            dt: 1.0 / 60.0,
+        },
     );
 
     // Friction should add additional tangential corrections
@@ -455,8 +696,14 @@ fn test_contact_both_static_no_correction() {
         &inv_mass,
         &mut corrections,
         &mut counts,
-        0.0,
-        1.0 / 60.0,
+        ContactParams {
+            phase: &[Phase::Free; 2],
+            friction: |_, _| 0.0,
+            granular: GranularFriction::default(),
+            contact_sor: 1.0,
+            max_correction: 0.0,
+            dt: 1.0 / 60.0,
+        },
     );
 
     assert_eq!(corrections[0], Vec3::ZERO, "static particle 0 should receive no correction");
@@ -489,8 +736,14 @@ fn test_contact_asymmetric_mass() {
         &inv_mass,
         &mut corrections,
         &mut counts,
-        0.0,
-        1.0 / 60.0,
+        ContactParams {
+            phase: &[Phase::Free; 2],
+            friction: |_, _| 0.0,
+            granular: GranularFriction::default(),
+            contact_sor: 1.0,
+            max_correction: 0.0,
+            dt: 1.0 / 60.0,
+        },
     );
 
     let heavy_correction = corrections[0].length();
@@ -625,13 +878,13 @@ fn test_contact_detect_coincident_particles() {
     let positions = vec![Vec3::ZERO, Vec3::ZERO];
     let radii = vec![0.1, 0.1];
     grid.build(&positions, 2);
-    let contacts = detect_contacts(&positions, &radii, 2, &grid);
+    let contacts = detect_contacts(&positions, &radii, &[1.0; 2], 2, &grid, 0);
     assert_eq!(contacts.len(), 0, "Coincident particles should not generate contact");
 }
 
 #[test]
 fn test_shape_matching_empty_group() {
-    let group = ShapeMatchGroup::from_particles(vec![], &[], 1.0);
+    let group = ShapeMatchGroup::from_particles(vec![], &[], &[], 1.0);
     assert!(group.particle_indices.is_empty());
 
     // Solve with empty group should not crash
@@ -641,7 +894,7 @@ fn test_shape_matching_empty_group() {
         particles.corrections[i] = Vec3::ZERO;
         particles.correction_counts[i] = 0;
     }
-    solve_shape_matching(&[group], &mut particles);
+    solve_shape_matching(&mut [group], &mut particles);
     // No corrections should be applied
     for i in 0..4 {
         assert_eq!(particles.corrections[i], Vec3::ZERO);
@@ -661,8 +914,13 @@ fn test_shape_matching_collinear_particles() {
         particles.correction_counts[i] = 0;
     }
 
-    let group = ShapeMatchGroup::from_particles(vec![0, 1, 2, 3], &particles.position, 1.0);
-    solve_shape_matching(&[group], &mut particles);
+    let group = ShapeMatchGroup::from_particles(
+        vec![0, 1, 2, 3],
+        &particles.position,
+        &particles.inv_mass,
+        1.0,
+    );
+    solve_shape_matching(&mut [group], &mut particles);
 
     // Should not panic or produce NaN
     for i in 0..4 {
@@ -670,3 +928,533 @@ fn test_shape_matching_collinear_particles() {
             "Collinear shape matching should not produce NaN at {}", i);
     }
 }
+
+#[test]
+fn test_melt_and_freeze_phase_transitions() {
+    let mut solver = Solver::new(8);
+    for i in 0..8 {
+        solver.particles.position[i] = Vec3::new(i as f32 * 0.05, 0.0, 0.0);
+    }
+    solver.create_rigid_body(0, 4, 1.0);
+    solver.create_rigid_body(4, 4, 1.0);
+    let com_before = solver.shape_match_groups[1].rest_com;
+
+    // Heat the first cluster and half of the second past the melt point
+    for i in 0..6 {
+        solver.particles.temperature[i] = solver.config.melt_temperature + 1.0;
+    }
+    solver.update_phase_transitions();
+
+    for i in 0..6 {
+        assert_eq!(solver.particles.phase[i], Phase::Fluid, "particle {} should melt", i);
+    }
+    assert_eq!(solver.particles.phase[6], Phase::Rigid);
+    assert_eq!(solver.shape_match_groups.len(), 1, "fully melted group is dropped");
+    let group = &solver.shape_match_groups[0];
+    assert_eq!(group.particle_indices, vec![6, 7]);
+    let rest_sum: Vec3 = group.rest_positions.iter().copied().sum();
+    assert!(rest_sum.length() < 1e-5, "rest shape re-centered: {:?}", rest_sum);
+    let expected_com = (solver.particles.position[6] + solver.particles.position[7]) * 0.5;
+    assert!((group.rest_com - expected_com).length() < 1e-5);
+    assert!((group.rest_com - com_before).length() > 1e-4);
+
+    // Cool the melted particles below the freeze point
+    for i in 0..6 {
+        solver.particles.temperature[i] = solver.config.freeze_temperature - 1.0;
+    }
+    solver.update_phase_transitions();
+
+    for i in 0..8 {
+        assert_eq!(solver.particles.phase[i], Phase::Rigid, "particle {} should be rigid", i);
+    }
+    assert_eq!(solver.shape_match_groups.len(), 2);
+    assert_eq!(solver.shape_match_groups[1].particle_indices, vec![0, 1, 2, 3, 4, 5]);
+
+    // Freezing to Static pins particles instead of forming a group
+    solver.particles.temperature[0] = solver.config.melt_temperature + 1.0;
+    solver.update_phase_transitions();
+    solver.particles.inv_mass[0] = 0.25;
+    solver.config.freeze_to_static = true;
+    solver.particles.temperature[0] = solver.config.freeze_temperature - 1.0;
+    solver.update_phase_transitions();
+    assert_eq!(solver.particles.phase[0], Phase::Static);
+    assert_eq!(solver.particles.inv_mass[0], 0.0);
+    assert_eq!(solver.shape_match_groups.len(), 2);
+
+    // Melting the pinned particle gives back the mass it froze with
+    solver.particles.temperature[0] = solver.config.melt_temperature + 1.0;
+    solver.update_phase_transitions();
+    assert_eq!(solver.particles.phase[0], Phase::Fluid);
+    assert_eq!(solver.particles.inv_mass[0], 0.25);
+}
+
+#[test]
+fn test_freezing_disjoint_fluid_regions_forms_one_body_each() {
+    let mut solver = Solver::new(6);
+    for i in 0..6 {
+        // Two clumps of three, far apart and interleaved by index
+        let side = if i % 2 == 0 { -1.0 } else { 1.0 };
+        solver.particles.position[i] = Vec3::new(side, (i / 2) as f32 * 0.05, 0.0);
+        solver.particles.phase[i] = Phase::Fluid;
+        solver.particles.temperature[i] = solver.config.freeze_temperature - 1.0;
+    }
+    solver.update_phase_transitions();
+
+    assert_eq!(solver.shape_match_groups.len(), 2);
+    assert_eq!(solver.shape_match_groups[0].particle_indices, vec![0, 2, 4]);
+    assert_eq!(solver.shape_match_groups[1].particle_indices, vec![1, 3, 5]);
+}
+
+#[test]
+fn test_contact_cap_keeps_deepest_overlaps() {
+    // Dense 4x4x4 pile where every particle overlaps many neighbors
+    let mut positions = Vec::new();
+    for i in 0..64 {
+        let x = (i % 4) as f32;
+        let y = ((i / 4) % 4) as f32;
+        let z = (i / 16) as f32;
+        positions.push(Vec3::new(x, y, z) * 0.05);
+    }
+    // One particle sits almost on top of particle 0: the deepest overlap
+    positions.push(Vec3::new(0.001, 0.0, 0.0));
+    let count = positions.len();
+    let radii = vec![0.06; count];
+    let inv_mass = vec![1.0; count];
+    let mut grid = SpatialHashGrid::new(0.2, 4096, count);
+    grid.build(&positions, count);
+
+    let uncapped = detect_contacts(&positions, &radii, &inv_mass, count, &grid, 0);
+    let max = 4;
+    let capped = detect_contacts(&positions, &radii, &inv_mass, count, &grid, max);
+
+    assert!(uncapped.len() > count * max, "scene should exceed the cap");
+    assert!(capped.len() <= count * max);
+    let mut per_particle = vec![0; count];
+    for c in &capped {
+        per_particle[c.i as usize] += 1;
+        per_particle[c.j as usize] += 1;
+    }
+    assert!(per_particle.iter().all(|&n| n <= max));
+
+    let deepest = uncapped.iter().map(|c| c.penetration).fold(0.0_f32, f32::max);
+    assert!(
+        capped.iter().any(|c| c.i == 0 && c.j == 64 && c.penetration == deepest),
+        "deepest overlap must survive the cap"
+    );
+}
+
+/// Contact corrections for particle 0 (radius `r0`) sitting 0.15 from particle 1.
+fn enlarged_pair_corrections(r0: f32, couple_mass: bool) -> (usize, Vec3, Vec3) {
+    let mut solver = Solver::new(2);
+    solver.particles.position[0] = Vec3::ZERO;
+    solver.particles.position[1] = Vec3::new(0.15, 0.0, 0.0);
+    if couple_mass {
+        solver.couple_mass_to_radius(true, 1000.0);
+    }
+    solver.set_particle_radius(0, r0);
+
+    let p = &mut solver.particles;
+    let mut grid = SpatialHashGrid::new(0.2, 1024, 2);
+    grid.build(&p.position, 2);
+    let contacts = detect_contacts(&p.position, &p.radius, &p.inv_mass, 2, &grid, 0);
+    let previous = p.position.clone();
+    solve_contacts(
+        &contacts,
+        &p.position,
+        &previous,
+        &p.inv_mass,
+        &mut p.corrections,
+        &mut p.correction_counts,
+        ContactParams {
+            phase: &p.phase,
+            friction: |_, _| 0.0,
+            granular: GranularFriction::default(),
+            contact_sor: 1.0,
+            max_correction: 0.0,
+            dt: 0.016,
+        },
+    );
+    (contacts.len(), p.corrections[0], p.corrections[1])
+}
+
+#[test]
+fn test_particle_radius_drives_contacts_and_mass() {
+    assert_eq!(enlarged_pair_corrections(0.05, false).0, 0, "default radii do not touch");
+
+    let (n, c0, c1) = enlarged_pair_corrections(0.12, false);
+    assert_eq!(n, 1, "enlarged particle should reach its neighbor");
+    assert!(c1.x > 0.0, "neighbor should be pushed away");
+    assert!((c0.length() - c1.length()).abs() < 1e-6, "unit masses share the push");
+
+    let (_, c0, c1) = enlarged_pair_corrections(0.12, true);
+    assert!(
+        c0.length() * 5.0 < c1.length(),
+        "heavier enlarged particle should barely move: {} vs {}",
+        c0.length(),
+        c1.length()
+    );
+}
+
+#[test]
+fn test_uncoupling_mass_restores_earlier_masses() {
+    let mut solver = Solver::new(3);
+    solver.particles.inv_mass[0] = 0.25;
+    solver.particles.inv_mass[2] = 0.0;
+
+    solver.couple_mass_to_radius(true, 1000.0);
+    assert!(solver.particles.inv_mass[0] != 0.25);
+    // Re-coupling with another density must not overwrite the saved masses
+    solver.couple_mass_to_radius(true, 500.0);
+    solver.couple_mass_to_radius(false, 500.0);
+
+    assert_eq!(solver.particles.inv_mass[..3], [0.25, 1.0, 0.0]);
+}
+
+#[test]
+fn test_static_static_pairs_skip_contacts() {
+    let positions = vec![Vec3::ZERO, Vec3::new(0.05, 0.0, 0.0), Vec3::new(0.1, 0.0, 0.0)];
+    let radii = vec![0.05; 3];
+    let mut grid = SpatialHashGrid::new(0.2, 1024, 3);
+    grid.build(&positions, 3);
+
+    // 0 and 1 are static, 2 is dynamic: only the 1-2 overlap remains
+    let contacts = detect_contacts(&positions, &radii, &[0.0, 0.0, 1.0], 3, &grid, 0);
+    assert_eq!(contacts.len(), 1);
+    assert_eq!((contacts[0].i, contacts[0].j), (1, 2));
+
+    // A dense shell of static particles with a few dynamic ones inside
+    let mut positions = Vec::new();
+    for i in 0..64 {
+        positions.push(Vec3::new((i % 8) as f32, (i / 8) as f32, 0.0) * 0.05);
+    }
+    let count = positions.len();
+    let radii = vec![0.04; count];
+    let mut grid = SpatialHashGrid::new(0.2, 4096, count);
+    grid.build(&positions, count);
+    let all_dynamic = detect_contacts(&positions, &radii, &vec![1.0; count], count, &grid, 0);
+    let inv_mass: Vec<f32> = (0..count).map(|i| if i % 8 == 0 { 1.0 } else { 0.0 }).collect();
+    let mostly_static = detect_contacts(&positions, &radii, &inv_mass, count, &grid, 0);
+    assert!(!mostly_static.is_empty(), "dynamic-static overlaps still collide");
+    assert!(
+        mostly_static.len() * 2 < all_dynamic.len(),
+        "static pairs should be dropped: {} vs {}",
+        mostly_static.len(),
+        all_dynamic.len()
+    );
+}
+
+#[test]
+fn test_reset_rigid_body_restores_rest_shape() {
+    let mut solver = Solver::new(4);
+    let square = [
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.4, 0.0, 0.0),
+        Vec3::new(0.4, 0.4, 0.0),
+        Vec3::new(0.0, 0.4, 0.0),
+    ];
+    solver.particles.position[..4].copy_from_slice(&square);
+    solver.create_rigid_body(0, 4, 0.9);
+
+    // Crush and shift the square, with some leftover motion
+    solver.particles.position[0] = Vec3::new(1.0, 1.0, 0.0);
+    solver.particles.position[1] = Vec3::new(1.1, 1.0, 0.2);
+    solver.particles.position[2] = Vec3::new(1.1, 1.05, 0.0);
+    solver.particles.position[3] = Vec3::new(0.9, 1.3, -0.1);
+    solver.particles.velocity[2] = Vec3::new(3.0, 0.0, 0.0);
+    let com = solver.particles.position[..4].iter().copied().sum::<Vec3>() / 4.0;
+
+    solver.reset_rigid_body(0);
+    solver.reset_rigid_body(7); // out of range: no-op
+
+    let p = &solver.particles.position;
+    for a in 0..4 {
+        for b in (a + 1)..4 {
+            let rest = (square[a] - square[b]).length();
+            assert!(((p[a] - p[b]).length() - rest).abs() < 1e-5, "pair ({}, {}) distorted", a, b);
+        }
+    }
+    let new_com = p[..4].iter().copied().sum::<Vec3>() / 4.0;
+    assert!((new_com - com).length() < 1e-5, "reset should keep the current CoM");
+    assert!(solver.particles.velocity[..4].iter().all(|v| *v == Vec3::ZERO));
+    assert_eq!(solver.particles.predicted[2], p[2]);
+}
+
+/// Gap between two deeply overlapping particles after each of `steps` contact passes.
+fn soft_separation(max_correction: f32, steps: usize) -> Vec<f32> {
+    let mut positions = vec![Vec3::ZERO, Vec3::new(0.02, 0.0, 0.0)];
+    let radii = vec![0.1; 2];
+    let inv_mass = vec![1.0; 2];
+    let mut grid = SpatialHashGrid::new(0.2, 1024, 2);
+    let mut gaps = Vec::new();
+    for _ in 0..steps {
+        grid.build(&positions, 2);
+        let contacts = detect_contacts(&positions, &radii, &inv_mass, 2, &grid, 0);
+        let mut corrections = vec![Vec3::ZERO; 2];
+        let mut counts = vec![0u32; 2];
+        solve_contacts(
+            &contacts,
+            &positions,
+            &positions.clone(),
+            &inv_mass,
+            &mut corrections,
+            &mut counts,
+            ContactParams {
+                phase: &[Phase::Free; 2],
+                friction: |_, _| 0.0,
+                granular: GranularFriction::default(),
+                contact_sor: 1.0,
+                max_correction,
+                dt: 1.0 / 60.0,
+            },
+        );
+        for i in 0..2 {
+            positions[i] += corrections[i];
+        }
+        gaps.push((positions[1] - positions[0]).length());
+    }
+    gaps
+}
+
+#[test]
+fn test_max_penetration_correction_separates_gradually() {
+    let hard = soft_separation(0.0, 1);
+    assert!((hard[0] - 0.2).abs() < 1e-5, "uncapped contact pops apart at once: {}", hard[0]);
+
+    let soft = soft_separation(0.02, 20);
+    assert!(soft[0] < 0.05, "capped contact should only separate a little per step: {}", soft[0]);
+    assert!(soft.windows(2).all(|w| w[1] >= w[0]), "separation should grow monotonically");
+    assert!((soft[19] - 0.2).abs() < 1e-4, "soft contact should still converge: {}", soft[19]);
+}
+
+#[test]
+fn test_shape_group_membership() {
+    let mut solver = Solver::new(10);
+    for i in 0..10 {
+        solver.particles.position[i] = Vec3::new(i as f32 * 0.1, 0.0, 0.0);
+    }
+    solver.create_rigid_body(0, 3, 0.9);
+    solver.create_rigid_body_from_indices(&[5, 7, 9], 0.9);
+
+    assert_eq!(
+        solver.shape_group_membership(),
+        vec![0, 0, 0, -1, -1, 1, -1, 1, -1, 1]
+    );
+}
+
+/// Rebound speed of a Free particle thrown at a pinned particle of `target_phase`.
+fn rebound_speed(target_phase: Phase) -> f32 {
+    let mut solver = Solver::new(2);
+    solver.config.collisions_enabled = true;
+    solver.config.restitution = 0.8;
+    solver.config.contact_restitution = true;
+    solver.config.phase_restitution[Phase::Granular as usize] = 0.1;
+    solver.config.shape_strength = 1.0;
+    solver.config.shape_attract_phases = [false; 7];
+    solver.particles.radius[..2].copy_from_slice(&[0.1, 0.1]);
+    solver.particles.phase[0] = target_phase;
+    solver.particles.inv_mass[0] = 0.0;
+    solver.particles.position[0] = Vec3::ZERO;
+    solver.particles.position[1] = Vec3::new(-0.25, 0.0, 0.0);
+    solver.particles.velocity[1] = Vec3::new(6.0, 0.0, 0.0);
+
+    let mut rebound = 0.0f32;
+    for frame in 0..10 {
+        solver.step(0.016, frame as f32 * 0.016);
+        rebound = rebound.max(-solver.particles.velocity[1].x);
+    }
+    rebound
+}
+
+#[test]
+fn test_phase_restitution_uses_lower_of_pair() {
+    let bouncy = rebound_speed(Phase::Free);
+    let dead = rebound_speed(Phase::Granular);
+    assert!(bouncy > 1.0, "Free-Free contact should bounce: {}", bouncy);
+    assert!(dead < bouncy * 0.4, "Granular should absorb the hit: {} vs {}", dead, bouncy);
+}
+
+/// Drop a jittered 2D block of particles of `phase` onto a floor of densely
+/// packed Static particles and let it settle. Returns (pile height, half-width).
+fn settled_pile(phase: Phase) -> (f32, f32) {
+    let (cols, rows, r) = (6, 6, 0.05_f32);
+    let pile = cols * rows;
+    let floor = 121;
+    let count = pile + floor;
+    let mut pos: Vec<Vec3> = (0..pile)
+        .map(|i| {
+            let jitter = ((i * 7919) % 13) as f32 / 13.0 - 0.5;
+            Vec3::new(
+                ((i % cols) as f32 - (cols as f32 - 1.0) * 0.5) * 2.0 * r + jitter * 0.2 * r,
+                2.0 * r + (i / cols) as f32 * 2.05 * r,
+                0.0,
+            )
+        })
+        .chain((0..floor).map(|k| Vec3::new((k as f32 - 60.0) * 0.025, 0.0, 0.0)))
+        .collect();
+    let mut vel = vec![Vec3::ZERO; count];
+    let radii = vec![r; count];
+    let inv_mass: Vec<f32> = (0..count).map(|i| if i < pile { 1.0 } else { 0.0 }).collect();
+    let phases: Vec<Phase> =
+        (0..count).map(|i| if i < pile { phase } else { Phase::Static }).collect();
+    let granular = GranularFriction { friction: 0.9, static_velocity: 0.05 };
+    let mut grid = SpatialHashGrid::new(0.2, 4096, count);
+    let dt = 1.0 / 240.0;
+
+    for _ in 0..1200 {
+        let mut predicted: Vec<Vec3> = (0..count)
+            .map(|i| pos[i] + (vel[i] + Vec3::new(0.0, -9.81, 0.0) * inv_mass[i] * dt) * dt)
+            .collect();
+        grid.build(&predicted, count);
+        let contacts = detect_contacts(&predicted, &radii, &inv_mass, count, &grid, 0);
+        let mut corrections = vec![Vec3::ZERO; count];
+        let mut counts = vec![0u32; count];
+        let params = ContactParams {
+            phase: &phases,
+            friction: |_, _| 0.05,
+            granular,
+            contact_sor: 1.0,
+            max_correction: 0.0,
+            dt,
+        };
+        solve_contacts(&contacts, &predicted, &pos, &inv_mass, &mut corrections, &mut counts, params);
+        for i in 0..pile {
+            if counts[i] > 0 {
+                predicted[i] += corrections[i] / counts[i] as f32;
+            }
+            vel[i] = (predicted[i] - pos[i]) / dt;
+            pos[i] = predicted[i];
+        }
+    }
+
+    let pile_pos = &pos[..pile];
+    let height = pile_pos.iter().map(|p| p.y).fold(0.0, f32::max);
+    let half_width = pile_pos.iter().map(|p| p.x.abs()).fold(0.0, f32::max);
+    (height, half_width)
+}
+
+#[test]
+fn test_granular_pile_holds_slope() {
+    let (free_height, free_width) = settled_pile(Phase::Free);
+    let (sand_height, sand_width) = settled_pile(Phase::Granular);
+    // Free particles slide flat into a single layer; sand keeps a heap
+    assert!(sand_height > free_height * 1.5, "sand {} vs free {}", sand_height, free_height);
+    assert!(sand_width < free_width, "sand {} vs free {}", sand_width, free_width);
+}
+
+#[test]
+fn test_crossing_rope_segments_pushed_apart_along_segment_normal() {
+    // Rope A runs along x, rope B along z just above it; the endpoints are far
+    // apart, only the segment middles overlap.
+    let positions = [
+        Vec3::new(-0.5, 0.0, 0.0),
+        Vec3::new(0.5, 0.0, 0.0),
+        Vec3::new(0.1, 0.03, -0.5),
+        Vec3::new(0.1, 0.03, 0.5),
+    ];
+    let radii = [0.02; 4];
+    let inv_mass = [1.0; 4];
+    let segments = [[0, 1], [2, 3]];
+
+    let mut grid = SpatialHashGrid::new(0.1, 1024, 4);
+    grid.build(&positions, 4);
+    assert!(detect_contacts(&positions, &radii, &inv_mass, 4, &grid, 0).is_empty());
+
+    let contacts = detect_capsule_contacts(&positions, &radii, &inv_mass, &segments, &grid);
+    assert_eq!(contacts.len(), 1);
+    let contact = &contacts[0];
+    assert!((contact.s - 0.6).abs() < 1e-5 && (contact.t - 0.5).abs() < 1e-5);
+    assert!(contact.normal.distance(Vec3::Y) < 1e-5, "normal={:?}", contact.normal);
+    assert!((contact.penetration - 0.01).abs() < 1e-5);
+
+    // Nearest endpoint pair (1, 3) would push along a diagonal instead
+    let point_normal = (positions[3] - positions[1]).normalize();
+    assert!(point_normal.dot(Vec3::Y) < 0.1);
+
+    let mut corrections = [Vec3::ZERO; 4];
+    let mut counts = [0u32; 4];
+    solve_capsule_contacts(&contacts, &inv_mass, &mut corrections, &mut counts);
+    for (k, c) in corrections.iter().enumerate() {
+        assert!(c.x.abs() < 1e-6 && c.z.abs() < 1e-6, "correction {k} off-normal: {:?}", c);
+        assert_eq!(counts[k], 1);
+    }
+    assert!(corrections[0].y < 0.0 && corrections[1].y < 0.0);
+    assert!(corrections[2].y > 0.0 && corrections[3].y > 0.0);
+
+    // The closest points end up exactly touching
+    let moved: Vec<Vec3> = positions.iter().zip(&corrections).map(|(p, c)| *p + *c).collect();
+    let gap = moved[2].lerp(moved[3], contact.t) - moved[0].lerp(moved[1], contact.s);
+    assert!((gap.length() - 0.04).abs() < 1e-5, "gap={}", gap.length());
+}
+
+#[test]
+fn test_speculative_reach_uses_pair_radius_sum() {
+    // A small particle resting against a much larger one: the pair overlaps, but
+    // the larger one lies beyond twice the small radius plus the margin.
+    let positions = [Vec3::ZERO, Vec3::new(0.3, 0.0, 0.0)];
+    let radii = [0.02, 0.3];
+    let mut grid = SpatialHashGrid::new(0.1, 1024, 2);
+    grid.build(&positions, 2);
+
+    let contacts = detect_speculative_contacts(&positions, &positions, &radii, &[1.0; 2], 2, &grid, 0.01);
+    assert_eq!(contacts.len(), 1);
+    assert!((contacts[0].penetration - 0.02).abs() < 1e-5);
+}
+
+#[test]
+fn test_capsules_only_for_rope_bonds_and_replace_bonded_sphere_contacts() {
+    let mut solver = Solver::new(12);
+    solver.create_cloth(0, 3, 3, 0.1, 0.0, 0.0);
+    // A three-particle rope whose spheres overlap along the chain
+    for k in 9..12 {
+        solver.particles.position[k] = Vec3::new((k - 9) as f32 * 0.15, -1.0, 0.0);
+    }
+    for k in 9..11 {
+        solver.distance_constraints.push(DistanceConstraint::new(k, k + 1, 0.15, 0.0));
+    }
+    assert_eq!(capsule_segments(&solver.distance_constraints), vec![[9, 10], [10, 11]]);
+
+    let count = solver.particles.count;
+    let positions = solver.particles.position[..count].to_owned();
+    solver.particles.predicted[..count].copy_from_slice(&positions);
+    solver.particles.radius[..count].fill(0.1);
+
+    // Cloth bonds keep their particle contacts; only the two rope bonds lose theirs
+    let mut contact_counts = [0; 2];
+    for (capsules, contacts) in [false, true].into_iter().zip(&mut contact_counts) {
+        solver.config.capsule_contacts = capsules;
+        solver.solve_collision_constraints(0, 0.001);
+        *contacts = solver.last_stats.contact_count;
+    }
+    assert!(contact_counts[0] > 2, "cloth neighbors overlap: {:?}", contact_counts);
+    assert_eq!(contact_counts[1], contact_counts[0] - 2);
+}
+
+#[test]
+fn test_material_combine_rules_for_contact_pair() {
+    let mut solver = Solver::new(2);
+    solver.config.friction = 1.0;
+    solver.config.restitution = 1.0;
+    solver.particles.friction[0] = 0.9;
+    solver.particles.friction[1] = 0.1;
+    solver.particles.restitution[0] = 0.8;
+    solver.particles.restitution[1] = 0.2;
+
+    let cases = [
+        (MaterialCombine::Average, 0.5, 0.5),
+        (MaterialCombine::Min, 0.1, 0.2),
+        (MaterialCombine::GeometricMean, 0.3, 0.4),
+    ];
+    for (combine, friction, restitution) in cases {
+        solver.config.material_combine = combine;
+        assert!((solver.contact_friction(0, 1) - friction).abs() < 1e-6, "{:?} friction", combine);
+        assert!((solver.contact_friction(1, 0) - friction).abs() < 1e-6, "{:?} is symmetric", combine);
+        assert!((solver.contact_restitution(0, 1) - restitution).abs() < 1e-6, "{:?} restitution", combine);
+    }
+
+    // The global coefficient and phase multipliers still scale the result
+    solver.config.friction = 0.5;
+    solver.config.phase_restitution[Phase::Free as usize] = 0.5;
+    solver.config.material_combine = MaterialCombine::Min;
+    assert!((solver.contact_friction(0, 1) - 0.05).abs() < 1e-6);
+    assert!((solver.contact_restitution(0, 1) - 0.1).abs() < 1e-6);
+}
@@ -23,6 +23,20 @@ fn test_grid_build_and_query() {
     assert!(!neighbors.contains(&2), "should NOT find far particle");
 }
 
+#[test]
+fn test_grid_masked_build_skips_excluded_particles() {
+    let mut grid = SpatialHashGrid::new(1.0, 1024, 3);
+    let positions = [Vec3::new(0.1, 0.1, 0.1), Vec3::new(0.2, 0.2, 0.2), Vec3::new(0.3, 0.1, 0.2)];
+
+    grid.build_masked(&positions, 3, &[true, false, true]);
+
+    let mut neighbors = Vec::new();
+    grid.query_neighbors(positions[0], |idx| neighbors.push(idx));
+    neighbors.sort_unstable();
+    assert_eq!(neighbors, vec![0, 2]);
+    assert_eq!(grid.sorted_indices().len(), 2);
+}
+
 #[test]
 fn test_grid_all_particles_found() {
     let mut grid = SpatialHashGrid::new(0.2, 131072, 1000);
@@ -149,3 +163,115 @@ fn test_grid_single_particle() {
         "should find single particle at origin"
     );
 }
+
+#[test]
+fn test_grid_update_matches_full_build() {
+    let count = 500;
+    let mut positions: Vec<Vec3> = (0..count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            let angle = t * std::f32::consts::TAU * 13.0;
+            let r = 0.3 + t * 1.5;
+            Vec3::new(angle.cos() * r, (t - 0.5) * 2.0, angle.sin() * r)
+        })
+        .collect();
+
+    // Small table so buckets are dense and moves slide across many of them
+    let mut incremental = SpatialHashGrid::new(0.2, 512, count);
+    incremental.build(&positions, count);
+
+    // Perturb a few particles across cell borders, in both hash directions
+    for (k, i) in [3, 77, 150, 151, 260, 499].into_iter().enumerate() {
+        let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+        positions[i] += Vec3::new(0.31, -0.17, 0.23) * sign;
+    }
+    incremental.update(&positions, count);
+
+    let mut full = SpatialHashGrid::new(0.2, 512, count);
+    full.build(&positions, count);
+
+    let neighbors = |grid: &SpatialHashGrid, pos: Vec3| {
+        let mut found = Vec::new();
+        grid.query_neighbors(pos, |j| found.push(j));
+        found.sort_unstable();
+        found
+    };
+    for pos in &positions {
+        assert_eq!(neighbors(&incremental, *pos), neighbors(&full, *pos));
+    }
+
+    // Large perturbation falls back to a full rebuild and still agrees
+    for p in positions.iter_mut() {
+        *p += Vec3::splat(0.45);
+    }
+    incremental.update(&positions, count);
+    full.build(&positions, count);
+    for pos in &positions {
+        assert_eq!(neighbors(&incremental, *pos), neighbors(&full, *pos));
+    }
+}
+
+#[test]
+fn test_grid_update_on_sparse_table_matches_full_build() {
+    // Few particles in a large table: moves slide across far-apart buckets
+    let count = 40;
+    let mut positions: Vec<Vec3> =
+        (0..count).map(|i| Vec3::new(i as f32 * 0.13, (i % 7) as f32 * 0.21, 0.0)).collect();
+    let mut incremental = SpatialHashGrid::new(0.2, 131072, count);
+    incremental.build(&positions, count);
+
+    for i in [5, 17, 33] {
+        positions[i] += Vec3::new(0.0, 0.0, 0.5);
+    }
+    incremental.update(&positions, count);
+    let mut full = SpatialHashGrid::new(0.2, 131072, count);
+    full.build(&positions, count);
+
+    for pos in &positions {
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        incremental.query_neighbors(*pos, |j| a.push(j));
+        full.query_neighbors(*pos, |j| b.push(j));
+        a.sort_unstable();
+        b.sort_unstable();
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn test_grid_set_table_size_keeps_neighbors() {
+    let count = 400;
+    let positions: Vec<Vec3> = (0..count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            let angle = t * std::f32::consts::TAU * 7.0;
+            Vec3::new(angle.cos(), (t - 0.5) * 2.0, angle.sin()) * (0.5 + t)
+        })
+        .collect();
+
+    // True neighbors (within one cell size) must be reported at any table size
+    let cell = 0.2;
+    let close_neighbors = |grid: &SpatialHashGrid, i: usize| {
+        let mut found = Vec::new();
+        grid.query_neighbors(positions[i], |j| {
+            if positions[i].distance(positions[j as usize]) < cell {
+                found.push(j);
+            }
+        });
+        found.sort_unstable();
+        found.dedup();
+        found
+    };
+
+    let mut grid = SpatialHashGrid::new(cell, 64, count);
+    grid.build(&positions, count);
+    let before: Vec<Vec<u32>> = (0..count).map(|i| close_neighbors(&grid, i)).collect();
+
+    grid.set_table_size(65536);
+    assert_eq!(grid.table_size(), 65536);
+    grid.build(&positions, count);
+    for (i, expected) in before.iter().enumerate() {
+        assert_eq!(&close_neighbors(&grid, i), expected, "particle {}", i);
+        assert!(expected.contains(&(i as u32)));
+    }
+}
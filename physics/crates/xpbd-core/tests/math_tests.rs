@@ -156,3 +156,38 @@ fn test_fbm_nonnegative() {
         assert!(v.is_finite(), "fbm({},{}) should be finite", x, y);
     }
 }
+
+#[test]
+fn test_pcg32_same_seed_reproduces_sequence() {
+    let mut a = Pcg32::new(42);
+    let mut b = Pcg32::new(42);
+    for _ in 0..100 {
+        assert_eq!(a.next_u32(), b.next_u32());
+    }
+    assert_eq!(a.next_unit_vec3(), b.next_unit_vec3());
+}
+
+#[test]
+fn test_pcg32_different_seeds_diverge() {
+    let mut a = Pcg32::new(1);
+    let mut b = Pcg32::new(2);
+    let same = (0..100).filter(|_| a.next_u32() == b.next_u32()).count();
+    assert!(same < 3, "seeds 1 and 2 matched {same} of 100 draws");
+}
+
+#[test]
+fn test_pcg32_f32_range_and_unit_vectors() {
+    let mut rng = Pcg32::new(7);
+    let mut sum = 0.0;
+    for _ in 0..10_000 {
+        let x = rng.next_f32();
+        assert!((0.0..1.0).contains(&x), "next_f32 out of [0,1): {x}");
+        sum += x;
+    }
+    let mean = sum / 10_000.0;
+    assert!((mean - 0.5).abs() < 0.02, "mean {mean} should be near 0.5");
+    for _ in 0..100 {
+        let v = rng.next_unit_vec3();
+        assert!((v.length() - 1.0).abs() < 1e-5, "not unit length: {v:?}");
+    }
+}
@@ -1,6 +1,6 @@
 use glam::Vec3;
-use xpbd_core::config::PhysicsConfig;
-use xpbd_core::particle::{ParticleSet, Phase};
+use xpbd_core::config::{BoundaryShape, MaterialCombine, PhysicsConfig};
+use xpbd_core::particle::{ParticleSet, Phase, FRAME_STRIDE};
 
 #[test]
 fn test_particle_set_new_initializes_correctly() {
@@ -12,6 +12,13 @@ fn test_particle_set_new_initializes_correctly() {
         assert_eq!(particles.position[i], Vec3::ZERO, "position[{i}] should be ZERO");
         assert_eq!(particles.velocity[i], Vec3::ZERO, "velocity[{i}] should be ZERO");
         assert_eq!(particles.inv_mass[i], 1.0, "inv_mass[{i}] should be 1.0");
+        assert_eq!(particles.uncoupled_inv_mass[i], 1.0, "uncoupled_inv_mass[{i}] should be 1.0");
+        assert!(particles.alive[i], "alive[{i}] should be true");
+        assert_eq!(particles.user_id[i], i as u32, "user_id[{i}] should be its index");
+        assert_eq!(particles.age[i], 0.0, "age[{i}] should be 0.0");
+        assert_eq!(particles.lifetime[i], 0.0, "lifetime[{i}] should be 0.0");
+        assert_eq!(particles.friction[i], 1.0, "friction[{i}] should be 1.0");
+        assert_eq!(particles.restitution[i], 1.0, "restitution[{i}] should be 1.0");
         assert_eq!(particles.phase[i], Phase::Free, "phase[{i}] should be Free");
         assert_eq!(particles.radius[i], 0.05, "radius[{i}] should be 0.05");
         assert_eq!(particles.hash[i], 0.0, "hash[{i}] should be 0.0");
@@ -24,9 +31,19 @@ fn test_particle_set_new_initializes_correctly() {
             "correction_counts[{i}] should be 0"
         );
         assert_eq!(particles.lambda[i], 0.0, "lambda[{i}] should be 0.0");
+        assert_eq!(particles.rest_density[i], 0.0, "rest_density[{i}] should be 0.0");
         assert_eq!(particles.density[i], 0.0, "density[{i}] should be 0.0");
+        assert_eq!(particles.pressure[i], 0.0, "pressure[{i}] should be 0.0");
+        assert_eq!(particles.divergence[i], 0.0, "divergence[{i}] should be 0.0");
+        assert_eq!(particles.heat[i], 0.0, "heat[{i}] should be 0.0");
         assert_eq!(particles.vorticity[i], Vec3::ZERO, "vorticity[{i}] should be ZERO");
         assert_eq!(particles.charge[i], 0.0, "charge[{i}] should be 0.0");
+        assert_eq!(particles.temperature[i], 0.0, "temperature[{i}] should be 0.0");
+        assert_eq!(particles.acceleration[i], Vec3::ZERO, "acceleration[{i}] should be ZERO");
+        assert_eq!(
+            particles.external_force[i], Vec3::ZERO,
+            "external_force[{i}] should be ZERO"
+        );
     }
 }
 
@@ -38,6 +55,13 @@ fn test_particle_set_zero_count() {
     assert_eq!(particles.position.len(), 0);
     assert_eq!(particles.velocity.len(), 0);
     assert_eq!(particles.inv_mass.len(), 0);
+    assert_eq!(particles.uncoupled_inv_mass.len(), 0);
+    assert_eq!(particles.alive.len(), 0);
+    assert_eq!(particles.user_id.len(), 0);
+    assert_eq!(particles.age.len(), 0);
+    assert_eq!(particles.lifetime.len(), 0);
+    assert_eq!(particles.friction.len(), 0);
+    assert_eq!(particles.restitution.len(), 0);
     assert_eq!(particles.phase.len(), 0);
     assert_eq!(particles.radius.len(), 0);
     assert_eq!(particles.hash.len(), 0);
@@ -47,9 +71,68 @@ fn test_particle_set_zero_count() {
     assert_eq!(particles.corrections.len(), 0);
     assert_eq!(particles.correction_counts.len(), 0);
     assert_eq!(particles.lambda.len(), 0);
+    assert_eq!(particles.rest_density.len(), 0);
     assert_eq!(particles.density.len(), 0);
+    assert_eq!(particles.pressure.len(), 0);
+    assert_eq!(particles.divergence.len(), 0);
+    assert_eq!(particles.heat.len(), 0);
     assert_eq!(particles.vorticity.len(), 0);
     assert_eq!(particles.charge.len(), 0);
+    assert_eq!(particles.temperature.len(), 0);
+    assert_eq!(particles.acceleration.len(), 0);
+    assert_eq!(particles.external_force.len(), 0);
+}
+
+#[test]
+fn test_particle_set_resize_preserves_and_defaults() {
+    let mut particles = ParticleSet::new(100);
+    for i in 0..100 {
+        particles.position[i] = Vec3::new(i as f32, 1.0, -2.0);
+        particles.inv_mass[i] = 0.5;
+    }
+
+    particles.resize(200);
+
+    assert_eq!(particles.count, 200);
+    assert_eq!(particles.external_force.len(), 200);
+    for i in 0..100 {
+        assert_eq!(particles.position[i], Vec3::new(i as f32, 1.0, -2.0));
+        assert_eq!(particles.inv_mass[i], 0.5);
+    }
+    let fresh = ParticleSet::new(1);
+    for i in 100..200 {
+        assert_eq!(particles.position[i], Vec3::ZERO);
+        assert_eq!(particles.radius[i], fresh.radius[0]);
+        assert_eq!(particles.inv_mass[i], fresh.inv_mass[0]);
+        assert_eq!(particles.phase[i], Phase::Free);
+        assert_eq!(particles.temperature[i], 0.0);
+    }
+
+    particles.resize(50);
+    assert_eq!(particles.count, 50);
+    assert_eq!(particles.position.len(), 50);
+    assert_eq!(particles.position[49], Vec3::new(49.0, 1.0, -2.0));
+}
+
+#[test]
+fn test_capture_frame_interleaves_particle_state() {
+    let mut particles = ParticleSet::new(5);
+    for i in 0..5 {
+        particles.position[i] = Vec3::new(i as f32, -1.0, 2.5);
+        particles.velocity[i] = Vec3::new(0.0, i as f32, 0.5);
+    }
+    particles.phase[3] = Phase::Rigid;
+    particles.charge[3] = -2.0;
+
+    let frame = particles.capture_frame();
+    assert_eq!(frame.len(), 5 * FRAME_STRIDE);
+    for (i, fields) in frame.chunks(FRAME_STRIDE).enumerate() {
+        assert_eq!(Vec3::from_slice(&fields[0..3]), particles.position[i]);
+        assert_eq!(Vec3::from_slice(&fields[3..6]), particles.velocity[i]);
+        assert_eq!(fields[6], particles.radius[i]);
+    }
+    assert_eq!(frame[3 * FRAME_STRIDE + 7], Phase::Rigid as u8 as f32);
+    assert_eq!(frame[3 * FRAME_STRIDE + 8], -2.0);
 }
 
 #[test]
@@ -59,31 +142,95 @@ fn test_config_default_values() {
     assert_eq!(config.substeps, 4);
     assert_eq!(config.solver_iterations, 3);
     assert_eq!(config.gravity, Vec3::new(0.0, -9.81, 0.0));
+    assert!(config.artistic_forces_enabled);
+    assert!(!config.gravity_follows_pointer);
+    assert_eq!(config.pointer_release_damping, 0.0);
+    assert_eq!(config.pointer_release_duration, 0.5);
+    assert_eq!(config.flow_gravity, 0.04);
+    assert_eq!(config.audio_speed_coupling, 0.0);
     assert_eq!(config.global_damping, 0.99);
+    assert_eq!(config.linear_damping_min, 0.93);
+    assert_eq!(config.linear_damping_max, 0.965);
+    assert_eq!(config.quadratic_drag, 0.018);
+    assert!(config.quadratic_drag_enabled);
+    assert_eq!(config.phase_drag, [1.0; 7]);
     assert_eq!(config.max_velocity, 18.0);
+    assert_eq!(config.settle_velocity_threshold, 0.05);
     assert_eq!(config.boundary_radius, 4.5);
+    assert_eq!(config.boundary_shape, BoundaryShape::Sphere);
     assert_eq!(config.shape_strength, 0.85);
+    assert_eq!(config.shape_close_distance, 0.5);
+    assert_eq!(config.shape_snap_distance, 0.15);
+    assert_eq!(config.flow_vortex_strength, 0.35);
+    assert_eq!(config.flow_z_wave_amplitude, 0.35);
+    assert_eq!(config.flow_z_wave_frequency, 1.0);
+    assert_eq!(config.shape_attract_phases, [true; 7]);
     assert_eq!(config.collisions_enabled, false);
+    assert_eq!(config.path_blend_frames, 0);
     assert_eq!(config.fluid_rest_density, 1000.0);
+    assert_eq!(config.fluid_inner_iterations, 1);
+    assert_eq!(config.max_fluid_neighbors, 0);
+    assert!(!config.free_contributes_to_density);
     assert_eq!(config.fluid_viscosity, 0.01);
+    assert_eq!(config.fluid_surface_tension, 0.0);
+    assert_eq!(config.fluid_interface_tension, 0.0);
     assert_eq!(config.fluid_vorticity, 0.1);
+    assert_eq!(config.fluid_vorticity_clamp, 50.0);
+    assert_eq!(config.fluid_restitution, 0.0);
     assert_eq!(config.smoothing_radius, 0.1);
     assert_eq!(config.tensile_correction, true);
     assert_eq!(config.cloth_stiffness, 0.001);
     assert_eq!(config.cloth_bending, 0.01);
     assert_eq!(config.friction, 0.3);
+    assert_eq!(config.granular_friction, 0.9);
+    assert_eq!(config.granular_static_velocity, 0.05);
     assert_eq!(config.restitution, 0.2);
+    assert_eq!(config.phase_restitution, [1.0; 7]);
+    assert!(!config.contact_restitution);
+    assert_eq!(config.material_combine, MaterialCombine::Average);
+    assert!(!config.mass_from_radius);
+    assert_eq!(config.mass_radius_density, 1000.0);
     assert_eq!(config.shape_matching_stiffness, 0.9);
+    assert_eq!(config.max_contacts_per_particle, 32);
+    assert_eq!(config.speculative_margin, 0.0);
+    assert_eq!(config.contact_relaxation, 1.0);
+    assert_eq!(config.max_penetration_correction, 0.0);
+    assert_eq!(config.convergence_epsilon, 0.0);
+    assert!(!config.capsule_contacts);
+    assert_eq!(config.melt_temperature, 1.0);
+    assert_eq!(config.freeze_temperature, -1.0);
+    assert!(!config.freeze_to_static);
     assert_eq!(config.grid_cell_size, 0.0);
+    assert!(config.grid_rebuild_every_substep);
     assert_eq!(config.grid_table_size, 131072);
+    assert!(!config.shape_as_constraint);
     assert_eq!(config.shape_compliance_at_zero, 100.0);
     assert_eq!(config.shape_compliance_at_one, 0.0001);
-    assert_eq!(config.boundary_stiffness, 100.0);
+    assert_eq!(config.boundary_stiffness, f32::INFINITY);
+    assert_eq!(config.boundary_return_stiffness, 0.6);
+    assert!(!config.euler_boundary_clamp);
+    assert_eq!(config.max_particle_displacement, 0.0);
     assert_eq!(config.nbody_enabled, false);
     assert_eq!(config.nbody_g, 0.001);
     assert_eq!(config.nbody_softening, 0.01);
+    assert!(!config.adaptive_softening);
+    assert_eq!(config.adaptive_softening_k, 0.05);
+    assert!(!config.central_attractor_enabled);
+    assert_eq!(config.central_attractor_pos, Vec3::ZERO);
+    assert_eq!(config.central_attractor_mass, 1000.0);
+    assert!(!config.beam_enabled);
+    assert_eq!(config.beam_start, Vec3::new(-1.0, 0.0, 0.0));
+    assert_eq!(config.beam_end, Vec3::new(1.0, 0.0, 0.0));
+    assert_eq!(config.beam_strength, 4.0);
+    assert_eq!(config.pointer_min_distance, 0.05);
     assert_eq!(config.nbody_theta, 0.7);
     assert_eq!(config.em_enabled, false);
     assert_eq!(config.em_coulomb_k, 1.0);
     assert_eq!(config.em_magnetic_field, Vec3::ZERO);
+    assert_eq!(config.init_spiral_turns, 20.0);
+    assert_eq!(config.init_spiral_radius, [0.5, 2.0]);
+    assert_eq!(config.radius_min, 0.05);
+    assert_eq!(config.radius_max, 0.1);
+    assert_eq!(config.spawn_flow_inheritance, 0.0);
+    assert_eq!(config.spawn_lifetime, 0.0);
 }
@@ -19,6 +19,7 @@ fn make_params(mode: u32) -> PointerParams {
         pressing: true,
         pulse: false,
         view_dir: Vec3::NEG_Z,
+        ..PointerParams::default()
     }
 }
 
@@ -113,6 +114,41 @@ fn test_repel_pushes_away_from_pointer() {
     );
 }
 
+#[test]
+fn test_repel_swirl_and_damping_configurable() {
+    let gentle = make_params(1);
+    let punchy = PointerParams {
+        repel_swirl: 6.0,
+        repel_damping: 0.9,
+        ..make_params(1)
+    };
+    // Particle in the XY plane so the swirl term is perpendicular to dir_p
+    let pos = Vec3::new(0.6, 0.8, 0.0);
+    let dir_p = -pos.normalize();
+    let r_gentle = compute_pointer_force(pos, Vec3::ZERO, 0.5, 0.0, &gentle);
+    let r_punchy = compute_pointer_force(pos, Vec3::ZERO, 0.5, 0.0, &punchy);
+
+    let radial_gentle = r_gentle.acc.dot(dir_p);
+    let radial_punchy = r_punchy.acc.dot(dir_p);
+    assert!(
+        (radial_gentle - radial_punchy).abs() < 1e-5,
+        "radial push should not depend on repel_swirl: {} vs {}",
+        radial_gentle,
+        radial_punchy,
+    );
+
+    let tangential_gentle = mag(r_gentle.acc - dir_p * radial_gentle);
+    let tangential_punchy = mag(r_punchy.acc - dir_p * radial_punchy);
+    assert!(
+        tangential_punchy > tangential_gentle * 2.0,
+        "higher repel_swirl should increase tangential acc: {} vs {}",
+        tangential_gentle,
+        tangential_punchy,
+    );
+    assert!((r_gentle.vel_scale - 0.97).abs() < f32::EPSILON);
+    assert!((r_punchy.vel_scale - 0.9).abs() < f32::EPSILON);
+}
+
 // ---------------------------------------------------------------------------
 // 4. Vortex left (mode 2) has tangential force
 // ---------------------------------------------------------------------------
@@ -1,6 +1,6 @@
 use xpbd_core::shapes::primitives::*;
-use xpbd_core::shapes::fractal::fractal_flow;
-use xpbd_core::shapes::dispatcher::target_for;
+use xpbd_core::shapes::fractal::{fractal_flow, fractal_flow_with_iter, DEFAULT_FRACTAL_ITERATIONS};
+use xpbd_core::shapes::dispatcher::{target_for, TargetParams};
 
 #[test]
 fn test_all_primitive_shapes_bounded() {
@@ -40,6 +40,26 @@ fn test_sphere_radius() {
     }
 }
 
+#[test]
+fn test_blob_shape_radius() {
+    let mut min_r = f32::MAX;
+    let mut max_r = 0.0_f32;
+    for i in 0..200 {
+        let t = i as f32 / 200.0;
+        let s = ((i * 7) % 200) as f32 / 200.0;
+        let smooth = shape_blob(t, s, 1.3, 0.0);
+        assert!((smooth - shape_sphere(t, s)).length() < 1e-6, "roughness 0 must be the sphere");
+        assert!((smooth.length() - 0.7).abs() < 0.02);
+
+        let r = shape_blob(t, s, 1.3, 0.6).length();
+        // fbm stays in [0, 1), so the radius is within [0.7, 0.7 * (1 + roughness)]
+        assert!((0.7 - 1e-4..=0.7 * 1.6 + 1e-4).contains(&r), "blob radius {} out of bounds", r);
+        min_r = min_r.min(r);
+        max_r = max_r.max(r);
+    }
+    assert!(max_r - min_r > 0.05, "roughness should vary the radius: {}..{}", min_r, max_r);
+}
+
 #[test]
 fn test_fractal_no_nan() {
     for ftype in 0..10 {
@@ -72,8 +92,8 @@ fn test_fractal_bounded() {
 fn test_dispatcher_all_shapes() {
     let rot = glam::Mat3::IDENTITY;
     let seed = [0.5, 0.1, -0.1, 0.3];
-    for sid in 0..=12 {
-        let p = target_for(sid, 0.5, 0.5, 1.0, &rot, &seed, 0.0, 0.0, 0.0);
+    for sid in 0..=13 {
+        let p = target_for(sid, 0.5, 0.5, 1.0, &rot, &seed, &TargetParams::default());
         assert!(!p.x.is_nan() && !p.y.is_nan() && !p.z.is_nan(),
             "dispatcher shape {} produced NaN: {:?}", sid, p);
         assert!(p.length() < 5.0,
@@ -83,7 +103,7 @@ fn test_dispatcher_all_shapes() {
 
 #[test]
 fn test_equalizer_shape() {
-    let p = shape_equalizer(0.5, 0.5, 0.5, 0.3, 0.2, 1.0);
+    let p = shape_equalizer(0.5, 0.5, 0.5, 0.3, 0.2, 1.0, DEFAULT_EQ_ZONES);
     assert!(!p.x.is_nan() && !p.y.is_nan() && !p.z.is_nan(),
         "equalizer produced NaN: {:?}", p);
     // Equalizer x range is about [-1.5, 1.5], y is about [-0.9, 2.0]
@@ -118,8 +138,8 @@ fn test_polygon_on_unit_circle() {
 #[test]
 fn test_equalizer_no_audio_demo_mode() {
     // With zero audio, the equalizer should use demo wave animation
-    let p1 = shape_equalizer(0.5, 0.5, 0.0, 0.0, 0.0, 1.0);
-    let p2 = shape_equalizer(0.5, 0.5, 0.0, 0.0, 0.0, 2.0);
+    let p1 = shape_equalizer(0.5, 0.5, 0.0, 0.0, 0.0, 1.0, DEFAULT_EQ_ZONES);
+    let p2 = shape_equalizer(0.5, 0.5, 0.0, 0.0, 0.0, 2.0, DEFAULT_EQ_ZONES);
     assert!(p1.is_finite() && p2.is_finite());
     // Demo animation should produce different positions at different times
     assert!((p1 - p2).length() > 1e-4, "Demo animation should vary with time");
@@ -130,20 +150,42 @@ fn test_dispatcher_unknown_sid_fallback() {
     use glam::Mat3;
     let rot = Mat3::IDENTITY;
     let seed = [0.5, 0.0, 0.0, 0.0];
-    let p_99 = target_for(99, 0.5, 0.5, 1.0, &rot, &seed, 0.0, 0.0, 0.0);
-    let p_10 = target_for(10, 0.5, 0.5, 1.0, &rot, &seed, 0.0, 0.0, 0.0);
-    // sid>12 fallback should produce same result as sid=10
+    let p_99 = target_for(99, 0.5, 0.5, 1.0, &rot, &seed, &TargetParams::default());
+    let p_10 = target_for(10, 0.5, 0.5, 1.0, &rot, &seed, &TargetParams::default());
+    // sid>13 fallback should produce same result as sid=10
     assert!((p_99 - p_10).length() < 1e-6,
         "sid=99 fallback should match sid=10: {:?} vs {:?}", p_99, p_10);
 }
 
+#[test]
+fn test_shape_thickness_scales_lifted_shape_depth() {
+    let rot = glam::Mat3::IDENTITY;
+    let seed = [0.5, 0.0, 0.0, 0.0];
+    let z = |sid: u32, id_x: f32, id_y: f32, thickness: f32| {
+        let params = TargetParams { thickness, ..TargetParams::default() };
+        target_for(sid, id_x, id_y, 1.0, &rot, &seed, &params).z
+    };
+    for sid in [5, 6, 10] {
+        let mut spread = 0.0_f32;
+        for k in 0..50 {
+            let (id_x, id_y) = ((k % 7) as f32 / 7.0, k as f32 / 50.0);
+            assert_eq!(z(sid, id_x, id_y, 0.0), 0.0, "shape {sid} should be planar at thickness 0");
+            let base = z(sid, id_x, id_y, 1.0);
+            assert!((z(sid, id_x, id_y, 2.5) - base * 2.5).abs() < 1e-5, "shape {sid} depth not proportional");
+            spread = spread.max(base.abs());
+        }
+        assert!(spread > 0.01, "shape {sid} should have depth at thickness 1");
+    }
+}
+
 #[test]
 fn test_dispatcher_equalizer_with_audio() {
     use glam::Mat3;
     let rot = Mat3::IDENTITY;
     let seed = [0.5, 0.0, 0.0, 0.0];
-    let p_silent = target_for(12, 0.5, 0.5, 1.0, &rot, &seed, 0.0, 0.0, 0.0);
-    let p_audio = target_for(12, 0.5, 0.5, 1.0, &rot, &seed, 0.8, 0.5, 0.3);
+    let p_silent = target_for(12, 0.5, 0.5, 1.0, &rot, &seed, &TargetParams::default());
+    let audio = TargetParams { audio_bass: 0.8, audio_mid: 0.5, audio_treble: 0.3, ..TargetParams::default() };
+    let p_audio = target_for(12, 0.5, 0.5, 1.0, &rot, &seed, &audio);
     // With audio, equalizer output should differ
     assert!((p_silent - p_audio).length() > 0.01,
         "Equalizer should respond to audio: {:?} vs {:?}", p_silent, p_audio);
@@ -177,3 +219,47 @@ fn test_fractal_types_produce_different_output() {
     assert!(different_count >= 3,
         "At least 3 fractal types should produce different output, got {}", different_count);
 }
+
+#[test]
+fn test_equalizer_zones_move_bass_response() {
+    // Top-of-bar growth when bass rises, for bar `bar` of 16
+    let bass_growth = |bar: f32, zones: [f32; 2]| {
+        let t = (bar + 0.5) / 16.0;
+        let quiet = shape_equalizer(t, 1.0, 0.05, 0.0, 0.0, 0.0, zones);
+        let loud = shape_equalizer(t, 1.0, 0.8, 0.0, 0.0, 0.0, zones);
+        loud.y - quiet.y
+    };
+
+    let shifted = [0.8, 0.9];
+    assert!(bass_growth(0.0, DEFAULT_EQ_ZONES) > 0.5, "low bars respond to bass");
+    assert!(bass_growth(0.0, shifted) > 0.5);
+
+    // Bar 9 sits past the default bass zone but inside the shifted one
+    assert!(bass_growth(9.0, DEFAULT_EQ_ZONES).abs() < 1e-4);
+    assert!(bass_growth(9.0, shifted) > 0.1, "shifted split should extend bass to bar 9");
+
+    // The top bar stays treble-only either way
+    assert!(bass_growth(15.0, DEFAULT_EQ_ZONES).abs() < 1e-4);
+    assert!(bass_growth(15.0, shifted).abs() < 1e-4);
+}
+
+#[test]
+fn test_fractal_iterations_refine_only_the_boundary() {
+    // Mandelbrot with a neutral seed maps id (u, 0.5) to c = (2u - 1.5, 0)
+    let seed = [0.0, 0.0, 0.0, 0.0];
+    let at = |u: f32, iters: u32| fractal_flow_with_iter(u, 0.5, 1.0, seed, iters);
+
+    // Matches fractal_flow at the default cap
+    assert_eq!(at(0.3, DEFAULT_FRACTAL_ITERATIONS), fractal_flow(0.3, 0.5, 1.0, seed));
+
+    // c = 0.253 escapes after ~57 iterations: interior at 48, banded at 200
+    let boundary = 0.8765;
+    assert!((at(boundary, 48) - at(boundary, 200)).length() > 0.01);
+
+    // Deep interior (c = 0) and far exterior (c = -1.5 - i) don't change
+    for &(u, v) in &[(0.75, 0.5), (0.0, 0.0)] {
+        let low = fractal_flow_with_iter(u, v, 1.0, seed, 48);
+        let high = fractal_flow_with_iter(u, v, 1.0, seed, 200);
+        assert!((low - high).length() < 1e-5, "id ({}, {}) moved: {:?} vs {:?}", u, v, low, high);
+    }
+}
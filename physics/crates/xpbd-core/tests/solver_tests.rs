@@ -1,7 +1,15 @@
 use glam::Vec3;
+use xpbd_core::config::{
+    BoundaryShape, MAX_SOLVER_ITERATIONS, MAX_SUBSTEPS, MIN_PERIODIC_HALF_EXTENT,
+};
+use xpbd_core::constraints::contact::detect_contacts;
+use xpbd_core::constraints::distance::DistanceConstraint;
 use xpbd_core::forces::pointer::PointerParams;
+use xpbd_core::grid::SpatialHashGrid;
+use xpbd_core::hull::convex_hull;
 use xpbd_core::particle::Phase;
-use xpbd_core::solver::Solver;
+use xpbd_core::shapes::dispatcher::CUSTOM_SHAPE;
+use xpbd_core::solver::{IntegrationPath, Solver};
 
 #[test]
 fn test_particles_move_toward_sphere() {
@@ -26,6 +34,52 @@ fn test_particles_move_toward_sphere() {
     );
 }
 
+#[test]
+fn test_particles_converge_to_custom_point_cloud() {
+    let mut solver = Solver::new(100);
+    // A flat 10x10 "logo" grid, well away from the initial spiral
+    let points: Vec<Vec3> = (0..100)
+        .map(|i| Vec3::new((i % 10) as f32 * 0.15 - 0.7, (i / 10) as f32 * 0.15 - 0.7, 0.5))
+        .collect();
+    solver.set_custom_shape_targets(&points);
+    solver.shape_params.shape_a = CUSTOM_SHAPE;
+    solver.shape_params.shape_b = CUSTOM_SHAPE;
+    solver.config.shape_strength = 1.0;
+
+    for step in 0..300 {
+        solver.step(0.016, step as f32 * 0.016);
+    }
+
+    assert_eq!(solver.particles.target_pos[37], points[37]);
+    let near: Vec<f32> = (0..100)
+        .filter(|&i| solver.particles.hash[i] > 0.5)
+        .map(|i| (solver.particles.position[i] - points[i]).length())
+        .collect();
+    let avg = near.iter().sum::<f32>() / near.len() as f32;
+    assert!(avg < 0.05, "strongly attracted particles should sit near their points, avg {avg}");
+}
+
+#[test]
+fn test_shape_spin_rotates_targets_each_step() {
+    let mut solver = Solver::new(1);
+    solver.set_custom_shape_targets(&[Vec3::X]);
+    solver.shape_params.shape_a = CUSTOM_SHAPE;
+    solver.shape_params.shape_b = CUSTOM_SHAPE;
+    solver.shape_params.rot_a_velocity = Vec3::Y * 0.5;
+    solver.shape_params.rot_b_velocity = Vec3::Y * 0.5;
+
+    let mut previous = Vec3::X;
+    for step in 1..=20 {
+        solver.step(0.1, step as f32 * 0.1);
+        let target = solver.particles.target_pos[0];
+        let angle = 0.05 * step as f32;
+        let expected = Vec3::new(angle.cos(), 0.0, -angle.sin());
+        assert!((target - expected).length() < 1e-4, "step {step}: {target:?} vs {expected:?}");
+        assert!(target != previous, "target did not move at step {step}");
+        previous = target;
+    }
+}
+
 #[test]
 fn test_boundary_containment() {
     let mut solver = Solver::new(100);
@@ -42,6 +96,152 @@ fn test_boundary_containment() {
     }
 }
 
+#[test]
+fn test_euler_boundary_clamp_keeps_particles_inside() {
+    let mut solver = Solver::new(100);
+    solver.config.shape_strength = 0.0;
+    solver.config.boundary_radius = 4.5;
+    solver.config.euler_boundary_clamp = true;
+    solver.particles.velocity[0] = Vec3::new(18.0, 0.0, 0.0);
+
+    for step in 0..500 {
+        solver.step(0.016, step as f32 * 0.016);
+        for (i, p) in solver.particles.position.iter().enumerate() {
+            assert!(p.length() <= 4.5 + 1e-5, "particle {} escaped at step {}: {}", i, step, p.length());
+        }
+    }
+}
+
+#[test]
+fn test_max_particle_displacement_clamps_runaway_particle() {
+    let mut solver = Solver::new(20);
+    solver.config.shape_strength = 0.0;
+    solver.config.max_particle_displacement = 0.1;
+    let mut reference = Solver::new(20);
+    reference.config.shape_strength = 0.0;
+    solver.particles.velocity[0] = Vec3::new(500.0, 0.0, 0.0);
+
+    let start = solver.particles.position.clone();
+    solver.step(0.016, 0.0);
+    reference.step(0.016, 0.0);
+
+    let moved = (solver.particles.position[0] - start[0]).length();
+    assert!((moved - 0.1).abs() < 1e-4, "runaway particle moved {moved}");
+    assert!(solver.particles.velocity[0].length() < 0.1 / 0.016 + 1e-3);
+    for i in 1..solver.particles.count {
+        assert_eq!(solver.particles.position[i], reference.particles.position[i], "particle {i}");
+        assert_eq!(solver.particles.velocity[i], reference.particles.velocity[i], "particle {i}");
+    }
+}
+
+#[test]
+fn test_particle_squeezed_between_static_and_wall_settles() {
+    // The static particle leaves less room than a diameter before the wall
+    let mut solver = Solver::new(2);
+    solver.config.collisions_enabled = true;
+    solver.config.artistic_forces_enabled = false;
+    solver.config.boundary_radius = 1.0;
+    solver.config.gravity = Vec3::new(5.0, 0.0, 0.0);
+    solver.particles.radius[0] = 0.1;
+    solver.particles.radius[1] = 0.1;
+    solver.particles.position[0] = Vec3::new(0.85, 0.0, 0.0);
+    solver.particles.inv_mass[0] = 0.0;
+    solver.particles.phase[0] = Phase::Static;
+    solver.particles.position[1] = Vec3::new(0.97, 0.0, 0.0);
+    solver.particles.velocity[0] = Vec3::ZERO;
+    solver.particles.velocity[1] = Vec3::ZERO;
+
+    let (mut lo, mut hi, mut max_speed) = (f32::MAX, f32::MIN, 0.0_f32);
+    for step in 0..300 {
+        solver.step(1.0 / 60.0, step as f32 / 60.0);
+        if step >= 200 {
+            let x = solver.particles.position[1].x;
+            lo = lo.min(x);
+            hi = hi.max(x);
+            max_speed = max_speed.max(solver.particles.velocity[1].length());
+        }
+    }
+    assert!(hi - lo < 1e-4, "particle kept oscillating between {lo} and {hi}");
+    assert!(max_speed < 1e-2, "trapped particle should be at rest, speed {max_speed}");
+    assert!((hi - 1.0).abs() < 0.01, "particle should rest against the wall, x = {hi}");
+}
+
+#[test]
+fn test_collision_pass_is_callable_per_iteration() {
+    let mut solver = Solver::new(2);
+    solver.particles.radius[..2].copy_from_slice(&[0.1, 0.1]);
+    for (i, x) in [-0.05, 0.05].into_iter().enumerate() {
+        solver.particles.position[i] = Vec3::new(x, 0.0, 0.0);
+        solver.particles.predicted[i] = Vec3::new(x, 0.0, 0.0);
+    }
+
+    // Iteration 0 detects the overlap and pushes the pair apart
+    solver.solve_collision_constraints(0, 1.0 / 240.0);
+    assert_eq!(solver.last_stats.contact_count, 1);
+    assert!(solver.particles.corrections[0].x < 0.0 && solver.particles.corrections[1].x > 0.0);
+    assert_eq!(solver.particles.correction_counts[..2], [1, 1]);
+
+    // Later iterations reuse the contact even once the pair has separated
+    solver.particles.predicted[1].x = 0.5;
+    solver.solve_collision_constraints(1, 1.0 / 240.0);
+    assert_eq!(solver.particles.correction_counts[..2], [2, 2]);
+}
+
+/// Launch one particle outward from the boundary and return its peak distance.
+fn peak_distance_after_launch(return_stiffness: f32) -> f32 {
+    let mut solver = Solver::new(1);
+    solver.config.shape_strength = 0.0;
+    solver.config.boundary_radius = 2.0;
+    solver.config.boundary_return_stiffness = return_stiffness;
+    solver.particles.position[0] = Vec3::new(2.0, 0.0, 0.0);
+    solver.particles.velocity[0] = Vec3::new(15.0, 0.0, 0.0);
+
+    let mut peak = 0.0_f32;
+    for step in 0..200 {
+        solver.step(0.016, step as f32 * 0.016);
+        peak = peak.max(solver.particles.position[0].length());
+    }
+    peak
+}
+
+#[test]
+fn test_boundary_return_stiffness_limits_overshoot() {
+    let default_peak = peak_distance_after_launch(0.6);
+    let stiff_peak = peak_distance_after_launch(6.0);
+    assert!(
+        stiff_peak < default_peak,
+        "stiffer return should limit overshoot: stiff={} default={}",
+        stiff_peak,
+        default_peak
+    );
+    assert!(default_peak > 2.0, "fast particle should overshoot the boundary");
+}
+
+/// Distance from the center after one XPBD step of a particle placed 0.5
+/// outside the boundary sphere.
+fn distance_after_boundary_step(boundary_stiffness: f32) -> f32 {
+    let mut solver = Solver::new(1);
+    solver.config.collisions_enabled = true;
+    solver.config.artistic_forces_enabled = false;
+    solver.config.gravity = Vec3::ZERO;
+    solver.config.boundary_radius = 2.0;
+    solver.config.boundary_stiffness = boundary_stiffness;
+    solver.particles.position[0] = Vec3::new(2.5, 0.0, 0.0);
+    solver.particles.velocity[0] = Vec3::ZERO;
+    solver.step(0.016, 0.0);
+    solver.particles.position[0].length()
+}
+
+#[test]
+fn test_boundary_stiffness_softens_wall_projection() {
+    let rigid = distance_after_boundary_step(f32::INFINITY);
+    let soft = distance_after_boundary_step(0.05);
+    let softer = distance_after_boundary_step(0.01);
+    assert!(rigid <= 2.0, "default wall should bring the particle inside: {}", rigid);
+    assert!(soft > 2.0, "soft wall should leave part of the overlap: {}", soft);
+    assert!(softer > soft, "softer wall should leave more: softer={} soft={}", softer, soft);
+}
+
 #[test]
 fn test_velocity_cap() {
     let mut solver = Solver::new(10);
@@ -94,6 +294,7 @@ fn test_attract_moves_toward_pointer() {
         pressing: true,
         pulse: false,
         view_dir: Vec3::NEG_Z,
+        ..PointerParams::default()
     };
     solver.particles.position[0] = Vec3::ZERO;
     solver.particles.velocity[0] = Vec3::ZERO;
@@ -122,6 +323,7 @@ fn test_repel_pushes_away() {
         pressing: true,
         pulse: false,
         view_dir: Vec3::NEG_Z,
+        ..PointerParams::default()
     };
     solver.particles.position[0] = Vec3::new(1.0, 0.0, 0.0);
     solver.particles.velocity[0] = Vec3::ZERO;
@@ -197,6 +399,45 @@ fn test_fluid_particles_get_density_corrections() {
     assert!(any_moved, "Fluid particles should have moved due to density corrections");
 }
 
+#[test]
+fn test_shape_attract_phase_mask() {
+    let mut solver = Solver::new(200);
+    solver.config.shape_strength = 1.0;
+    solver.config.shape_attract_phases = [false; 7];
+    solver.config.shape_attract_phases[Phase::Free as usize] = true;
+    for i in (1..200).step_by(2) {
+        solver.particles.phase[i] = Phase::Fluid;
+    }
+
+    for step in 0..300 {
+        solver.step(0.016, step as f32 * 0.016);
+    }
+
+    // Mean distance to target among attracted (high-affinity hash) particles
+    let mean_dist = |phase: Phase| {
+        let (mut sum, mut n) = (0.0_f32, 0);
+        for i in 0..solver.particles.count {
+            if solver.particles.phase[i] == phase && solver.particles.hash[i] > 0.5 {
+                sum += (solver.particles.position[i] - solver.particles.target_pos[i]).length();
+                n += 1;
+            }
+        }
+        sum / n as f32
+    };
+    let free_dist = mean_dist(Phase::Free);
+    let fluid_dist = mean_dist(Phase::Fluid);
+    assert!(free_dist < 0.3, "free particles should form the shape: {}", free_dist);
+    assert!(
+        fluid_dist > free_dist * 3.0,
+        "masked fluid should not converge: fluid={}, free={}",
+        fluid_dist,
+        free_dist
+    );
+    for i in (1..200).step_by(2) {
+        assert_eq!(solver.particles.target_weight[i], 0.0);
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Solver integration paths
 // ---------------------------------------------------------------------------
@@ -451,115 +692,1863 @@ fn test_create_cloth_overflow_guard() {
 }
 
 #[test]
-fn test_create_rigid_body_overflow_guard() {
-    let mut solver = Solver::new(10);
-    solver.create_rigid_body(8, 5, 0.9); // 8+5=13 > 10
-    assert!(solver.shape_match_groups.is_empty(),
-        "Overflow should prevent rigid body creation");
-    for i in 0..10 {
-        assert_eq!(solver.particles.phase[i], Phase::Free);
+fn test_solver_resize_grows_and_drops_removed_constraints() {
+    let mut solver = Solver::new(20);
+    solver.config.collisions_enabled = true;
+    solver.create_rigid_body(14, 4, 1.0);
+    solver.create_cloth(0, 2, 2, 0.1, 0.0, 0.0);
+    let kept = solver.particles.position[3];
+
+    solver.resize(16);
+    assert_eq!(solver.particles.count, 16);
+    assert_eq!(solver.particles.position[3], kept);
+    assert_eq!(solver.shape_match_groups.len(), 1);
+    assert_eq!(solver.shape_match_groups[0].particle_indices, vec![14, 15]);
+    solver.step(0.016, 0.0);
+
+    solver.resize(40);
+    assert_eq!(solver.particles.count, 40);
+    assert!(solver.particles.hash[30] > 0.0, "new slots are seeded like Solver::new");
+    for step in 0..5 {
+        solver.step(0.016, step as f32 * 0.016);
     }
+    assert!(solver.particles.position.iter().all(|p| p.is_finite()));
 }
 
-#[test]
-fn test_speed_multiplier_affects_motion() {
-    // Normal speed
-    let mut solver1 = Solver::new(5);
-    solver1.config.shape_strength = 0.0;
-    solver1.particles.position[0] = Vec3::ZERO;
-    solver1.particles.velocity[0] = Vec3::new(1.0, 0.0, 0.0);
-    solver1.shape_params.speed_multiplier = 1.0;
-    solver1.step(0.016, 0.0);
-    let pos1 = solver1.particles.position[0];
+/// Rigid 3x3x2 block whose particles are rotated by `angle` about +Z (about the
+/// block center) after the body is created, then solved for one step.
+fn rotated_rigid_block(angle: f32) -> Solver {
+    let mut solver = Solver::new(18);
+    solver.config.collisions_enabled = true;
+    solver.config.shape_strength = 0.0;
+    let center = Vec3::new(0.5, 0.5, 0.0);
+    for i in 0..18 {
+        let cell = Vec3::new((i % 3) as f32 - 1.0, ((i / 3) % 3) as f32 - 1.0, (i / 9) as f32 - 0.5);
+        solver.particles.position[i] = center + cell * 0.2;
+        solver.particles.velocity[i] = Vec3::ZERO;
+        solver.particles.radius[i] = 0.02;
+    }
+    solver.create_rigid_body(0, 18, 1.0);
+    let rot = glam::Quat::from_rotation_z(angle);
+    for i in 0..18 {
+        solver.particles.position[i] = center + rot * (solver.particles.position[i] - center);
+    }
+    solver.step(0.016, 0.0);
+    solver
+}
 
-    // Double speed
-    let mut solver2 = Solver::new(5);
-    solver2.config.shape_strength = 0.0;
-    solver2.particles.position[0] = Vec3::ZERO;
-    solver2.particles.velocity[0] = Vec3::new(1.0, 0.0, 0.0);
-    solver2.shape_params.speed_multiplier = 2.0;
-    solver2.step(0.016, 0.0);
-    let pos2 = solver2.particles.position[0];
+#[test]
+fn test_rigid_body_rotation_reports_quaternion() {
+    let still = rotated_rigid_block(0.0);
+    let q = still.rigid_body_rotation(0);
+    assert!(glam::Quat::from_array(q).angle_between(glam::Quat::IDENTITY) < 0.01, "unrotated: {q:?}");
+
+    let turned = rotated_rigid_block(std::f32::consts::FRAC_PI_2);
+    let q = turned.rigid_body_rotation(0);
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    let expected = [0.0, 0.0, half, half];
+    for (got, want) in q.iter().zip(expected) {
+        assert!((got - want).abs() < 0.01, "90 degrees about Z: {q:?}");
+    }
 
-    // Double speed should move further in X
-    assert!(pos2.x > pos1.x * 1.3,
-        "2x speed should move further: pos1.x={}, pos2.x={}", pos1.x, pos2.x);
+    let (com, q_t) = turned.rigid_body_transform(0).unwrap();
+    assert!(com.distance(Vec3::new(0.5, 0.5, 0.0)) < 0.05, "com {com:?}");
+    assert_eq!(q_t, q);
+    assert_eq!(turned.rigid_body_rotation(3), [0.0, 0.0, 0.0, 1.0]);
+    assert!(turned.rigid_body_transform(3).is_none());
 }
 
 #[test]
-fn test_morph_blending_differs() {
-    let mut solver_a = Solver::new(10);
-    solver_a.shape_params.shape_a = 0; // cube
-    solver_a.shape_params.shape_b = 1; // sphere
-    solver_a.shape_params.morph = 0.0;
-    solver_a.config.shape_strength = 0.9;
-    solver_a.step(0.016, 1.0);
-    let targets_a: Vec<Vec3> = solver_a.particles.target_pos[..10].to_vec();
+fn test_rigid_body_hull_of_cube_and_degenerate_inputs() {
+    let mut solver = Solver::new(8);
+    for i in 0..8 {
+        let corner = Vec3::new((i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32);
+        solver.particles.position[i] = corner * 0.4 + Vec3::new(1.0, 0.5, 0.0);
+    }
+    solver.create_rigid_body(0, 8, 1.0);
+    let (vertices, triangles) = solver.rigid_body_hull(0);
+    assert_eq!(vertices.len(), 8);
+    assert_eq!(triangles.len(), 12);
+    let center = vertices.iter().copied().sum::<Vec3>() / 8.0;
+    for t in &triangles {
+        let [a, b, c] = t.map(|i| vertices[i as usize]);
+        let normal = (b - a).cross(c - a);
+        assert!(normal.length() > 1e-4, "degenerate triangle {t:?}");
+        assert!(normal.dot(a - center) > 0.0, "triangle {t:?} faces inward");
+    }
+    assert_eq!(solver.rigid_body_hull(5), (Vec::new(), Vec::new()));
 
-    let mut solver_b = Solver::new(10);
-    solver_b.shape_params.shape_a = 0;
-    solver_b.shape_params.shape_b = 1;
-    solver_b.shape_params.morph = 1.0;
-    solver_b.config.shape_strength = 0.9;
-    solver_b.step(0.016, 1.0);
-    let targets_b: Vec<Vec3> = solver_b.particles.target_pos[..10].to_vec();
+    // Coplanar, collinear and coincident point sets don't panic and give no hull
+    let flat: Vec<Vec3> = (0..9).map(|i| Vec3::new((i % 3) as f32, (i / 3) as f32, 2.0)).collect();
+    let line: Vec<Vec3> = (0..5).map(|i| Vec3::splat(i as f32)).collect();
+    for points in [flat, line, vec![Vec3::ONE; 4], Vec::new()] {
+        assert_eq!(convex_hull(&points).1.len(), 0, "{points:?}");
+    }
 
-    // morph=0 vs morph=1 should produce different targets
-    let mut diff_count = 0;
-    for i in 0..10 {
-        if (targets_a[i] - targets_b[i]).length() > 0.01 {
-            diff_count += 1;
+    // A lattice with points on its faces and inside: every point lies behind every face
+    let lattice: Vec<Vec3> = (0..27)
+        .map(|i| Vec3::new((i % 3) as f32, ((i / 3) % 3) as f32, (i / 9) as f32) * 0.5)
+        .collect();
+    let (vertices, triangles) = convex_hull(&lattice);
+    assert!(triangles.len() >= 12);
+    for t in &triangles {
+        let [a, b, c] = t.map(|i| vertices[i as usize]);
+        let normal = (b - a).cross(c - a).normalize();
+        for &p in &lattice {
+            assert!((p - a).dot(normal) < 1e-4, "{p:?} outside face {t:?}");
         }
     }
-    assert!(diff_count > 3, "morph=0 vs morph=1 should produce different targets: {} differ", diff_count);
 }
 
 #[test]
-fn test_combined_xpbd_all_constraints() {
-    // Test that all constraint types coexist without NaN or panic
-    let mut solver = Solver::new(50);
+fn test_rigid_torque_spins_body_rigidly() {
+    let mut solver = Solver::new(9);
     solver.config.collisions_enabled = true;
-    solver.config.substeps = 2;
-    solver.config.solver_iterations = 2;
-    solver.config.shape_strength = 0.5;
-    solver.config.nbody_enabled = true;
-    solver.config.nbody_g = 0.001;
-    solver.config.em_enabled = true;
-    solver.config.em_coulomb_k = 0.1;
+    solver.config.shape_strength = 0.0;
+    let center = Vec3::new(1.0, 1.0, 0.5);
+    for i in 0..9 {
+        let cell = Vec3::new((i % 3) as f32 - 1.0, (i / 3) as f32 - 1.0, 0.0);
+        solver.particles.position[i] = center + cell * 0.2;
+        solver.particles.velocity[i] = Vec3::ZERO;
+        solver.particles.radius[i] = 0.02;
+    }
+    solver.create_rigid_body(0, 9, 1.0);
+
+    let omega = Vec3::new(0.0, 0.0, 3.0);
+    solver.apply_rigid_torque(0, Vec3::Z, 3.0);
+    for i in 0..9 {
+        let expected = omega.cross(solver.particles.position[i] - center);
+        let vel = solver.particles.velocity[i];
+        assert!(vel.distance(expected) < 1e-5, "member {i}: {:?} vs {:?}", vel, expected);
+    }
+    solver.apply_rigid_torque(7, Vec3::Z, 3.0); // out of range: ignored
+
+    let rest_dists: Vec<f32> = (1..9)
+        .map(|i| solver.particles.position[i].distance(solver.particles.position[0]))
+        .collect();
+    let corner_angle = |s: &Solver| {
+        let com = s.particles.position.iter().copied().sum::<Vec3>() / 9.0;
+        let r = s.particles.position[8] - com;
+        r.y.atan2(r.x)
+    };
+    let start_angle = corner_angle(&solver);
+    for step in 0..5 {
+        solver.step(0.016, step as f32 * 0.016);
+    }
 
-    // Cloth patch (first 16 particles)
-    solver.create_cloth(0, 4, 4, 0.2, 0.001, 0.01);
+    let turned = corner_angle(&solver) - start_angle;
+    assert!(turned > 0.02, "body should turn counter-clockwise about +Z: {}", turned);
+    for i in 1..9 {
+        let d = solver.particles.position[i].distance(solver.particles.position[0]);
+        assert!((d - rest_dists[i - 1]).abs() < 0.01, "member {i} deformed: {} vs {}", d, rest_dists[i - 1]);
+    }
+}
 
-    // Rigid body (particles 16-19)
-    solver.particles.position[16] = Vec3::new(2.0, 0.0, 0.0);
-    solver.particles.position[17] = Vec3::new(2.5, 0.0, 0.0);
-    solver.particles.position[18] = Vec3::new(2.5, 0.5, 0.0);
-    solver.particles.position[19] = Vec3::new(2.0, 0.5, 0.0);
-    solver.create_rigid_body(16, 4, 0.9);
+/// Mean displacement of free-flying particles after 60 steps with gravity
+/// following a pointer at `pointer` (or pointing down when `follow` is off).
+fn tilted_drift(follow: bool, pointer: Vec3) -> Vec3 {
+    let mut solver = Solver::new(200);
+    solver.config.gravity_follows_pointer = follow;
+    solver.config.shape_strength = 0.0;
+    solver.pointer_params.position = pointer;
+    let start = solver.particles.position.clone();
+    for step in 0..60 {
+        solver.step(0.016, step as f32 * 0.016);
+    }
+    let moved: Vec3 = solver.particles.position.iter().zip(&start).map(|(p, s)| *p - *s).sum();
+    moved / 200.0
+}
 
-    // Some fluid particles
-    for i in 20..30 {
-        solver.particles.phase[i] = Phase::Fluid;
-        solver.particles.position[i] = Vec3::new(-1.0 + (i - 20) as f32 * 0.05, 0.0, 0.0);
+#[test]
+fn test_gravity_follows_pointer_tilts_drift() {
+    let down = tilted_drift(false, Vec3::new(2.0, 0.0, 0.0));
+    let east = tilted_drift(true, Vec3::new(2.0, 0.0, 0.0));
+    let north = tilted_drift(true, Vec3::new(0.0, 0.0, 2.0));
+
+    // Same flow noise in every run, so differences come from gravity alone
+    assert!(east.x - down.x > 0.002, "pointer at +X should pull toward +X: {:?} vs {:?}", east, down);
+    assert!(east.y > down.y, "tilted gravity no longer pulls down: {:?} vs {:?}", east, down);
+    assert!(north.z - east.z > 0.002, "moving the pointer turns the drift: {:?} vs {:?}", north, east);
+    assert!(east.x > north.x);
+}
+
+#[test]
+fn test_gravity_follows_pointer_turns_physics_gravity() {
+    let mut solver = Solver::new(1);
+    solver.config.artistic_forces_enabled = false;
+    solver.config.gravity_follows_pointer = true;
+    solver.config.boundary_radius = 100.0;
+    solver.pointer_params.position = Vec3::new(3.0, 0.0, 0.0);
+    solver.particles.position[0] = Vec3::ZERO;
+    solver.particles.velocity[0] = Vec3::ZERO;
+
+    solver.step(0.016, 0.0);
+    let vel = solver.particles.velocity[0];
+    assert!(vel.x > 0.05, "gravity should pull toward the pointer: {:?}", vel);
+    assert!(vel.y.abs() < 1e-4 && vel.z.abs() < 1e-4, "no downward pull left: {:?}", vel);
+}
+
+#[test]
+fn test_kill_particles_leaves_compact_active_list() {
+    let mut solver = Solver::new(20);
+    solver.config.collisions_enabled = true;
+    assert_eq!(solver.active_indices(), (0..20).collect::<Vec<u32>>().as_slice());
+
+    let killed: Vec<u32> = (0..20).step_by(2).collect();
+    solver.kill_particles(&killed);
+    solver.kill_particles(&[0, 99]); // already dead / out of range: ignored
+    let survivors: Vec<u32> = (1..20).step_by(2).collect();
+    assert_eq!(solver.active_indices(), survivors.as_slice());
+
+    let dead_before: Vec<Vec3> = killed.iter().map(|&i| solver.particles.position[i as usize]).collect();
+    let live_before = solver.particles.position[1];
+    for step in 0..10 {
+        solver.step(0.016, step as f32 * 0.016);
     }
+    for (&i, &before) in killed.iter().zip(&dead_before) {
+        assert_eq!(solver.particles.position[i as usize], before, "dead particle {i} moved");
+        assert_eq!(solver.particles.acceleration[i as usize], Vec3::ZERO);
+    }
+    assert_ne!(solver.particles.position[1], live_before, "live particles keep simulating");
 
-    // Some charged particles
-    for i in 30..35 {
-        solver.particles.charge[i] = if i % 2 == 0 { 1.0 } else { -1.0 };
+    // New slots from a resize are alive
+    solver.resize(22);
+    assert_eq!(solver.active_indices().len(), survivors.len() + 2);
+    assert_eq!(solver.active_indices().last(), Some(&21));
+}
+
+#[test]
+fn test_user_ids_follow_particles_through_reorder_by_cell() {
+    let mut solver = Solver::new(200);
+    for i in 0..200 {
+        solver.particles.user_id[i] = 1000 + i as u32;
+        solver.particles.velocity[i] = Vec3::new(i as f32, 0.0, 0.0);
+    }
+    solver.distance_constraints.push(DistanceConstraint::new(3, 7, 0.1, 0.0));
+    solver.kill_particles(&[5]);
+    let before: Vec<Vec3> = solver.particles.position.clone();
+
+    solver.reorder_by_cell();
+
+    let moved = (0..200).filter(|&k| solver.particles.user_id[k] != 1000 + k as u32).count();
+    assert!(moved > 0, "spiral layout should not already be in cell order");
+    let mut seen = [false; 200];
+    for k in 0..200 {
+        let orig = (solver.particles.user_id[k] - 1000) as usize;
+        assert!(!seen[orig], "user id {} duplicated", orig + 1000);
+        seen[orig] = true;
+        assert_eq!(solver.particles.position[k], before[orig], "slot {k} position");
+        assert_eq!(solver.particles.alive[k], orig != 5);
+        if orig != 5 {
+            assert_eq!(solver.particles.velocity[k].x, orig as f32, "slot {k} velocity");
+        }
     }
+    let c = &solver.distance_constraints[0];
+    assert_eq!(solver.particles.user_id[c.i as usize], 1003);
+    assert_eq!(solver.particles.user_id[c.j as usize], 1007);
+    assert_eq!(solver.active_indices().len(), 199);
+}
 
-    // Step several times
-    for step in 0..20 {
-        solver.step(1.0 / 60.0, step as f32 / 60.0);
+#[test]
+fn test_resize_never_reuses_user_ids() {
+    let mut solver = Solver::new(10);
+    solver.resize(6);
+    solver.resize(12);
+    let mut ids = solver.particles.user_id.clone();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), 12, "user ids collide: {:?}", solver.particles.user_id);
+    assert_eq!(&solver.particles.user_id[6..], &[10, 11, 12, 13, 14, 15]);
+}
+
+#[test]
+fn test_reorder_by_cell_keeps_shape_targets_and_drops_stale_snapshot() {
+    let mut solver = Solver::new(64);
+    let points: Vec<Vec3> = (0..64).map(|i| Vec3::new(i as f32 * 0.01, 0.0, 0.0)).collect();
+    solver.set_custom_shape_targets(&points);
+    solver.shape_params.shape_a = 0;
+    solver.step(0.016, 0.0);
+    let by_id = |solver: &Solver| {
+        let mut targets: Vec<(u32, Vec3)> = (0..64)
+            .map(|k| (solver.particles.user_id[k], solver.particles.target_pos[k]))
+            .collect();
+        targets.sort_by_key(|&(id, _)| id);
+        targets
+    };
+    let targets_before = by_id(&solver);
+    let prev = solver.snapshot();
+
+    solver.reorder_by_cell();
+    assert!((0..64).any(|k| solver.particles.user_id[k] != k as u32));
+    let mut out = vec![Vec3::ZERO; 64];
+    solver.lerp_output(0.0, &prev, &mut out);
+    assert_eq!(out, solver.particles.position, "stale snapshot must not be blended");
+
+    solver.step(0.016, 0.0);
+    assert_eq!(by_id(&solver), targets_before, "particles kept their shape targets");
+    for k in 0..64 {
+        let own = solver.particles.shape_index[k] as usize;
+        assert_eq!(solver.particles.user_id[k] as usize, own);
+    }
+    solver.shape_params.shape_a = CUSTOM_SHAPE;
+    solver.shape_params.shape_b = CUSTOM_SHAPE;
+    solver.step(0.016, 0.0);
+    for k in 0..64 {
+        let own = solver.particles.user_id[k] as usize;
+        assert_eq!(solver.particles.target_pos[k], points[own], "slot {k}");
     }
+}
 
-    // Verify no NaN
-    for i in 0..50 {
-        let p = solver.particles.position[i];
-        let v = solver.particles.velocity[i];
-        assert!(!p.x.is_nan() && !p.y.is_nan() && !p.z.is_nan(),
-            "NaN position at particle {}", i);
-        assert!(!v.x.is_nan() && !v.y.is_nan() && !v.z.is_nan(),
-            "NaN velocity at particle {}", i);
+#[test]
+fn test_stir_swirls_only_inside_radius() {
+    let mut solver = Solver::new(25);
+    for i in 0..25 {
+        let cell = Vec3::new((i % 5) as f32 - 2.0, (i / 5) as f32 - 2.0, 0.0);
+        solver.particles.position[i] = Vec3::new(1.0, 1.0, 1.0) + cell * 0.1;
+        solver.particles.velocity[i] = Vec3::ZERO;
+    }
+    solver.particles.inv_mass[13] = 0.0; // static particles are never stirred
+
+    let center = Vec3::new(1.0, 1.0, 1.0);
+    let omega = Vec3::new(0.0, 0.0, 4.0);
+    let stirred = solver.stir(center, 0.15, omega);
+    assert_eq!(stirred, 8, "center plus the 8-neighborhood, minus the static one");
+
+    for i in 0..25 {
+        let offset = solver.particles.position[i] - center;
+        let vel = solver.particles.velocity[i];
+        if offset.length() <= 0.15 && i != 13 {
+            let expected = omega.cross(offset);
+            assert!(vel.distance(expected) < 1e-5, "particle {i}: {:?} vs {:?}", vel, expected);
+            assert!(vel.dot(offset).abs() < 1e-5, "stir velocity is tangential");
+        } else {
+            assert_eq!(vel, Vec3::ZERO, "particle {i} outside the stir zone moved");
+        }
+    }
+}
+
+#[test]
+fn test_spawn_inherits_flow_velocity() {
+    let mut solver = Solver::new(4);
+    let pos = Vec3::new(0.7, -0.3, 0.4);
+    let time = 1.25;
+    let given = Vec3::new(0.5, 0.0, 0.0);
+
+    // Without inheritance the particle starts at exactly the given velocity
+    let plain = solver.spawn_particle(pos, given, time);
+    assert_eq!(plain, 4, "no dead slot: the set grows");
+    assert_eq!(solver.particles.count, 5);
+    assert_eq!(solver.particles.velocity[plain], given);
+    assert_eq!(solver.particles.position[plain], pos);
+
+    // With inheritance a still spawn moves along the sampled flow
+    solver.config.spawn_flow_inheritance = 0.5;
+    solver.kill_particles(&[1]);
+    let drifting = solver.spawn_particle(pos, Vec3::ZERO, time);
+    assert_eq!(drifting, 1, "killed slots are reused first");
+    assert!(solver.active_indices().contains(&1));
+    let flow = solver.sample_flow(pos, time);
+    let vel = solver.particles.velocity[drifting];
+    assert!(vel.length() > 1e-3, "inherited velocity should be nonzero");
+    assert!(vel.normalize().dot(flow.normalize()) > 0.999);
+    assert!(vel.distance(flow * 0.5) < 1e-5);
+}
+
+#[test]
+fn test_spawned_particle_expires_after_lifetime() {
+    let mut solver = Solver::new(4);
+    solver.config.spawn_lifetime = 1.0;
+    let i = solver.spawn_particle(Vec3::new(0.2, 0.0, 0.0), Vec3::ZERO, 0.0);
+    assert_eq!(solver.particles.lifetime[i], 1.0);
+    assert_eq!(solver.particles.lifetime[0], 0.0, "initial particles are immortal");
+
+    let dt = 0.1;
+    for step in 0..9 {
+        solver.step(dt, step as f32 * dt);
+    }
+    assert!(solver.particles.alive[i], "alive at 0.9s (age {})", solver.particles.age[i]);
+
+    for step in 9..11 {
+        solver.step(dt, step as f32 * dt);
+    }
+    assert!(!solver.particles.alive[i], "killed by 1.1s (age {})", solver.particles.age[i]);
+    assert!(!solver.active_indices().contains(&(i as u32)));
+    assert!((0..4).all(|k| solver.particles.alive[k]), "immortal particles survive");
+
+    // The freed slot is reused, starting a fresh life
+    solver.config.spawn_lifetime = 0.0;
+    assert_eq!(solver.spawn_particle(Vec3::ZERO, Vec3::ZERO, 1.1), i);
+    assert_eq!(solver.particles.age[i], 0.0);
+    assert_eq!(solver.particles.lifetime[i], 0.0);
+}
+
+#[test]
+fn test_spawn_into_killed_slot_resets_state_and_constraints() {
+    let mut solver = Solver::new(4);
+    solver.distance_constraints.push(DistanceConstraint::new(1, 2, 0.1, 0.0));
+    solver.particles.charge[1] = 2.0;
+    solver.particles.temperature[1] = 5.0;
+    solver.particles.rest_density[1] = 3.0;
+    solver.particles.friction[1] = 0.2;
+    solver.particles.restitution[1] = 0.3;
+    solver.particles.target_weight[1] = 0.8;
+    solver.particles.inv_mass[1] = 0.0;
+    solver.particles.phase[1] = Phase::Static;
+    let old_id = solver.particles.user_id[1];
+    solver.kill_particles(&[1]);
+    solver.config.mass_from_radius = true;
+
+    let i = solver.spawn_particle(Vec3::new(0.1, 0.2, 0.0), Vec3::ZERO, 0.0);
+    assert_eq!(i, 1);
+    let p = &solver.particles;
+    assert_eq!(p.charge[i], 0.0);
+    assert_eq!(p.temperature[i], 0.0);
+    assert_eq!(p.rest_density[i], 0.0);
+    assert_eq!(p.friction[i], 1.0);
+    assert_eq!(p.restitution[i], 1.0);
+    assert_eq!(p.target_weight[i], 0.0);
+    assert_eq!(p.phase[i], Phase::Free);
+    assert_ne!(p.user_id[i], old_id, "a reused slot is a new particle");
+    assert!(
+        solver.distance_constraints.iter().all(|c| c.i != 1 && c.j != 1),
+        "the reused slot must not inherit the dead particle's constraints"
+    );
+
+    // Mass follows the radius, as for any other particle of that size
+    let radius = solver.particles.radius[i];
+    solver.set_particle_radius(0, radius);
+    assert_eq!(solver.particles.inv_mass[i], solver.particles.inv_mass[0]);
+    assert_ne!(solver.particles.inv_mass[i], 1.0);
+}
+
+/// Mean speed of the particles an attract pointer was pulling, a few steps
+/// after it is released.
+fn speed_after_pointer_release(release_damping: f32) -> f32 {
+    let mut solver = Solver::new(200);
+    solver.config.shape_strength = 0.0;
+    solver.config.pointer_release_damping = release_damping;
+    let center = Vec3::new(0.0, 0.0, 0.0);
+    solver.pointer_params = PointerParams {
+        active: true,
+        mode: 0,
+        position: center,
+        strength: 3.0,
+        radius: 1.5,
+        pressing: true,
+        ..PointerParams::default()
+    };
+    for step in 0..20 {
+        solver.step(0.016, step as f32 * 0.016);
+    }
+    let pulled: Vec<usize> =
+        (0..200).filter(|&i| solver.particles.position[i].distance(center) <= 1.5).collect();
+    assert!(!pulled.is_empty());
+
+    solver.pointer_params.active = false;
+    for step in 20..30 {
+        solver.step(0.016, step as f32 * 0.016);
+    }
+    pulled.iter().map(|&i| solver.particles.velocity[i].length()).sum::<f32>() / pulled.len() as f32
+}
+
+#[test]
+fn test_pointer_release_damping_settles_pulled_particles() {
+    let coasting = speed_after_pointer_release(0.0);
+    let damped = speed_after_pointer_release(0.3);
+    assert!(damped < coasting * 0.7, "release damping should settle faster: {damped} vs {coasting}");
+}
+
+#[test]
+fn test_grid_allocated_lazily_and_sized_to_count() {
+    let mut euler = Solver::new(1000);
+    for step in 0..3 {
+        euler.step(0.016, step as f32 * 0.016);
+    }
+    assert_eq!(euler.grid_table_size(), 0, "no collisions: no hash table");
+
+    let mut xpbd = Solver::new(1000);
+    xpbd.config.collisions_enabled = true;
+    assert_eq!(xpbd.grid_table_size(), 0);
+    xpbd.step(0.016, 0.0);
+    assert_eq!(xpbd.grid_table_size(), 16384, "small scenes get the minimum table");
+
+    xpbd.resize(20000);
+    assert_eq!(xpbd.grid_table_size(), 65536, "next power of two above 2 * count");
+
+    let mut capped = Solver::new(1000);
+    capped.config.collisions_enabled = true;
+    capped.config.grid_table_size = 512;
+    capped.step(0.016, 0.0);
+    assert_eq!(capped.grid_table_size(), 512);
+}
+
+/// Drop a tight clump of granular particles under gravity with the given hash
+/// table size and return their positions after 30 steps.
+fn granular_pile_positions(grid_table_size: usize) -> Vec<Vec3> {
+    let n = 64;
+    let mut solver = Solver::new(n);
+    solver.config.collisions_enabled = true;
+    solver.config.artistic_forces_enabled = false;
+    solver.config.gravity = Vec3::new(0.0, -9.8, 0.0);
+    solver.config.grid_table_size = grid_table_size;
+    for i in 0..n {
+        let (x, y, z) = ((i % 4) as f32, (i / 16) as f32, ((i / 4) % 4) as f32);
+        solver.particles.position[i] = Vec3::new(x, y, z) * 0.09 - Vec3::new(0.15, 1.0, 0.15);
+        solver.particles.velocity[i] = Vec3::ZERO;
+        solver.particles.radius[i] = 0.05;
+        solver.particles.phase[i] = Phase::Granular;
+    }
+    for step in 0..30 {
+        solver.step(1.0 / 60.0, step as f32 / 60.0);
+    }
+    solver.particles.position.clone()
+}
+
+#[test]
+fn test_contact_order_independent_of_grid_buckets() {
+    let reference = granular_pile_positions(16384);
+    for table_size in [7, 61, 1024] {
+        assert_eq!(granular_pile_positions(table_size), reference, "table size {table_size}");
+    }
+}
+
+#[test]
+fn test_freeze_restores_exact_inv_mass() {
+    let mut solver = Solver::new(10);
+    solver.particles.inv_mass[3] = 1.0 / 5.0;
+    solver.particles.velocity[3] = Vec3::new(1.0, 0.0, 0.0);
+    let held = solver.particles.position[3];
+
+    solver.freeze_particles(&[3]);
+    solver.freeze_particles(&[3, 42]); // refreezing must not overwrite the stash
+    assert_eq!(solver.particles.inv_mass[3], 0.0);
+    for step in 0..20 {
+        solver.step(0.016, step as f32 * 0.016);
+    }
+    assert_eq!(solver.particles.position[3], held, "frozen particle should not move");
+
+    solver.unfreeze_particles(&[3, 7]);
+    assert_eq!(solver.particles.inv_mass[3], 0.2);
+    assert_eq!(solver.particles.inv_mass[7], 1.0, "unfrozen-but-never-frozen is untouched");
+}
+
+/// Pull large particles into a static shape target until they pile up, then return
+/// (mean per-particle frame-to-frame displacement over the last 20 frames, max penetration).
+fn settled_pile_jitter(contact_relaxation: f32) -> (f32, f32) {
+    let n = 60;
+    let mut solver = Solver::new(n);
+    solver.config.collisions_enabled = true;
+    solver.config.contact_relaxation = contact_relaxation;
+    solver.config.shape_strength = 1.0;
+    for i in 0..n {
+        solver.particles.radius[i] = 0.12;
+    }
+
+    let mut jitter = 0.0;
+    let mut prev = solver.particles.position.clone();
+    for step in 0..100 {
+        solver.step(0.016, 0.0); // fixed time keeps the targets still
+        if step >= 80 {
+            let moved: f32 = solver
+                .particles
+                .position
+                .iter()
+                .zip(&prev)
+                .map(|(a, b)| (*a - *b).length())
+                .sum();
+            jitter += moved / n as f32;
+        }
+        prev.copy_from_slice(&solver.particles.position);
+    }
+
+    let mut grid = SpatialHashGrid::new(0.3, 4096, n);
+    grid.build(&solver.particles.position, n);
+    let particles = &solver.particles;
+    let max_penetration = detect_contacts(&particles.position, &particles.radius, &particles.inv_mass, n, &grid, 0)
+        .iter()
+        .map(|c| c.penetration)
+        .fold(0.0_f32, f32::max);
+    (jitter / 20.0, max_penetration)
+}
+
+#[test]
+fn test_contact_relaxation_reduces_pile_jitter() {
+    let (jitter_full, _) = settled_pile_jitter(1.0);
+    let (jitter_relaxed, penetration_relaxed) = settled_pile_jitter(0.5);
+    assert!(
+        jitter_relaxed < jitter_full,
+        "under-relaxed contacts should jitter less: relaxed={}, full={}",
+        jitter_relaxed,
+        jitter_full
+    );
+    assert!(
+        penetration_relaxed < 0.06,
+        "overlap should still be resolved: max penetration {}",
+        penetration_relaxed
+    );
+}
+
+#[test]
+fn test_init_galaxy_layout() {
+    let mut solver = Solver::new(2000);
+    solver.init_galaxy(3, 2.0, 0.1, 0.5, 7);
+
+    let (mut inner_speed, mut inner_n) = (0.0_f32, 0);
+    let (mut outer_speed, mut outer_n) = (0.0_f32, 0);
+    for i in 0..solver.particles.count {
+        let p = solver.particles.position[i];
+        let v = solver.particles.velocity[i];
+        assert!(p.z.abs() <= 0.05 + 1e-6, "disk should be thin: z={}", p.z);
+        assert!(v.dot(p).abs() < 1e-3 * (1.0 + p.length()), "velocity should be tangential");
+        let r = p.truncate().length();
+        if r < 0.5 {
+            inner_speed += v.length();
+            inner_n += 1;
+        } else if r > 1.5 {
+            outer_speed += v.length();
+            outer_n += 1;
+        }
+    }
+    assert!(inner_n > 0 && outer_n > 0);
+    assert!(
+        inner_speed / inner_n as f32 > outer_speed / outer_n as f32,
+        "inner orbits should be faster"
+    );
+
+    let mut again = Solver::new(2000);
+    again.init_galaxy(3, 2.0, 0.1, 0.5, 7);
+    assert_eq!(again.particles.position, solver.particles.position);
+    again.init_galaxy(3, 2.0, 0.1, 0.5, 8);
+    assert_ne!(again.particles.position, solver.particles.position);
+
+    // The central mass holds the disk in orbit
+    assert!(solver.config.central_attractor_enabled);
+    solver.config.artistic_forces_enabled = false;
+    solver.config.gravity = Vec3::ZERO;
+    let outer: Vec<usize> = (0..solver.particles.count)
+        .filter(|&i| solver.particles.position[i].truncate().length() > 1.5)
+        .collect();
+    let mean_radius = |s: &Solver| {
+        outer.iter().map(|&i| s.particles.position[i].truncate().length()).sum::<f32>() / outer.len() as f32
+    };
+    let before = mean_radius(&solver);
+    for frame in 0..120 {
+        solver.step(1.0 / 60.0, frame as f32 / 60.0);
+    }
+    let after = mean_radius(&solver);
+    assert!((after - before).abs() < 0.1 * before, "outer disk radius {} -> {}", before, after);
+}
+
+#[test]
+fn test_create_rigid_body_overflow_guard() {
+    let mut solver = Solver::new(10);
+    solver.create_rigid_body(8, 5, 0.9); // 8+5=13 > 10
+    assert!(solver.shape_match_groups.is_empty(),
+        "Overflow should prevent rigid body creation");
+    for i in 0..10 {
+        assert_eq!(solver.particles.phase[i], Phase::Free);
+    }
+}
+
+#[test]
+fn test_speed_multiplier_affects_motion() {
+    // Normal speed
+    let mut solver1 = Solver::new(5);
+    solver1.config.shape_strength = 0.0;
+    solver1.particles.position[0] = Vec3::ZERO;
+    solver1.particles.velocity[0] = Vec3::new(1.0, 0.0, 0.0);
+    solver1.shape_params.speed_multiplier = 1.0;
+    solver1.step(0.016, 0.0);
+    let pos1 = solver1.particles.position[0];
+
+    // Double speed
+    let mut solver2 = Solver::new(5);
+    solver2.config.shape_strength = 0.0;
+    solver2.particles.position[0] = Vec3::ZERO;
+    solver2.particles.velocity[0] = Vec3::new(1.0, 0.0, 0.0);
+    solver2.shape_params.speed_multiplier = 2.0;
+    solver2.step(0.016, 0.0);
+    let pos2 = solver2.particles.position[0];
+
+    // Double speed should move further in X
+    assert!(pos2.x > pos1.x * 1.3,
+        "2x speed should move further: pos1.x={}, pos2.x={}", pos1.x, pos2.x);
+}
+
+/// X distance a particle coasting at unit speed covers in one step, with
+/// artistic (including audio) forces off so only the time scale matters.
+fn audio_coupled_travel(coupling: f32, audio_energy: f32) -> f32 {
+    let mut solver = Solver::new(1);
+    solver.config.artistic_forces_enabled = false;
+    solver.config.gravity = Vec3::ZERO;
+    solver.config.audio_speed_coupling = coupling;
+    solver.shape_params.audio_energy = audio_energy;
+    solver.particles.position[0] = Vec3::ZERO;
+    solver.particles.velocity[0] = Vec3::new(1.0, 0.0, 0.0);
+    solver.step(0.016, 0.0);
+    solver.particles.position[0].x
+}
+
+#[test]
+fn test_audio_speed_coupling_scales_motion() {
+    let silent = audio_coupled_travel(1.0, 0.0);
+    let loud = audio_coupled_travel(1.0, 1.0);
+    assert!(loud > silent * 1.5, "loud frame should advance further: {loud} vs {silent}");
+    assert_eq!(audio_coupled_travel(0.0, 1.0), audio_coupled_travel(0.0, 0.0));
+    assert_eq!(silent, audio_coupled_travel(0.0, 0.0));
+}
+
+#[test]
+fn test_morph_blending_differs() {
+    let mut solver_a = Solver::new(10);
+    solver_a.shape_params.shape_a = 0; // cube
+    solver_a.shape_params.shape_b = 1; // sphere
+    solver_a.shape_params.morph = 0.0;
+    solver_a.config.shape_strength = 0.9;
+    solver_a.step(0.016, 1.0);
+    let targets_a: Vec<Vec3> = solver_a.particles.target_pos[..10].to_vec();
+
+    let mut solver_b = Solver::new(10);
+    solver_b.shape_params.shape_a = 0;
+    solver_b.shape_params.shape_b = 1;
+    solver_b.shape_params.morph = 1.0;
+    solver_b.config.shape_strength = 0.9;
+    solver_b.step(0.016, 1.0);
+    let targets_b: Vec<Vec3> = solver_b.particles.target_pos[..10].to_vec();
+
+    // morph=0 vs morph=1 should produce different targets
+    let mut diff_count = 0;
+    for i in 0..10 {
+        if (targets_a[i] - targets_b[i]).length() > 0.01 {
+            diff_count += 1;
+        }
+    }
+    assert!(diff_count > 3, "morph=0 vs morph=1 should produce different targets: {} differ", diff_count);
+}
+
+#[test]
+fn test_combined_xpbd_all_constraints() {
+    // Test that all constraint types coexist without NaN or panic
+    let mut solver = Solver::new(50);
+    solver.config.collisions_enabled = true;
+    solver.config.substeps = 2;
+    solver.config.solver_iterations = 2;
+    solver.config.shape_strength = 0.5;
+    solver.config.nbody_enabled = true;
+    solver.config.nbody_g = 0.001;
+    solver.config.em_enabled = true;
+    solver.config.em_coulomb_k = 0.1;
+
+    // Cloth patch (first 16 particles)
+    solver.create_cloth(0, 4, 4, 0.2, 0.001, 0.01);
+
+    // Rigid body (particles 16-19)
+    solver.particles.position[16] = Vec3::new(2.0, 0.0, 0.0);
+    solver.particles.position[17] = Vec3::new(2.5, 0.0, 0.0);
+    solver.particles.position[18] = Vec3::new(2.5, 0.5, 0.0);
+    solver.particles.position[19] = Vec3::new(2.0, 0.5, 0.0);
+    solver.create_rigid_body(16, 4, 0.9);
+
+    // Some fluid particles
+    for i in 20..30 {
+        solver.particles.phase[i] = Phase::Fluid;
+        solver.particles.position[i] = Vec3::new(-1.0 + (i - 20) as f32 * 0.05, 0.0, 0.0);
+    }
+
+    // Some charged particles
+    for i in 30..35 {
+        solver.particles.charge[i] = if i % 2 == 0 { 1.0 } else { -1.0 };
+    }
+
+    // Step several times
+    for step in 0..20 {
+        solver.step(1.0 / 60.0, step as f32 / 60.0);
+    }
+
+    // Verify no NaN
+    for i in 0..50 {
+        let p = solver.particles.position[i];
+        let v = solver.particles.velocity[i];
+        assert!(!p.x.is_nan() && !p.y.is_nan() && !p.z.is_nan(),
+            "NaN position at particle {}", i);
+        assert!(!v.x.is_nan() && !v.y.is_nan() && !v.z.is_nan(),
+            "NaN velocity at particle {}", i);
+    }
+}
+
+#[test]
+fn test_external_force_applied_once_then_cleared() {
+    let mut pushed = Solver::new(10);
+    let mut baseline = Solver::new(10);
+    for solver in [&mut pushed, &mut baseline] {
+        solver.config.shape_strength = 0.0;
+        solver.particles.velocity[0] = Vec3::ZERO;
+    }
+
+    let dt = 0.016;
+    pushed.add_external_force(0, Vec3::new(10.0, 0.0, 0.0));
+    pushed.add_external_force(0, Vec3::new(5.0, 0.0, 0.0));
+    pushed.step(dt, 1.0);
+    baseline.step(dt, 1.0);
+
+    // 15 units of force over one step, scaled by the speed-multiplier damping
+    let dv = pushed.particles.velocity[0] - baseline.particles.velocity[0];
+    assert!(
+        (dv.x - 15.0 * dt * 0.915).abs() < 1e-3,
+        "external force should add F*dt to velocity: dv={:?}",
+        dv
+    );
+    assert_eq!(
+        pushed.particles.external_force[0],
+        Vec3::ZERO,
+        "external force should be cleared after the step"
+    );
+
+    // A second step without re-adding must not push further
+    pushed.step(dt, 1.016);
+    baseline.step(dt, 1.016);
+    let dv2 = pushed.particles.velocity[0] - baseline.particles.velocity[0];
+    assert!(
+        dv2.x < dv.x,
+        "velocity gap should only decay once the force is cleared: {} -> {}",
+        dv.x,
+        dv2.x
+    );
+}
+
+#[test]
+fn test_periodic_boundary_wraps_position_keeps_velocity() {
+    let half_extents = Vec3::splat(2.0);
+    let mut wrapped = Solver::new(1);
+    let mut open = Solver::new(1);
+    wrapped.config.boundary_shape = BoundaryShape::Periodic { half_extents };
+    for solver in [&mut wrapped, &mut open] {
+        solver.config.shape_strength = 0.1;
+        solver.particles.position[0] = Vec3::new(1.98, 0.0, 0.0);
+        solver.particles.velocity[0] = Vec3::new(5.0, 0.0, 0.0);
+    }
+
+    wrapped.step(0.016, 1.0);
+    open.step(0.016, 1.0);
+
+    let p_open = open.particles.position[0];
+    let p_wrapped = wrapped.particles.position[0];
+    assert!(p_open.x > 2.0, "unwrapped particle should cross +X: {:?}", p_open);
+    assert!(p_wrapped.x < -1.9, "particle should reappear at -X: {:?}", p_wrapped);
+    assert!(
+        (p_wrapped - (p_open - Vec3::new(4.0, 0.0, 0.0))).length() < 1e-5,
+        "wrap should shift by exactly one period: {:?} vs {:?}",
+        p_wrapped,
+        p_open
+    );
+    assert_eq!(
+        wrapped.particles.velocity[0], open.particles.velocity[0],
+        "velocity should be continuous across the seam"
+    );
+}
+
+/// Solver iterations the last step actually used, with an 8-iteration budget
+/// and a convergence tolerance, for particles laid out at `spacing`.
+fn iterations_used_at_spacing(spacing: f32) -> (u32, u32) {
+    let mut solver = Solver::new(27);
+    solver.config.collisions_enabled = true;
+    solver.config.shape_strength = 0.0;
+    solver.config.convergence_epsilon = 1e-4;
+    solver.adaptive_quality.max_iterations = 8;
+    for i in 0..27 {
+        let cell = Vec3::new((i % 3) as f32, ((i / 3) % 3) as f32, (i / 9) as f32) - Vec3::ONE;
+        solver.particles.position[i] = cell * spacing;
+        solver.particles.velocity[i] = Vec3::ZERO;
+        solver.particles.radius[i] = 0.05;
+    }
+    solver.step(0.016, 0.0);
+    (solver.last_stats.iterations_used, solver.last_stats.iterations)
+}
+
+#[test]
+fn test_convergence_epsilon_ends_iterations_early() {
+    // Well separated: nothing to correct, so the first iteration converges
+    let (used, max) = iterations_used_at_spacing(0.5);
+    assert_eq!(max, 8);
+    assert!(used < max, "resolved scene used {used} of {max} iterations");
+
+    // Heavily overlapping block: Jacobi contacts keep correcting every iteration
+    let (used, max) = iterations_used_at_spacing(0.03);
+    assert_eq!(used, max, "hard scene should use the full iteration count");
+}
+
+#[test]
+fn test_degenerate_periodic_extents_are_clamped() {
+    let mut solver = Solver::new(8);
+    solver.config.collisions_enabled = true;
+    solver.config.boundary_shape =
+        BoundaryShape::Periodic { half_extents: Vec3::new(2.0, 0.0, f32::NAN) };
+    for frame in 0..5 {
+        solver.step(0.016, frame as f32 * 0.016);
+    }
+    for p in &solver.particles.position {
+        assert!(p.is_finite(), "wrap math should stay finite: {:?}", p);
+        assert!(p.y.abs() <= MIN_PERIODIC_HALF_EXTENT + 1e-6 && p.z.abs() <= MIN_PERIODIC_HALF_EXTENT + 1e-6);
+    }
+}
+
+#[test]
+fn test_periodic_boundary_contacts_across_seam() {
+    let mut solver = Solver::new(2);
+    solver.config.boundary_shape = BoundaryShape::Periodic { half_extents: Vec3::splat(2.0) };
+    solver.config.collisions_enabled = true;
+    solver.config.shape_strength = 0.0;
+    solver.adaptive_quality.max_substeps = 1;
+    solver.particles.position[0] = Vec3::new(1.97, 0.0, 0.0);
+    solver.particles.position[1] = Vec3::new(-1.97, 0.0, 0.0);
+    for i in 0..2 {
+        solver.particles.velocity[i] = Vec3::ZERO;
+        solver.particles.radius[i] = 0.05;
+    }
+
+    solver.step(0.001, 0.0);
+
+    assert_eq!(
+        solver.last_stats.contact_count, 1,
+        "particles on opposite faces should be in contact across the wrap seam"
+    );
+}
+
+/// Build a 200-particle scene with several rigid bodies from the spiral layout of
+/// `seed` and run it for 60 steps.
+fn run_shape_matching_scene(seed: u32) -> Solver {
+    let mut solver = Solver::new(200);
+    solver.reinitialize(seed);
+    solver.config.collisions_enabled = true;
+    solver.config.shape_strength = 0.5;
+    for body in 0..5 {
+        solver.create_rigid_body(body * 40, 40, 0.8);
+    }
+    for step in 0..60 {
+        solver.step(1.0 / 60.0, step as f32 / 60.0);
+    }
+    solver
+}
+
+#[test]
+fn test_shape_matching_scene_bit_identical_across_runs() {
+    let a = run_shape_matching_scene(42);
+    let b = run_shape_matching_scene(42);
+
+    for i in 0..200 {
+        let (pa, pb) = (a.particles.position[i], b.particles.position[i]);
+        assert!(
+            pa.x.to_bits() == pb.x.to_bits()
+                && pa.y.to_bits() == pb.y.to_bits()
+                && pa.z.to_bits() == pb.z.to_bits(),
+            "particle {} diverged between runs: {:?} vs {:?}",
+            i, pa, pb
+        );
+    }
+
+    // The seed picks the starting layout
+    let c = run_shape_matching_scene(43);
+    assert_ne!(a.particles.position, c.particles.position);
+}
+
+#[test]
+fn test_sample_flow_matches_particle_flow() {
+    let pos = Vec3::new(0.7, -0.3, 0.4);
+    let time = 2.5;
+    let dt = 0.016;
+    let mut solver = Solver::new(1);
+    solver.config.shape_strength = 0.5;
+    solver.particles.position[0] = pos;
+    solver.particles.velocity[0] = Vec3::ZERO;
+    // Zero hash => zero shape affinity, so only flow + constant gravity act
+    solver.particles.hash[0] = 0.0;
+
+    let sampled = solver.sample_flow(pos, time);
+    solver.step(dt, time);
+
+    // vel = (flow - 0.04 * Y) * dt, then the speed-multiplier damping of 0.915
+    let flow_in_step = solver.particles.velocity[0] / (dt * 0.915) + Vec3::new(0.0, 0.04, 0.0);
+    assert!(
+        (flow_in_step - sampled).length() < 1e-3,
+        "sample_flow {:?} should match the in-step flow {:?}",
+        sampled,
+        flow_in_step
+    );
+    assert_eq!(solver.sample_pointer_force(pos, time), Vec3::ZERO);
+}
+
+#[test]
+fn test_pointer_force_fades_out_at_cursor() {
+    let mut solver = Solver::new(1);
+    let pointer = Vec3::new(0.3, -0.2, 0.1);
+    let outside = pointer + Vec3::new(0.4, 0.2, 0.0);
+    for mode in 0..=6 {
+        solver.pointer_params = PointerParams {
+            active: true,
+            mode,
+            position: pointer,
+            strength: 1.0,
+            radius: 2.0,
+            pressing: true,
+            pulse: false,
+            view_dir: Vec3::NEG_Z,
+            ..PointerParams::default()
+        };
+        solver.config.pointer_min_distance = 0.05;
+        let at_cursor = solver.sample_pointer_force(pointer, 1.0);
+        let near_cursor = solver.sample_pointer_force(pointer + Vec3::X * 0.001, 1.0);
+        assert!(at_cursor.length() < 1e-6, "mode {mode}: force on the cursor {at_cursor:?}");
+        assert!(near_cursor.is_finite(), "mode {mode}: non-finite force {near_cursor:?}");
+        assert!(near_cursor.length() < 1.0, "mode {mode}: force near cursor {near_cursor:?}");
+
+        // Outside the falloff radius the force is untouched
+        let faded = solver.sample_pointer_force(outside, 1.0);
+        solver.config.pointer_min_distance = 0.0;
+        assert_eq!(faded, solver.sample_pointer_force(outside, 1.0), "mode {mode}");
+    }
+}
+
+/// Launch one particle tangentially around an attractor at the origin and
+/// return (min radius, max radius, total swept angle) over `steps` frames.
+fn attractor_orbit(mass: f32, enabled: bool, steps: usize) -> (f32, f32, f32) {
+    let mut solver = Solver::new(1);
+    solver.config.shape_strength = 1.0;
+    solver.config.shape_attract_phases = [false; 7];
+    solver.config.central_attractor_enabled = enabled;
+    solver.config.central_attractor_mass = mass;
+    let r0 = 1.5;
+    let gm = solver.config.nbody_g * 20000.0;
+    solver.particles.position[0] = Vec3::new(r0, 0.0, 0.0);
+    solver.particles.velocity[0] = Vec3::new(0.0, (gm / r0).sqrt(), 0.0);
+
+    let dt = 1.0 / 60.0;
+    let (mut r_min, mut r_max, mut swept) = (f32::MAX, 0.0_f32, 0.0_f32);
+    let mut prev_angle = 0.0_f32;
+    for s in 0..steps {
+        solver.step(dt, s as f32 * dt);
+        let p = solver.particles.position[0];
+        assert!(p.is_finite(), "orbit blew up at step {}", s);
+        r_min = r_min.min(p.length());
+        r_max = r_max.max(p.length());
+        let angle = p.y.atan2(p.x);
+        let mut delta = angle - prev_angle;
+        if delta > std::f32::consts::PI {
+            delta -= std::f32::consts::TAU;
+        } else if delta < -std::f32::consts::PI {
+            delta += std::f32::consts::TAU;
+        }
+        swept += delta;
+        prev_angle = angle;
+    }
+    (r_min, r_max, swept)
+}
+
+#[test]
+fn test_central_attractor_holds_orbit_and_zero_mass_is_inert() {
+    let r0 = 1.5;
+    // Bound: the particle circles the attractor and never escapes its launch radius
+    let (_, r_max, swept) = attractor_orbit(20000.0, true, 300);
+    assert!(r_max <= r0 + 1e-3, "orbit should stay bound: r_max={}", r_max);
+    assert!(swept.abs() > std::f32::consts::TAU, "particle should circle the attractor: swept={}", swept);
+
+    // Without a pull the tangential launch carries the particle outward
+    let (_, free_r_max, _) = attractor_orbit(0.0, true, 60);
+    assert!(free_r_max > r0 + 0.02, "zero mass should exert no pull: r_max={}", free_r_max);
+    assert_eq!(attractor_orbit(0.0, true, 60), attractor_orbit(20000.0, false, 60));
+}
+
+/// Horizontal drift of one particle dropped from rest at the origin after 30 steps.
+fn drop_drift(artistic_forces_enabled: bool) -> (f32, f32) {
+    let mut solver = Solver::new(1);
+    solver.config.artistic_forces_enabled = artistic_forces_enabled;
+    solver.particles.position[0] = Vec3::ZERO;
+    solver.particles.velocity[0] = Vec3::ZERO;
+    let dt = 1.0 / 60.0;
+    for s in 0..30 {
+        solver.step(dt, s as f32 * dt);
+    }
+    let p = solver.particles.position[0];
+    (Vec3::new(p.x, 0.0, p.z).length(), p.y)
+}
+
+#[test]
+fn test_collision_only_mode_falls_straight_under_gravity() {
+    let (drift, height) = drop_drift(false);
+    assert_eq!(drift, 0.0, "no curl drift without artistic forces");
+    assert!(height < -0.5, "gravity should pull the particle down: y={}", height);
+
+    let (default_drift, _) = drop_drift(true);
+    assert!(default_drift > 1e-3, "default flow should push sideways: {}", default_drift);
+}
+
+/// Per-particle acceleration of one Euler step from `positions` at rest.
+fn first_step_acceleration(positions: &[Vec3], beam_enabled: bool) -> Vec<Vec3> {
+    let mut solver = Solver::new(positions.len());
+    solver.config.beam_enabled = beam_enabled;
+    solver.config.beam_start = Vec3::new(-1.0, 0.5, 0.0);
+    solver.config.beam_end = Vec3::new(1.0, 0.5, 0.0);
+    solver.particles.position.copy_from_slice(positions);
+    solver.particles.velocity.fill(Vec3::ZERO);
+    solver.step(0.016, 0.0);
+    solver.particles.acceleration.clone()
+}
+
+#[test]
+fn test_beam_pulls_toward_segment() {
+    let positions = [
+        Vec3::new(0.3, 1.5, 0.0),  // above the beam
+        Vec3::new(-0.2, 0.5, 0.8), // off axis in z
+        Vec3::new(2.0, 0.5, 0.0),  // past the end, on the beam's line
+        Vec3::new(0.4, 0.5, 0.0),  // on the beam
+    ];
+    let with = first_step_acceleration(&positions, true);
+    let without = first_step_acceleration(&positions, false);
+    let beam: Vec<Vec3> = with.iter().zip(&without).map(|(a, b)| *a - *b).collect();
+
+    let strength = 4.0;
+    let expected = [
+        Vec3::new(0.0, -1.0, 0.0),
+        Vec3::new(0.0, 0.0, -0.8),
+        Vec3::new(-1.0, 0.0, 0.0), // toward the clamped endpoint
+        Vec3::ZERO,
+    ];
+    for (i, (got, want)) in beam.iter().zip(expected).enumerate() {
+        assert!((*got - want * strength).length() < 1e-4, "particle {}: {:?}", i, got);
+    }
+}
+
+/// Speed and |z| of a fast attractor orbit after 200 steps without linear damping.
+fn fast_orbit_after_200_steps(quadratic_drag_enabled: bool) -> (f32, f32) {
+    let mut solver = Solver::new(1);
+    solver.config.shape_strength = 1.0;
+    solver.config.shape_attract_phases = [false; 7];
+    solver.config.central_attractor_enabled = true;
+    solver.config.linear_damping_min = 1.0;
+    solver.config.linear_damping_max = 1.0;
+    solver.config.quadratic_drag_enabled = quadratic_drag_enabled;
+    let r0 = 1.5;
+    let gm = solver.config.nbody_g * solver.config.central_attractor_mass;
+    solver.particles.position[0] = Vec3::new(r0, 0.0, 0.0);
+    solver.particles.velocity[0] = Vec3::new(0.0, (gm / r0).sqrt(), 0.0);
+
+    let dt = 1.0 / 60.0;
+    for s in 0..200 {
+        solver.step(dt, s as f32 * dt);
+    }
+    (solver.particles.velocity[0].length(), solver.particles.position[0].z.abs())
+}
+
+#[test]
+fn test_disabling_quadratic_drag_preserves_orbit_speed() {
+    let (drag_speed, _) = fast_orbit_after_200_steps(true);
+    let (free_speed, free_z) = fast_orbit_after_200_steps(false);
+    assert!(free_speed > drag_speed, "no drag {} vs drag {}", free_speed, drag_speed);
+    // Attractor and launch lie in the xy plane; only the flow pushes out of it
+    assert!(free_z > 1e-4, "flow should still act with drag off: z={}", free_z);
+}
+
+#[test]
+fn test_heat_from_speed_and_fluid_density() {
+    let mut solver = Solver::new(10);
+    solver.particles.position[0] = Vec3::new(-2.0, 0.0, 0.0);
+    solver.particles.velocity[0] = Vec3::new(9.0, 0.0, 0.0);
+    solver.particles.position[1] = Vec3::new(2.0, 0.0, 0.0);
+    solver.particles.velocity[1] = Vec3::new(0.0, 1.0, 0.0);
+    // Resting fluid cube, packed well inside the smoothing radius
+    for i in 2..10 {
+        let k = i - 2;
+        solver.particles.phase[i] = Phase::Fluid;
+        solver.particles.position[i] =
+            Vec3::new((k % 2) as f32, ((k / 2) % 2) as f32, (k / 4) as f32) * 0.03;
+    }
+
+    solver.compute_heat(1.0, 0.0);
+    let heat = &solver.particles.heat;
+    assert!(heat[0] > heat[1], "fast particle should be hotter: {} vs {}", heat[0], heat[1]);
+    assert!((heat[0] - 9.0 / solver.config.max_velocity).abs() < 1e-5);
+    assert_eq!(heat[2], 0.0, "resting particle has no speed heat without density weight");
+
+    solver.compute_heat(1.0, 0.5);
+    let heat = &solver.particles.heat;
+    assert!(heat[2] > 0.0, "dense fluid should contribute density heat");
+    assert!((0.0..=1.0).contains(&heat[2]));
+    assert!((heat[0] - 9.0 / solver.config.max_velocity).abs() < 1e-5, "non-fluid ignores density");
+
+    // A particle's own rest density scales its density heat
+    let rest = 4.0 * solver.particles.density[2];
+    solver.particles.rest_density[2] = rest;
+    solver.compute_heat(0.0, 0.5);
+    let expected = 0.5 * solver.particles.density[2] / rest;
+    assert!((solver.particles.heat[2] - expected).abs() < 1e-5, "heat {}", solver.particles.heat[2]);
+}
+
+#[test]
+fn test_step_n_matches_individual_steps() {
+    let make = || {
+        let mut solver = Solver::new(64);
+        solver.config.collisions_enabled = true;
+        for i in 0..64 {
+            solver.particles.position[i] = Vec3::new((i % 8) as f32, (i / 8) as f32, 0.0) * 0.15;
+        }
+        solver
+    };
+    let (dt, t0) = (1.0 / 60.0, 2.5);
+
+    let mut batched = make();
+    batched.step_n(10, dt, t0);
+
+    let mut single = make();
+    for i in 0..10 {
+        single.step(dt, t0 + i as f32 * dt);
+    }
+
+    assert_eq!(batched.particles.position, single.particles.position);
+    assert_eq!(batched.particles.velocity, single.particles.velocity);
+}
+
+/// Velocity of a lone particle launched along +X after one step.
+fn velocity_after_one_step(shape_strength: f32, tweak: impl Fn(&mut Solver)) -> Vec3 {
+    let mut solver = Solver::new(1);
+    solver.config.shape_strength = shape_strength;
+    solver.config.shape_attract_phases = [false; 7];
+    solver.particles.velocity[0] = Vec3::new(10.0, 0.0, 0.0);
+    tweak(&mut solver);
+    solver.step(1.0 / 60.0, 0.5);
+    solver.particles.velocity[0]
+}
+
+#[test]
+fn test_configurable_damping_coefficients() {
+    let light = velocity_after_one_step(0.5, |_| {});
+    let heavy = velocity_after_one_step(0.5, |s| s.config.quadratic_drag = 0.2);
+    assert!(heavy.x < light.x, "more quadratic drag should slow more: {} vs {}", heavy.x, light.x);
+
+    // Loose shapes (calm_factor = 0) use the min endpoint only
+    let loose = velocity_after_one_step(0.3, |_| {});
+    assert_eq!(velocity_after_one_step(0.3, |s| s.config.linear_damping_max = 0.5), loose);
+    assert!(velocity_after_one_step(0.3, |s| s.config.linear_damping_min = 0.5).x < loose.x);
+
+    // Calm shapes (calm_factor = 1) use the max endpoint only
+    let calm = velocity_after_one_step(1.0, |_| {});
+    assert_eq!(velocity_after_one_step(1.0, |s| s.config.linear_damping_min = 0.5), calm);
+    assert!(velocity_after_one_step(1.0, |s| s.config.linear_damping_max = 0.5).x < calm.x);
+}
+
+#[test]
+fn test_integration_path_follows_collisions_toggle() {
+    let mut solver = Solver::new(16);
+    assert_eq!(solver.active_integration_path(), IntegrationPath::Euler);
+    solver.step(0.016, 0.0);
+    assert!(!solver.take_integration_path_switch());
+
+    solver.config.collisions_enabled = true;
+    assert_eq!(solver.active_integration_path(), IntegrationPath::Xpbd);
+    solver.step(0.016, 0.016);
+    assert!(solver.take_integration_path_switch(), "switching paths mid-run should be reported");
+    assert!(!solver.take_integration_path_switch(), "switch is reported once");
+
+    solver.step(0.016, 0.032);
+    assert!(!solver.take_integration_path_switch());
+}
+
+/// Largest per-particle velocity change on the step that turns collisions on,
+/// after a few Euler steps of a moving scene.
+fn velocity_jump_on_path_switch(blend_frames: u32) -> f32 {
+    let mut solver = Solver::new(64);
+    solver.config.path_blend_frames = blend_frames;
+    for i in 0..64 {
+        solver.particles.velocity[i] = Vec3::new(0.5, 0.2, 0.0);
+    }
+    for step in 0..5 {
+        solver.step(0.016, step as f32 * 0.016);
+    }
+    let before = solver.particles.velocity.clone();
+    solver.config.collisions_enabled = true;
+    solver.step(0.016, 5.0 * 0.016);
+    solver
+        .particles
+        .velocity
+        .iter()
+        .zip(&before)
+        .map(|(after, before)| (*after - *before).length())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn test_path_blend_softens_velocity_jump_on_switch() {
+    let abrupt = velocity_jump_on_path_switch(0);
+    let blended = velocity_jump_on_path_switch(4);
+    assert!(abrupt > 0.4, "abrupt switch should spike, got {abrupt}");
+    assert!(blended < 0.3, "cross-fade should cap the spike, got {blended}");
+    assert!(blended < abrupt * 0.5, "blended jump {blended} vs abrupt {abrupt}");
+}
+
+#[test]
+fn test_settling_detector() {
+    // Calm, fully formed shape at a frozen time: damping bleeds off the motion
+    let mut calm = Solver::new(64);
+    calm.config.shape_strength = 1.0;
+    calm.config.settle_velocity_threshold = 0.3;
+    assert!(!calm.is_settled(0.0), "nothing is slower than zero");
+    let mut settled_at = None;
+    for k in 0..400 {
+        calm.step(1.0 / 60.0, 0.0);
+        if calm.settled_frames() >= 10 {
+            settled_at = Some(k);
+            break;
+        }
+    }
+    assert!(settled_at.is_some(), "damped scene should settle");
+    assert!(calm.is_settled(0.3));
+
+    // A two-body circular orbit keeps its speed and separation indefinitely
+    let mut orbit = Solver::new(2);
+    orbit.config.collisions_enabled = true;
+    orbit.config.artistic_forces_enabled = false;
+    orbit.config.gravity = Vec3::ZERO;
+    orbit.config.nbody_enabled = true;
+    orbit.config.nbody_g = 1.0;
+    orbit.config.settle_velocity_threshold = 0.3;
+    // Equal unit masses 2 apart orbit their midpoint at half the relative speed sqrt(G*M/d)
+    let speed = 0.5 * (1.0_f32 * 2.0 / 2.0).sqrt();
+    for (i, side) in [-1.0_f32, 1.0].into_iter().enumerate() {
+        orbit.particles.inv_mass[i] = 1.0;
+        orbit.particles.position[i] = Vec3::new(side, 0.0, 0.0);
+        orbit.particles.velocity[i] = Vec3::new(0.0, 0.0, side * speed);
+    }
+    // About two thirds of a period
+    for k in 0..500 {
+        orbit.step(1.0 / 60.0, k as f32 / 60.0);
+        let separation = orbit.particles.position[0].distance(orbit.particles.position[1]);
+        assert!((1.9..2.1).contains(&separation), "orbit separation {} at step {}", separation, k);
+        assert_eq!(orbit.settled_frames(), 0, "orbit settled at step {}", k);
+    }
+}
+
+fn mean_distance_from_centroid(solver: &Solver) -> f32 {
+    let p = &solver.particles.position;
+    let centroid = p.iter().copied().sum::<Vec3>() / p.len() as f32;
+    p.iter().map(|&x| (x - centroid).length()).sum::<f32>() / p.len() as f32
+}
+
+#[test]
+fn test_scatter_reform_restores_shape_strength() {
+    let mut solver = Solver::new(64);
+    let original = solver.config.shape_strength;
+    let dt = 1.0 / 60.0;
+    for k in 0..30 {
+        solver.step(dt, k as f32 * dt);
+    }
+    let before = mean_distance_from_centroid(&solver);
+
+    solver.trigger_scatter_reform(6.0, 0.5);
+    assert_eq!(solver.config.shape_strength, 0.0, "attraction is off right after the trigger");
+    for k in 0..5 {
+        solver.step(dt, (30 + k) as f32 * dt);
+    }
+    assert!(
+        mean_distance_from_centroid(&solver) > before + 0.2,
+        "particles should spread: {} -> {}",
+        before,
+        mean_distance_from_centroid(&solver)
+    );
+    assert!(solver.config.shape_strength > 0.0 && solver.config.shape_strength < original);
+
+    for k in 0..30 {
+        solver.step(dt, (35 + k) as f32 * dt);
+    }
+    assert_eq!(solver.config.shape_strength, original, "strength restored after the ramp");
+}
+
+#[test]
+fn test_animate_shape_strength_ramps_over_duration() {
+    let mut solver = Solver::new(16);
+    solver.config.shape_strength = 0.0;
+    solver.animate_shape_strength(0.9, 1.0);
+    assert_eq!(solver.config.shape_strength, 0.0, "no jump on the call itself");
+
+    let dt = 1.0 / 60.0;
+    let mut prev = 0.0;
+    for k in 0..59 {
+        solver.step(dt, k as f32 * dt);
+        let strength = solver.config.shape_strength;
+        assert!(strength > prev && strength < 0.9, "frame {}: {} after {}", k, strength, prev);
+        prev = strength;
+    }
+    for k in 59..62 {
+        solver.step(dt, k as f32 * dt);
+    }
+    assert!((solver.config.shape_strength - 0.9).abs() < 1e-6);
+}
+
+#[test]
+fn test_force_fields_accelerate_and_stack() {
+    let run = |fields: Vec<f32>| {
+        let mut solver = Solver::new(16);
+        solver.config.shape_strength = 0.5;
+        for lift in fields {
+            solver.add_force_field(Box::new(move |_, _, _| Vec3::new(0.0, lift, 0.0)));
+        }
+        solver.step(1.0 / 60.0, 1.0);
+        solver.particles.velocity.iter().map(|v| v.y).sum::<f32>() / 16.0
+    };
+
+    let baseline = run(vec![]);
+    let one = run(vec![20.0]);
+    let two = run(vec![20.0, 20.0]);
+    assert!(one > baseline, "upward field should lift particles: {} vs {}", one, baseline);
+    assert!(
+        ((two - baseline) - 2.0 * (one - baseline)).abs() < 1e-4,
+        "second field should add to the first: {} vs {}",
+        two - baseline,
+        one - baseline
+    );
+}
+
+#[test]
+fn test_phase_drag_damps_fluid_more_than_gas() {
+    let speed_after = |phase: Phase| {
+        let mut solver = Solver::new(1);
+        solver.config.shape_strength = 0.5;
+        solver.config.shape_attract_phases = [false; 7];
+        solver.config.phase_drag[Phase::Fluid as usize] = 3.0;
+        solver.config.phase_drag[Phase::Gas as usize] = 0.2;
+        solver.particles.phase[0] = phase;
+        solver.particles.velocity[0] = Vec3::new(8.0, 0.0, 0.0);
+        for k in 0..5 {
+            solver.step(1.0 / 60.0, k as f32 / 60.0);
+        }
+        solver.particles.velocity[0].length()
+    };
+
+    let fluid = speed_after(Phase::Fluid);
+    let gas = speed_after(Phase::Gas);
+    let free = speed_after(Phase::Free);
+    assert!(fluid < free && free < gas, "fluid {} < free {} < gas {}", fluid, free, gas);
+}
+
+#[test]
+fn test_acceleration_buffer_reports_gravity() {
+    let mut solver = Solver::new(1);
+    solver.config.shape_strength = 1.0;
+    solver.config.shape_attract_phases = [false; 7];
+    solver.particles.position[0] = Vec3::new(0.5, 0.5, 0.0);
+    solver.add_external_force(0, Vec3::new(0.0, -9.81, 0.0));
+    solver.step(1.0 / 60.0, 0.0);
+
+    let acc = solver.particles.acceleration[0];
+    assert!(acc.y < -9.5, "gravity should dominate: {:?}", acc);
+    assert!(acc.x.abs() < 0.2 && acc.z.abs() < 0.2, "no sideways pull: {:?}", acc);
+}
+
+/// Fire particle 1 (from `start_x`, at `speed`) at static particle 0, both of
+/// `radius`, over one 0.1 s frame; returns the final gap between their
+/// surfaces (negative = still overlapping).
+fn approach_gap(rebuild_every_substep: bool, radius: f32, start_x: f32, speed: f32) -> f32 {
+    let mut solver = Solver::new(2);
+    solver.config.collisions_enabled = true;
+    solver.config.grid_rebuild_every_substep = rebuild_every_substep;
+    solver.config.shape_strength = 1.0;
+    solver.config.shape_attract_phases = [false; 7];
+    solver.particles.radius[..2].copy_from_slice(&[radius, radius]);
+    solver.particles.inv_mass[0] = 0.0;
+    solver.particles.position[0] = Vec3::ZERO;
+    solver.particles.position[1] = Vec3::new(start_x, 0.0, 0.0);
+    solver.particles.velocity[1] = Vec3::new(speed, 0.0, 0.0);
+
+    solver.step(0.1, 0.0);
+    solver.particles.position[1].distance(solver.particles.position[0]) - 2.0 * radius
+}
+
+#[test]
+fn test_grid_rebuild_every_substep_catches_fast_contacts() {
+    // A long frame so the mover crosses several grid cells between substeps
+    let fresh = approach_gap(true, 0.1, -1.2, 18.0);
+    let stale = approach_gap(false, 0.1, -1.2, 18.0);
+    assert!(fresh >= -1e-3, "per-substep grid should resolve the overlap: gap={}", fresh);
+    assert!(stale < -0.1, "frame-stale grid misses the overlap: gap={}", stale);
+
+    // Within the documented limit (combined radius plus the frame's travel
+    // inside one 0.2 grid cell) the frame-stale grid still finds the contact
+    let slow = approach_gap(false, 0.05, -0.15, 0.8);
+    assert!(slow < 1e-2, "the pair must actually have met: gap={}", slow);
+    assert!(slow >= -1e-3, "slow pair within the stale-grid limit: gap={}", slow);
+}
+
+#[test]
+fn test_paint_phase_only_inside_brush() {
+    let mut solver = Solver::new(100);
+    for i in 0..100 {
+        solver.particles.position[i] = Vec3::new((i % 10) as f32, (i / 10) as f32, 0.0) * 0.1;
+    }
+    let center = Vec3::new(0.45, 0.45, 0.0);
+    let radius = 0.25;
+
+    let painted = solver.paint_phase(center, radius, Phase::Fluid, false);
+    let inside = |i: usize| solver.particles.position[i].distance(center) <= radius;
+    let expected = (0..100).filter(|&i| inside(i)).count();
+    assert!(expected > 0 && expected < 100);
+    assert_eq!(painted, expected);
+    for i in 0..100 {
+        let want = if inside(i) { Phase::Fluid } else { Phase::Free };
+        assert_eq!(solver.particles.phase[i], want, "particle {}", i);
+    }
+
+    // A larger-than-cell brush in the corner, grouped into a rigid body
+    let painted = solver.paint_phase(Vec3::new(0.9, 0.9, 0.0), 0.15, Phase::Rigid, true);
+    assert_eq!(solver.shape_match_groups.len(), 1);
+    assert_eq!(solver.shape_match_groups[0].particle_indices.len(), painted);
+
+    // Repainting overlapping particles moves them into a new group, and
+    // killed particles inside the brush are left alone
+    solver.kill_particles(&[88]);
+    let repainted = solver.paint_phase(Vec3::new(0.8, 0.8, 0.0), 0.15, Phase::Rigid, true);
+    assert!(repainted > 0);
+    let members: Vec<u32> =
+        solver.shape_match_groups.iter().flat_map(|g| g.particle_indices.clone()).collect();
+    assert!(!members.contains(&88), "killed particle must not be painted");
+    let newest = solver.shape_match_groups.last().unwrap();
+    assert_eq!(newest.particle_indices.len(), repainted);
+    let mut sorted = members.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(sorted.len(), members.len(), "no particle in two groups");
+}
+
+/// x of a fast mover shot at a static particle at the origin from x = -0.5.
+fn tunneling_mover_x(speculative_margin: f32) -> f32 {
+    let mut solver = Solver::new(2);
+    solver.config.collisions_enabled = true;
+    solver.config.speculative_margin = speculative_margin;
+    solver.config.shape_strength = 1.0;
+    solver.config.shape_attract_phases = [false; 7];
+    solver.particles.radius[..2].copy_from_slice(&[0.1, 0.1]);
+    solver.particles.inv_mass[0] = 0.0;
+    solver.particles.position[0] = Vec3::ZERO;
+    solver.particles.position[1] = Vec3::new(-0.5, 0.0, 0.0);
+    solver.particles.velocity[1] = Vec3::new(18.0, 0.0, 0.0);
+
+    // A long frame: each substep moves the mover more than its own diameter
+    solver.step(0.08, 0.0);
+    solver.particles.position[1].x
+}
+
+#[test]
+fn test_speculative_contacts_prevent_tunneling() {
+    let plain = tunneling_mover_x(0.0);
+    assert!(plain > 0.2, "without speculation the mover passes through: x={}", plain);
+
+    let caught = tunneling_mover_x(1.0);
+    assert!(caught <= -0.19, "speculative contact should stop the mover: x={}", caught);
+}
+
+/// Total unwrapped azimuth swept by consecutive particles, and their xz radius range.
+fn spiral_sweep_and_radius(solver: &Solver) -> (f32, f32, f32) {
+    let pos = &solver.particles.position[..solver.particles.count];
+    let mut sweep = 0.0;
+    for w in pos.windows(2) {
+        let mut d = w[1].z.atan2(w[1].x) - w[0].z.atan2(w[0].x);
+        if d > std::f32::consts::PI {
+            d -= std::f32::consts::TAU;
+        } else if d < -std::f32::consts::PI {
+            d += std::f32::consts::TAU;
+        }
+        sweep += d;
+    }
+    let radii = pos.iter().map(|p| Vec3::new(p.x, 0.0, p.z).length());
+    let (lo, hi) = radii.fold((f32::MAX, 0.0f32), |(lo, hi), r| (lo.min(r), hi.max(r)));
+    (sweep, lo, hi)
+}
+
+#[test]
+fn test_init_spiral_parameters() {
+    let mut solver = Solver::new(1000);
+    let (default_sweep, lo, hi) = spiral_sweep_and_radius(&solver);
+    assert!((default_sweep / std::f32::consts::TAU - 20.0).abs() < 0.1, "sweep={}", default_sweep);
+    assert!((lo - 0.5).abs() < 1e-3 && (hi - 2.0).abs() < 0.01, "lo={} hi={}", lo, hi);
+
+    solver.config.init_spiral_turns = 3.0;
+    solver.config.init_spiral_radius = [1.0, 1.5];
+    solver.reinitialize(0);
+    let (sweep, lo, hi) = spiral_sweep_and_radius(&solver);
+    assert!((sweep / std::f32::consts::TAU - 3.0).abs() < 0.05, "sweep={}", sweep);
+    assert!((lo - 1.0).abs() < 1e-3 && (hi - 1.5).abs() < 0.01, "lo={} hi={}", lo, hi);
+}
+
+#[test]
+fn test_bounding_sphere_of_cube() {
+    let mut solver = Solver::new(27);
+    let corner_min = Vec3::new(1.0, -2.0, 0.5);
+    for i in 0..27 {
+        let cell = Vec3::new((i % 3) as f32, ((i / 3) % 3) as f32, (i / 9) as f32);
+        solver.particles.position[i] = corner_min + cell * 0.5;
+    }
+
+    let (center, radius) = solver.bounding_sphere();
+    let expected_center = corner_min + Vec3::splat(0.5);
+    assert!(center.distance(expected_center) < 1e-5, "center={:?}", center);
+    assert!((radius - 0.5 * 3f32.sqrt()).abs() < 1e-5, "radius={}", radius);
+
+    // A killed straggler far away does not pull the camera
+    solver.resize(28);
+    solver.particles.position[27] = Vec3::splat(50.0);
+    solver.kill_particles(&[27]);
+    assert_eq!(solver.bounding_sphere(), (center, radius));
+
+    assert_eq!(Solver::new(0).bounding_sphere(), (Vec3::ZERO, 0.0));
+    let mut dead = Solver::new(2);
+    dead.kill_particles(&[0, 1]);
+    assert_eq!(dead.bounding_sphere(), (Vec3::ZERO, 0.0));
+}
+
+#[test]
+fn test_sample_velocity_of_moving_cluster() {
+    let mut solver = Solver::new(28);
+    let drift = Vec3::new(0.3, -0.2, 0.5);
+    for i in 0..27 {
+        let cell = Vec3::new((i % 3) as f32, ((i / 3) % 3) as f32, (i / 9) as f32);
+        solver.particles.position[i] = Vec3::splat(1.0) + cell * 0.05;
+        solver.particles.velocity[i] = drift;
+    }
+    // A distant particle moving differently must not leak into the sample
+    solver.particles.position[27] = Vec3::new(3.0, 3.0, 3.0);
+    solver.particles.velocity[27] = Vec3::new(-5.0, 0.0, 0.0);
+
+    let inside = solver.sample_velocity(Vec3::new(1.04, 1.05, 1.06), 0.2);
+    assert!(inside.distance(drift) < 1e-5, "inside={:?}", inside);
+
+    assert_eq!(solver.sample_velocity(Vec3::new(-2.0, 0.0, 0.0), 0.2), Vec3::ZERO);
+
+    // A batch answers every point from one grid build and leaves the
+    // predicted scratch buffer alone
+    solver.particles.predicted[0] = Vec3::splat(-7.0);
+    let points = [Vec3::new(1.04, 1.05, 1.06), Vec3::new(3.0, 3.0, 3.0), Vec3::new(-2.0, 0.0, 0.0)];
+    let batch = solver.sample_velocities(&points, 0.2);
+    assert_eq!(batch.len(), 3);
+    assert_eq!(batch[0], inside);
+    assert!(batch[1].distance(Vec3::new(-5.0, 0.0, 0.0)) < 1e-5);
+    assert_eq!(batch[2], Vec3::ZERO);
+    assert_eq!(solver.particles.predicted[0], Vec3::splat(-7.0));
+}
+
+/// Steps on the XPBD path until particles dropped 0.8 above their shape targets
+/// are within 0.01 on average, and the summed per-step overshoot below the targets.
+fn shape_formation(as_constraint: bool) -> (usize, f32) {
+    let mut solver = Solver::new(64);
+    solver.config.collisions_enabled = true;
+    solver.config.shape_as_constraint = as_constraint;
+    solver.config.shape_strength = 1.0;
+    // Soft enough that the spring force on top of the constraint rings
+    solver.config.shape_compliance_at_one = 0.01;
+    for i in 0..64 {
+        solver.particles.radius[i] = 0.005;
+        solver.particles.hash[i] = 1.0; // full shape affinity
+    }
+    // Targets stay fixed while time stays at zero
+    solver.step(0.016, 0.0);
+    for i in 0..64 {
+        solver.particles.position[i] = solver.particles.target_pos[i] + Vec3::new(0.0, 0.8, 0.0);
+        solver.particles.velocity[i] = Vec3::ZERO;
+    }
+
+    let mut formed_at = usize::MAX;
+    let mut overshoot = 0.0;
+    for step in 0..150 {
+        solver.step(0.016, 0.0);
+        let p = &solver.particles;
+        let error = (0..64).map(|i| p.position[i].distance(p.target_pos[i])).sum::<f32>() / 64.0;
+        if error < 0.01 && formed_at == usize::MAX {
+            formed_at = step;
+        }
+        overshoot += (0..64).map(|i| p.target_pos[i].y - p.position[i].y).fold(0.0, f32::max);
+    }
+    (formed_at, overshoot)
+}
+
+#[test]
+fn test_shape_as_constraint_forms_without_overshoot() {
+    let (force_steps, force_overshoot) = shape_formation(false);
+    let (constraint_steps, constraint_overshoot) = shape_formation(true);
+    assert!(force_steps < 150, "force path should still form the shape");
+    assert!(constraint_steps < 150, "constraint path should still form the shape");
+    assert!(force_overshoot > 0.0, "the spring force should ring past the targets");
+    assert!(
+        constraint_overshoot < force_overshoot * 0.5,
+        "constraint overshoot {} vs force {}", constraint_overshoot, force_overshoot
+    );
+}
+
+#[test]
+fn test_lerp_output_between_snapshots() {
+    let mut solver = Solver::new(50);
+    for i in 0..50 {
+        solver.particles.velocity[i] = Vec3::new(1.0, -0.5, 0.25);
+    }
+    let prev = solver.snapshot();
+    solver.step(0.016, 0.0);
+
+    let mut out = vec![Vec3::ZERO; 50];
+    solver.lerp_output(0.0, &prev, &mut out);
+    assert_eq!(out, prev.position);
+    solver.lerp_output(1.0, &prev, &mut out);
+    assert_eq!(out, solver.particles.position);
+    solver.lerp_output(0.5, &prev, &mut out);
+    for (i, p) in out.iter().enumerate() {
+        let mid = (prev.position[i] + solver.particles.position[i]) * 0.5;
+        assert!(p.distance(mid) < 1e-6, "particle {}", i);
+    }
+}
+
+#[test]
+fn test_solver_quality_clamps_to_limits() {
+    let mut solver = Solver::new(10);
+    solver.config.collisions_enabled = true;
+    assert!(solver.set_solver_quality(1000, 3));
+    assert_eq!(solver.config.substeps, MAX_SUBSTEPS);
+    assert_eq!(solver.adaptive_quality.substeps(), MAX_SUBSTEPS);
+    assert_eq!(solver.adaptive_quality.iterations(), 3);
+
+    assert!(solver.set_solver_quality(0, 0));
+    assert_eq!(solver.adaptive_quality.substeps(), 1);
+    assert_eq!(solver.adaptive_quality.iterations(), 1);
+    solver.step(0.016, 0.0);
+    assert_eq!(solver.last_stats.substeps, 1);
+
+    assert!(!solver.set_solver_quality(8, MAX_SOLVER_ITERATIONS));
+    assert_eq!(solver.config.solver_iterations, MAX_SOLVER_ITERATIONS);
+}
+
+#[test]
+fn test_phase_timings_add_up_to_total() {
+    let mut solver = Solver::new(2000);
+    solver.config.collisions_enabled = true;
+    for i in 0..500 {
+        solver.particles.phase[i] = Phase::Fluid;
+    }
+    solver.step(0.016, 0.0);
+
+    let s = solver.last_stats;
+    let phases = s.forces_ms + s.grid_ms + s.contacts_ms + s.constraints_ms + s.fluid_ms;
+    assert!(s.total_ms > 0.0, "native clock should measure the step");
+    assert!(s.constraints_ms > 0.0 && s.fluid_ms > 0.0, "{} {}", s.constraints_ms, s.fluid_ms);
+    assert!(
+        (phases - s.total_ms).abs() <= s.total_ms * 0.01 + 0.01,
+        "phases {} vs total {}",
+        phases,
+        s.total_ms
+    );
+}
+
+#[test]
+fn test_randomize_radii_in_range_and_reproducible() {
+    let mut a = Solver::new(200);
+    let mut b = Solver::new(200);
+    a.randomize_radii(7, 0.02, 0.2);
+    b.randomize_radii(7, 0.02, 0.2);
+    assert_eq!(a.particles.radius, b.particles.radius);
+    assert!(a.particles.radius.iter().all(|&r| (0.02..=0.2).contains(&r)));
+    let radii = &a.particles.radius;
+    let lo = radii.iter().copied().fold(f32::MAX, f32::min);
+    let hi = radii.iter().copied().fold(0.0, f32::max);
+    assert!(lo < 0.05 && hi > 0.15, "radii should spread over the range: {}..{}", lo, hi);
+
+    b.randomize_radii(8, 0.02, 0.2);
+    assert_ne!(a.particles.radius, b.particles.radius, "another seed gives other radii");
+
+    // Masses follow the new radii when coupled
+    b.couple_mass_to_radius(true, 1000.0);
+    b.randomize_radii(8, 0.02, 0.2);
+    let (small, large) = if b.particles.radius[0] < b.particles.radius[1] { (0, 1) } else { (1, 0) };
+    assert!(b.particles.inv_mass[small] > b.particles.inv_mass[large]);
+
+    // `reinitialize` reassigns radii from the configured range
+    a.config.radius_min = 0.3;
+    a.config.radius_max = 0.4;
+    a.reinitialize(0);
+    assert!(a.particles.radius.iter().all(|&r| (0.3..=0.4).contains(&r)));
+}
+
+#[test]
+fn test_large_random_radii_still_collide() {
+    // Radii of 0.3 put touching centers more than a default 0.2 cell apart
+    let mut solver = Solver::new(2);
+    solver.randomize_radii(1, 0.3, 0.3);
+    for (i, x) in [-0.25, 0.25].into_iter().enumerate() {
+        solver.particles.position[i] = Vec3::new(x, 0.0, 0.0);
+        solver.particles.predicted[i] = Vec3::new(x, 0.0, 0.0);
+    }
+    solver.solve_collision_constraints(0, 1.0 / 240.0);
+    assert!(solver.grid_cell_size() >= 0.6);
+    assert_eq!(solver.last_stats.contact_count, 1);
+}
+
+#[test]
+fn test_damp_all_velocities_scales_speed_only() {
+    let mut solver = Solver::new(32);
+    for (i, vel) in solver.particles.velocity.iter_mut().enumerate() {
+        *vel = Vec3::new(1.0 + i as f32, -2.0, 0.5);
+    }
+    let positions = solver.particles.position.clone();
+    let speeds: Vec<f32> = solver.particles.velocity.iter().map(|v| v.length()).collect();
+
+    solver.damp_all_velocities(0.5);
+    for (vel, speed) in solver.particles.velocity.iter().zip(&speeds) {
+        assert!((vel.length() - speed * 0.5).abs() < 1e-5);
+    }
+    assert_eq!(solver.particles.position, positions);
+
+    solver.damp_all_velocities(0.0);
+    assert!(solver.particles.velocity.iter().all(|&v| v == Vec3::ZERO));
+    assert_eq!(solver.particles.position, positions);
+}
+
+/// Mean distance to a static 10x10 custom point cloud over steps 300..400,
+/// with the given close-range and snap distances.
+fn mean_shape_error(close_distance: f32, snap_distance: f32) -> f32 {
+    let mut solver = Solver::new(100);
+    let points: Vec<Vec3> = (0..100)
+        .map(|i| Vec3::new((i % 10) as f32 * 0.15 - 0.7, (i / 10) as f32 * 0.15 - 0.7, 0.5))
+        .collect();
+    solver.set_custom_shape_targets(&points);
+    solver.shape_params.shape_a = CUSTOM_SHAPE;
+    solver.shape_params.shape_b = CUSTOM_SHAPE;
+    solver.config.shape_strength = 1.0;
+    solver.config.shape_close_distance = close_distance;
+    solver.config.shape_snap_distance = snap_distance;
+
+    let mut total = 0.0;
+    for step in 0..400 {
+        solver.step(0.016, step as f32 * 0.016);
+        if step >= 300 {
+            let p = &solver.particles.position;
+            total += p.iter().zip(&points).map(|(p, t)| p.distance(*t)).sum::<f32>() / 100.0;
+        }
+    }
+    total / 100.0
+}
+
+#[test]
+fn test_shape_snap_distances_control_formation_tightness() {
+    let default = mean_shape_error(0.5, 0.15);
+    // Loosely attracted particles hover just outside the default ranges;
+    // wider ranges pull them in, zero ranges drop the close-range terms
+    let wide = mean_shape_error(2.0, 0.6);
+    let off = mean_shape_error(0.0, 0.0);
+    assert!(wide < default - 0.01, "wide {} vs default {}", wide, default);
+    assert!(off > default, "off {} vs default {}", off, default);
+}
+
+#[test]
+fn test_set_velocities_checks_length() {
+    let mut solver = Solver::new(4);
+    let before = solver.particles.velocity.clone();
+    assert!(!solver.set_velocities(&[1.0; 11]));
+    assert!(!solver.set_velocities(&[1.0; 13]));
+    assert_eq!(solver.particles.velocity, before, "rejected input must not write");
+
+    let xyz: Vec<f32> = (0..12).map(|k| k as f32 * 0.5 - 2.0).collect();
+    assert!(solver.set_velocities(&xyz));
+    for (i, vel) in solver.particles.velocity.iter().enumerate() {
+        assert_eq!(*vel, Vec3::new(xyz[3 * i], xyz[3 * i + 1], xyz[3 * i + 2]));
     }
 }
@@ -1,5 +1,5 @@
 use wasm_bindgen::prelude::*;
-use xpbd_core::solver::Solver;
+use xpbd_core::solver::{IntegrationPath, Solver, StateSnapshot};
 
 /// GPU-compatible particle struct: 32 bytes, matches WGSL Particle
 #[repr(C)]
@@ -15,6 +15,13 @@ struct GpuParticle {
 pub struct PhysicsWorld {
     solver: Solver,
     gpu_buffer: Vec<GpuParticle>,
+    /// The integration-path switch warning has already been logged
+    path_switch_warned: bool,
+    /// State before the latest step, kept while output interpolation is enabled
+    interp_prev: Option<StateSnapshot>,
+    /// Wall time of the latest `step` call, including GPU output; the solver's
+    /// own `last_stats.total_ms` covers the physics step alone
+    wrapper_ms: f32,
 }
 
 #[wasm_bindgen]
@@ -25,7 +32,8 @@ impl PhysicsWorld {
             &format!("WASM PhysicsWorld created: {} particles", particle_count).into()
         );
 
-        let solver = Solver::new(particle_count);
+        let mut solver = Solver::new(particle_count);
+        solver.set_clock(js_sys::Date::now); // `Instant` is unavailable in the browser
         let gpu_buffer = vec![GpuParticle {
             position: [0.0; 3],
             radius: 0.05,
@@ -33,7 +41,13 @@ impl PhysicsWorld {
             _pad: 0.0,
         }; particle_count];
 
-        let mut world = PhysicsWorld { solver, gpu_buffer };
+        let mut world = PhysicsWorld {
+            solver,
+            gpu_buffer,
+            path_switch_warned: false,
+            interp_prev: None,
+            wrapper_ms: 0.0,
+        };
         world.write_gpu_output();
         world
     }
@@ -41,14 +55,63 @@ impl PhysicsWorld {
     #[wasm_bindgen]
     pub fn step(&mut self, dt: f32, time: f32) -> f32 {
         let start = js_sys::Date::now();
+        self.capture_interp_prev();
+        let live = self.solver.active_indices().len();
         self.solver.step(dt, time);
+        if self.solver.active_indices().len() < live {
+            self.hide_dead_slots(); // lifetimes expired
+        }
+        self.warn_on_path_switch();
         self.write_gpu_output();
         let elapsed = (js_sys::Date::now() - start) as f32;
-        self.solver.last_stats.total_ms = elapsed;
+        self.wrapper_ms = elapsed;
         self.solver.adaptive_quality.update(elapsed);
         elapsed
     }
 
+    /// Advance `n` steps of `dt` from `start_time` in one call (offline rendering,
+    /// benchmarks). Output buffers are written once at the end; adaptive quality
+    /// is not updated, and interpolation blends across the last step only.
+    /// Returns the elapsed time in ms.
+    #[wasm_bindgen]
+    pub fn step_many(&mut self, n: u32, dt: f32, start_time: f32) -> f32 {
+        let start = js_sys::Date::now();
+        let live = self.solver.active_indices().len();
+        if n > 0 {
+            self.solver.step_n(n - 1, dt, start_time);
+            self.capture_interp_prev();
+            self.solver.step_n(1, dt, start_time + (n - 1) as f32 * dt);
+        }
+        if self.solver.active_indices().len() < live {
+            self.hide_dead_slots();
+        }
+        self.warn_on_path_switch();
+        self.write_gpu_output();
+        (js_sys::Date::now() - start) as f32
+    }
+
+    /// Keep the pre-step state on every step so `write_interpolated_output` can
+    /// blend between fixed physics steps.
+    #[wasm_bindgen]
+    pub fn set_output_interpolation(&mut self, enabled: bool) {
+        self.interp_prev = enabled.then(|| self.solver.snapshot());
+    }
+
+    /// Rewrite GPU buffer positions between the previous and latest step, with
+    /// alpha = `remainder / fixed_dt` from a fixed-timestep accumulator.
+    /// Without interpolation enabled this writes the latest state.
+    #[wasm_bindgen]
+    pub fn write_interpolated_output(&mut self, remainder: f32, fixed_dt: f32) {
+        self.write_gpu_output();
+        let Some(prev) = &self.interp_prev else { return };
+        let alpha = if fixed_dt > 0.0 { (remainder / fixed_dt).clamp(0.0, 1.0) } else { 1.0 };
+        let mut positions = vec![glam::Vec3::ZERO; self.solver.particles.count];
+        self.solver.lerp_output(alpha, prev, &mut positions);
+        for (gpu, pos) in self.gpu_buffer.iter_mut().zip(&positions) {
+            gpu.position = pos.to_array();
+        }
+    }
+
     #[wasm_bindgen]
     pub fn get_gpu_buffer_ptr(&self) -> *const f32 {
         self.gpu_buffer.as_ptr() as *const f32
@@ -80,6 +143,13 @@ impl PhysicsWorld {
         self.solver.shape_params.speed_multiplier = speed_multiplier;
     }
 
+    /// Point cloud for shape id 14 as flat xyz triples; particle i targets point i (wrapping).
+    #[wasm_bindgen]
+    pub fn set_custom_targets(&mut self, points: &[f32]) {
+        let points: Vec<glam::Vec3> = points.chunks_exact(3).map(glam::Vec3::from_slice).collect();
+        self.solver.set_custom_shape_targets(&points);
+    }
+
     #[wasm_bindgen]
     pub fn set_shape_rotations(&mut self, rot_a: &[f32], rot_b: &[f32]) {
         if let (Ok(a), Ok(b)) = (rot_a.try_into(), rot_b.try_into()) {
@@ -88,6 +158,14 @@ impl PhysicsWorld {
         }
     }
 
+    /// Spin both shape rotations about `axis` at `rate` radians per second (0 = static).
+    #[wasm_bindgen]
+    pub fn set_shape_spin(&mut self, axis_x: f32, axis_y: f32, axis_z: f32, rate: f32) {
+        let omega = glam::Vec3::new(axis_x, axis_y, axis_z).normalize_or_zero() * rate;
+        self.solver.shape_params.rot_a_velocity = omega;
+        self.solver.shape_params.rot_b_velocity = omega;
+    }
+
     #[wasm_bindgen]
     pub fn set_fractal_seeds(&mut self, seed_a: &[f32], seed_b: &[f32]) {
         if let (Ok(a), Ok(b)) = (seed_a.try_into(), seed_b.try_into()) {
@@ -104,6 +182,37 @@ impl PhysicsWorld {
         self.solver.shape_params.audio_energy = energy;
     }
 
+    /// Speed the simulation up with `audio_energy`: `speed * (1 + coupling * energy)` (0 = off).
+    #[wasm_bindgen]
+    pub fn set_audio_speed_coupling(&mut self, coupling: f32) {
+        self.solver.config.audio_speed_coupling = coupling;
+    }
+
+    /// Equalizer treble sparkle: speed and strength multipliers (1, 1 = default; amount 0 = off).
+    #[wasm_bindgen]
+    pub fn set_audio_sparkle(&mut self, frequency: f32, amount: f32) {
+        self.solver.shape_params.sparkle_frequency = frequency;
+        self.solver.shape_params.sparkle_amount = amount.max(0.0);
+    }
+
+    /// Equalizer zone split points: normalized bar positions where bass ends and treble starts.
+    #[wasm_bindgen]
+    pub fn set_eq_zones(&mut self, bass_end: f32, treble_start: f32) {
+        self.solver.shape_params.eq_zones = [bass_end, treble_start];
+    }
+
+    /// Escape-time iteration cap for the fractal shape (48 = default; higher = finer detail).
+    #[wasm_bindgen]
+    pub fn set_fractal_iterations(&mut self, iterations: u32) {
+        self.solver.shape_params.fractal_iterations = iterations.max(1);
+    }
+
+    /// Depth of the 2D-lifted shapes (superformula, rose, polygon): 0 = flat, 1 = default.
+    #[wasm_bindgen]
+    pub fn set_shape_thickness(&mut self, thickness: f32) {
+        self.solver.shape_params.shape_thickness = thickness.max(0.0);
+    }
+
     #[wasm_bindgen]
     pub fn set_pointer(
         &mut self,
@@ -125,9 +234,113 @@ impl PhysicsWorld {
             pressing,
             pulse,
             view_dir: glam::Vec3::new(view_dir_x, view_dir_y, view_dir_z),
+            repel_swirl: self.solver.pointer_params.repel_swirl,
+            repel_damping: self.solver.pointer_params.repel_damping,
         };
     }
 
+    /// Collision-only mode when false: skip flow, shape attraction, free-flight and audio forces.
+    #[wasm_bindgen]
+    pub fn set_artistic_forces_enabled(&mut self, enabled: bool) {
+        self.solver.config.artistic_forces_enabled = enabled;
+    }
+
+    /// Gravity used when artistic forces are disabled.
+    #[wasm_bindgen]
+    pub fn set_gravity(&mut self, x: f32, y: f32, z: f32) {
+        self.solver.config.gravity = glam::Vec3::new(x, y, z);
+    }
+
+    /// Tilt gravity toward the pointer position (from the scene center) instead of down.
+    #[wasm_bindgen]
+    pub fn set_gravity_follows_pointer(&mut self, enabled: bool) {
+        self.solver.config.gravity_follows_pointer = enabled;
+    }
+
+    /// Extra damping of particles the pointer was acting on when released (0 = off), fading over `duration` seconds.
+    #[wasm_bindgen]
+    pub fn set_pointer_release_damping(&mut self, damping: f32, duration: f32) {
+        self.solver.config.pointer_release_damping = damping.clamp(0.0, 1.0);
+        self.solver.config.pointer_release_duration = duration.max(0.0);
+    }
+
+    /// Flow-phase damping: linear per-step factors at loose/calm shape strength, plus quadratic drag.
+    #[wasm_bindgen]
+    pub fn set_damping(&mut self, linear_min: f32, linear_max: f32, quadratic_drag: f32) {
+        self.solver.config.linear_damping_min = linear_min;
+        self.solver.config.linear_damping_max = linear_max;
+        self.solver.config.quadratic_drag = quadratic_drag;
+    }
+
+    /// Distances from the shape target where the close-range pull and the final snap begin.
+    #[wasm_bindgen]
+    pub fn set_shape_snap_distances(&mut self, close_distance: f32, snap_distance: f32) {
+        self.solver.config.shape_close_distance = close_distance.max(0.0);
+        self.solver.config.shape_snap_distance = snap_distance.max(0.0);
+    }
+
+    /// Toggle the quadratic drag term independently of the flow forces (off for n-body orbits).
+    #[wasm_bindgen]
+    pub fn set_quadratic_drag_enabled(&mut self, enabled: bool) {
+        self.solver.config.quadratic_drag_enabled = enabled;
+    }
+
+    /// Drag multiplier for one phase (0..=6); >1 damps harder (thick fluid), <1 lighter (gas).
+    #[wasm_bindgen]
+    pub fn set_phase_drag(&mut self, phase: u8, multiplier: f32) {
+        if let Some(slot) = self.solver.config.phase_drag.get_mut(phase as usize) {
+            *slot = multiplier;
+        }
+    }
+
+    /// Restitution multiplier for one phase (0..=6); a contact uses the smaller of its pair's.
+    #[wasm_bindgen]
+    pub fn set_phase_restitution(&mut self, phase: u8, multiplier: f32) {
+        if let Some(slot) = self.solver.config.phase_restitution.get_mut(phase as usize) {
+            *slot = multiplier.max(0.0);
+        }
+    }
+
+    /// Bounce particle contacts by their combined restitution (off by default).
+    #[wasm_bindgen]
+    pub fn set_contact_restitution(&mut self, enabled: bool) {
+        self.solver.config.contact_restitution = enabled;
+    }
+
+    /// Scale the wandering vortex in the flow field (0.35 = default, 0 = pure curl noise).
+    #[wasm_bindgen]
+    pub fn set_flow_vortex_strength(&mut self, strength: f32) {
+        self.solver.config.flow_vortex_strength = strength;
+    }
+
+    /// Flow z-wave amplitude (0.35 = default, 0 = off) and frequency multiplier (1 = default).
+    #[wasm_bindgen]
+    pub fn set_flow_z_wave(&mut self, amplitude: f32, frequency: f32) {
+        self.solver.config.flow_z_wave_amplitude = amplitude;
+        self.solver.config.flow_z_wave_frequency = frequency;
+    }
+
+    /// Strength of the built-in flow gravity (0.04 = default, 0 = weightless drift).
+    #[wasm_bindgen]
+    pub fn set_flow_gravity(&mut self, strength: f32) {
+        self.solver.config.flow_gravity = strength;
+    }
+
+    /// Choose which phases feel shape attraction: bit `n` enables `Phase` value `n`.
+    #[wasm_bindgen]
+    pub fn set_shape_attract_phases(&mut self, mask: u32) {
+        for (phase, enabled) in self.solver.config.shape_attract_phases.iter_mut().enumerate() {
+            *enabled = mask & (1 << phase) != 0;
+        }
+    }
+
+    /// Tune the repel mode: tangential swirl magnitude and per-step velocity scale.
+    #[wasm_bindgen]
+    pub fn set_pointer_repel(&mut self, swirl: f32, damping: f32) {
+        self.solver.pointer_params.repel_swirl = swirl;
+        self.solver.pointer_params.repel_damping = damping;
+    }
+
     /// Enable or disable adaptive quality control.
     #[wasm_bindgen]
     pub fn set_adaptive_quality(&mut self, enabled: bool, budget_ms: f32) {
@@ -137,7 +350,10 @@ impl PhysicsWorld {
         }
     }
 
-    /// Get current step statistics as [total_ms, substeps, iterations, particle_count, contact_count].
+    /// Get current step statistics as
+    /// [total_ms, substeps, iterations, particle_count, contact_count, iterations_used, wrapper_ms].
+    /// `total_ms` is the solver step (the sum of `get_phase_timings`); `wrapper_ms`
+    /// is the whole `step` call including GPU output.
     #[wasm_bindgen]
     pub fn get_step_stats(&self) -> Vec<f32> {
         let s = &self.solver.last_stats;
@@ -147,9 +363,19 @@ impl PhysicsWorld {
             s.iterations as f32,
             s.particle_count as f32,
             s.contact_count as f32,
+            s.iterations_used as f32,
+            self.wrapper_ms,
         ]
     }
 
+    /// Get the latest step's time per phase in milliseconds as
+    /// [forces, grid, contacts, constraints, fluid].
+    #[wasm_bindgen]
+    pub fn get_phase_timings(&self) -> Vec<f32> {
+        let s = &self.solver.last_stats;
+        vec![s.forces_ms, s.grid_ms, s.contacts_ms, s.constraints_ms, s.fluid_ms]
+    }
+
     #[wasm_bindgen]
     pub fn set_solver_config(
         &mut self,
@@ -157,12 +383,19 @@ impl PhysicsWorld {
         solver_iterations: u32,
         collisions_enabled: bool,
     ) {
-        self.solver.config.substeps = substeps;
-        self.solver.config.solver_iterations = solver_iterations;
+        if self.solver.set_solver_quality(substeps, solver_iterations) {
+            web_sys::console::warn_1(
+                &format!(
+                    "Solver config clamped: {} substeps, {} iterations requested, using {} and {}",
+                    substeps,
+                    solver_iterations,
+                    self.solver.config.substeps,
+                    self.solver.config.solver_iterations
+                )
+                .into()
+            );
+        }
         self.solver.config.collisions_enabled = collisions_enabled;
-        // Sync adaptive quality max values
-        self.solver.adaptive_quality.max_substeps = substeps;
-        self.solver.adaptive_quality.max_iterations = solver_iterations;
     }
 
     #[wasm_bindgen]
@@ -191,11 +424,265 @@ impl PhysicsWorld {
         self.solver.create_rigid_body(start_idx as usize, count as usize, stiffness);
     }
 
+    #[wasm_bindgen]
+    pub fn create_rigid_body_from_indices(&mut self, indices: &[u32], stiffness: f32) {
+        self.solver.create_rigid_body_from_indices(indices, stiffness);
+    }
+
+    /// Every particle's state in one readback for saving, `xpbd_core::particle::FRAME_STRIDE`
+    /// (9) floats each: `[pos.xyz, vel.xyz, radius, phase, charge]`.
+    #[wasm_bindgen]
+    pub fn capture_full_frame(&self) -> Vec<f32> {
+        self.solver.particles.capture_frame()
+    }
+
+    /// Distance constraints for GPU solving: [i, j, rest_length, compliance] per
+    /// constraint, floats as raw bits (read the buffer as u32, bitcast to f32).
+    #[wasm_bindgen]
+    pub fn pack_distance_constraints(&self) -> Vec<u32> {
+        self.solver.pack_distance_constraints()
+    }
+
+    /// Bending constraints for GPU solving: [i, j, k, l, rest_angle, compliance]
+    /// per constraint, floats as raw bits.
+    #[wasm_bindgen]
+    pub fn pack_bending_constraints(&self) -> Vec<u32> {
+        self.solver.pack_bending_constraints()
+    }
+
+    /// Rigid-body group index per particle (-1 = not in a group), for UI highlighting.
+    #[wasm_bindgen]
+    pub fn get_shape_group_membership(&self) -> Vec<i32> {
+        self.solver.shape_group_membership()
+    }
+
+    /// Snap rigid body `group_idx` back to its rest shape at its current center of mass.
+    #[wasm_bindgen]
+    pub fn reset_rigid_body(&mut self, group_idx: usize) {
+        self.solver.reset_rigid_body(group_idx);
+        self.write_gpu_output();
+    }
+
+    /// Add swirling velocity (`angular_velocity` xyz) to particles within `radius` of
+    /// `center` (xyz). Returns how many particles were stirred (0 unless both have length 3).
+    #[wasm_bindgen]
+    pub fn stir(&mut self, center: &[f32], radius: f32, angular_velocity: &[f32]) -> u32 {
+        let (Ok(center), Ok(omega)) = (center.try_into(), angular_velocity.try_into()) else {
+            return 0;
+        };
+        self.solver.stir(glam::Vec3::from_array(center), radius, glam::Vec3::from_array(omega)) as u32
+    }
+
+    /// Spin rigid body `group_idx` about axis (x, y, z) at `magnitude` radians per second.
+    #[wasm_bindgen]
+    pub fn apply_rigid_torque(&mut self, group_idx: usize, x: f32, y: f32, z: f32, magnitude: f32) {
+        self.solver.apply_rigid_torque(group_idx, glam::Vec3::new(x, y, z), magnitude);
+    }
+
+    /// Rigid body orientation as a quaternion [x, y, z, w] (identity if unsolved or out of range).
+    #[wasm_bindgen]
+    pub fn rigid_body_rotation(&self, group_idx: usize) -> Vec<f32> {
+        self.solver.rigid_body_rotation(group_idx).to_vec()
+    }
+
+    /// Convex hull of a rigid body as [vertex_count, x, y, z, ..., i0, i1, i2, ...]
+    /// (outward counter-clockwise triangles); [0] for flat bodies or bad indices.
+    #[wasm_bindgen]
+    pub fn get_rigid_body_hull(&self, group_idx: usize) -> Vec<f32> {
+        let (vertices, triangles) = self.solver.rigid_body_hull(group_idx);
+        let mut out = Vec::with_capacity(1 + vertices.len() * 3 + triangles.len() * 3);
+        out.push(vertices.len() as f32);
+        out.extend(vertices.iter().flat_map(|v| v.to_array()));
+        out.extend(triangles.iter().flatten().map(|&i| i as f32));
+        out
+    }
+
+    /// Rigid body transform as [com_x, com_y, com_z, qx, qy, qz, qw] (empty if unavailable).
+    #[wasm_bindgen]
+    pub fn rigid_body_transform(&self, group_idx: usize) -> Vec<f32> {
+        match self.solver.rigid_body_transform(group_idx) {
+            Some((com, q)) => vec![com.x, com.y, com.z, q[0], q[1], q[2], q[3]],
+            None => Vec::new(),
+        }
+    }
+
+    /// Kill particles: their slots stay but stop simulating and are hidden (zero radius)
+    /// in the GPU buffer, which from then on only rewrites live slots.
+    #[wasm_bindgen]
+    pub fn kill_particles(&mut self, indices: &[u32]) {
+        self.solver.kill_particles(indices);
+        for &idx in indices {
+            if let Some(gpu) = self.gpu_buffer.get_mut(idx as usize) {
+                gpu.radius = 0.0;
+                gpu.velocity = [0.0; 3];
+            }
+        }
+    }
+
+    /// Spawn a particle at `pos` with velocity `vel` (both xyz), reusing a killed slot
+    /// else growing the set, and return its index (u32::MAX, nothing spawned, unless
+    /// both have length 3). Its velocity gains `spawn_flow_inheritance` times the
+    /// ambient flow at the spawn point.
+    #[wasm_bindgen]
+    pub fn spawn_particle(&mut self, pos: &[f32], vel: &[f32], time: f32) -> u32 {
+        let (Ok(pos), Ok(vel)) = (pos.try_into(), vel.try_into()) else {
+            return u32::MAX;
+        };
+        let i = self.solver.spawn_particle(
+            glam::Vec3::from_array(pos),
+            glam::Vec3::from_array(vel),
+            time,
+        );
+        if i >= self.gpu_buffer.len() {
+            self.gpu_buffer.resize(i + 1, GpuParticle {
+                position: [0.0; 3],
+                radius: 0.05,
+                velocity: [0.0; 3],
+                _pad: 0.0,
+            });
+        }
+        self.write_gpu_output();
+        i as u32
+    }
+
+    /// Reorder particle slots by grid cell for locality. Indices change; use
+    /// `get_particle_user_id` to follow logical particles across the pass.
+    #[wasm_bindgen]
+    pub fn reorder_by_cell(&mut self) {
+        self.solver.reorder_by_cell();
+        self.hide_dead_slots();
+        self.write_gpu_output();
+    }
+
+    /// Stable user id of the particle at `index` (u32::MAX if out of range).
+    #[wasm_bindgen]
+    pub fn get_particle_user_id(&self, index: u32) -> u32 {
+        self.solver.particles.user_id.get(index as usize).copied().unwrap_or(u32::MAX)
+    }
+
+    /// Assign a stable user id to the particle at `index`; it follows the particle across reorders.
+    #[wasm_bindgen]
+    pub fn set_particle_user_id(&mut self, index: u32, id: u32) {
+        if let Some(slot) = self.solver.particles.user_id.get_mut(index as usize) {
+            *slot = id;
+        }
+    }
+
+    /// Fraction of the ambient flow added to spawned particles' velocity (0 = off).
+    #[wasm_bindgen]
+    pub fn set_spawn_flow_inheritance(&mut self, factor: f32) {
+        self.solver.config.spawn_flow_inheritance = factor;
+    }
+
+    /// Lifetime in seconds of spawned particles; they are killed once older (0 = immortal).
+    #[wasm_bindgen]
+    pub fn set_spawn_lifetime(&mut self, seconds: f32) {
+        self.solver.config.spawn_lifetime = seconds.max(0.0);
+    }
+
+    /// Lifetime in seconds for particles [start, start + count), counted from their current age.
+    #[wasm_bindgen]
+    pub fn set_particle_lifetime(&mut self, start: u32, count: u32, seconds: f32) {
+        let end = ((start + count) as usize).min(self.solver.particles.count);
+        for i in (start as usize)..end {
+            self.solver.particles.lifetime[i] = seconds.max(0.0);
+        }
+    }
+
+    /// Number of live (not killed) particles.
+    #[wasm_bindgen]
+    pub fn active_particle_count(&self) -> usize {
+        self.solver.active_indices().len()
+    }
+
+    /// Pin particles in place, remembering their inverse mass for `unfreeze_particles`.
+    #[wasm_bindgen]
+    pub fn freeze_particles(&mut self, indices: &[u32]) {
+        self.solver.freeze_particles(indices);
+    }
+
+    /// Release particles pinned by `freeze_particles`.
+    #[wasm_bindgen]
+    pub fn unfreeze_particles(&mut self, indices: &[u32]) {
+        self.solver.unfreeze_particles(indices);
+    }
+
     #[wasm_bindgen]
     pub fn clear_constraints(&mut self) {
         self.solver.clear_constraints();
     }
 
+    /// Grow or shrink the particle count, keeping surviving particles' state
+    /// (unlike `resize`, which rebuilds the world from scratch).
+    #[wasm_bindgen]
+    pub fn resize_preserving(&mut self, new_count: usize) {
+        self.solver.resize(new_count);
+        self.gpu_buffer.resize(new_count, GpuParticle {
+            position: [0.0; 3],
+            radius: 0.05,
+            velocity: [0.0; 3],
+            _pad: 0.0,
+        });
+        self.write_gpu_output();
+    }
+
+    /// Merge another world's particles and constraints into this one; false if
+    /// the boundary shapes differ or the particle count would overflow.
+    #[wasm_bindgen]
+    pub fn append_world(&mut self, other: &PhysicsWorld) -> bool {
+        if self.solver.append(&other.solver).is_err() {
+            return false;
+        }
+        self.gpu_buffer.resize(self.solver.particles.count, GpuParticle {
+            position: [0.0; 3],
+            radius: 0.05,
+            velocity: [0.0; 3],
+            _pad: 0.0,
+        });
+        self.write_gpu_output();
+        true
+    }
+
+    /// Blast particles outward, then ramp shape attraction back over `reform_after` seconds.
+    #[wasm_bindgen]
+    pub fn trigger_scatter_reform(&mut self, scatter_strength: f32, reform_after: f32) {
+        self.solver.trigger_scatter_reform(scatter_strength, reform_after);
+    }
+
+    /// Ease `shape_strength` to `target` over `duration` seconds of simulated time.
+    #[wasm_bindgen]
+    pub fn animate_shape_strength(&mut self, target: f32, duration: f32) {
+        self.solver.animate_shape_strength(target, duration);
+    }
+
+    /// Lay particles out as a spiral galaxy disk orbiting a central mass (the central attractor).
+    #[wasm_bindgen]
+    pub fn init_galaxy(&mut self, arms: u32, radius: f32, thickness: f32, rotation_velocity: f32, seed: u32) {
+        self.solver.init_galaxy(arms, radius, thickness, rotation_velocity, seed);
+        self.write_gpu_output();
+    }
+
+    /// Set the spiral ring used by `reinitialize`: turn count and inner/outer radius.
+    #[wasm_bindgen]
+    pub fn set_init_spiral(&mut self, turns: f32, radius_min: f32, radius_max: f32) {
+        self.solver.config.init_spiral_turns = turns;
+        self.solver.config.init_spiral_radius = [radius_min, radius_max];
+    }
+
+    /// Radius range used by `reinitialize` and for slots added by a resize.
+    #[wasm_bindgen]
+    pub fn set_radius_range(&mut self, min: f32, max: f32) {
+        self.solver.config.radius_min = min;
+        self.solver.config.radius_max = max;
+    }
+
+    /// Random radii uniform in [min, max], reproducible per seed.
+    #[wasm_bindgen]
+    pub fn randomize_radii(&mut self, seed: u32, min: f32, max: f32) {
+        self.solver.randomize_radii(seed as u64, min, max);
+        self.write_gpu_output();
+    }
+
     #[wasm_bindgen]
     pub fn reinitialize(&mut self, seed: u32) {
         self.solver.reinitialize(seed);
@@ -216,23 +703,70 @@ impl PhysicsWorld {
         self.solver.config.smoothing_radius = smoothing_radius;
     }
 
+    /// PBF density passes per solver iteration, independent of contact iterations.
+    #[wasm_bindgen]
+    pub fn set_fluid_inner_iterations(&mut self, iterations: u32) {
+        self.solver.config.fluid_inner_iterations = iterations;
+    }
+
+    /// Cap on neighbors per fluid particle in density kernels (0 = no cap), bounding cost when compressed.
+    #[wasm_bindgen]
+    pub fn set_max_fluid_neighbors(&mut self, max: u32) {
+        self.solver.config.max_fluid_neighbors = max as usize;
+    }
+
+    /// Let Free (spray) particles carry a fluid density so they rejoin the fluid smoothly.
+    #[wasm_bindgen]
+    pub fn set_free_contributes_to_density(&mut self, enabled: bool) {
+        self.solver.config.free_contributes_to_density = enabled;
+    }
+
+    /// Bounciness of fluid particles at the boundary sphere (0 = stick, adds splash spray when > 0).
+    #[wasm_bindgen]
+    pub fn set_fluid_restitution(&mut self, restitution: f32) {
+        self.solver.config.fluid_restitution = restitution;
+    }
+
+    /// Cohesion within a fluid and repulsion between fluids of different rest density.
+    #[wasm_bindgen]
+    pub fn set_fluid_tension(&mut self, surface: f32, interface: f32) {
+        self.solver.config.fluid_surface_tension = surface;
+        self.solver.config.fluid_interface_tension = interface;
+    }
+
+    /// Rest density of particles [start, start + count) (0 = global fluid rest density).
+    /// Fluids with different rest densities don't mix when interface tension is on.
+    #[wasm_bindgen]
+    pub fn set_particle_rest_density(&mut self, start: u32, count: u32, density: f32) {
+        let end = ((start + count) as usize).min(self.solver.particles.count);
+        for i in (start as usize)..end {
+            self.solver.particles.rest_density[i] = density.max(0.0);
+        }
+    }
+
+    /// Cap the per-particle vorticity confinement force (prevents fluid blow-up).
+    #[wasm_bindgen]
+    pub fn set_vorticity_clamp(&mut self, max_force: f32) {
+        self.solver.config.fluid_vorticity_clamp = max_force;
+    }
+
     #[wasm_bindgen]
     pub fn set_particle_phase(&mut self, index: usize, phase: u8) {
         if index < self.solver.particles.count {
-            let p = match phase {
-                1 => xpbd_core::particle::Phase::Fluid,
-                2 => xpbd_core::particle::Phase::Cloth,
-                3 => xpbd_core::particle::Phase::Rigid,
-                4 => xpbd_core::particle::Phase::Granular,
-                5 => xpbd_core::particle::Phase::Gas,
-                6 => xpbd_core::particle::Phase::Static,
-                _ => xpbd_core::particle::Phase::Free,
-            };
+            let p = phase_from_u8(phase);
             self.solver.particles.phase[index] = p;
             self.solver.particles.inv_mass[index] = if p == xpbd_core::particle::Phase::Static { 0.0 } else { 1.0 };
         }
     }
 
+    /// Brush a phase (same codes as `set_particle_phase`) onto particles within `radius`.
+    /// With `group_rigid`, painted Rigid particles also form one rigid body. Returns the painted count.
+    #[wasm_bindgen]
+    pub fn paint_phase(&mut self, x: f32, y: f32, z: f32, radius: f32, phase: u8, group_rigid: bool) -> u32 {
+        let center = glam::Vec3::new(x, y, z);
+        self.solver.paint_phase(center, radius, phase_from_u8(phase), group_rigid) as u32
+    }
+
     #[wasm_bindgen]
     pub fn set_nbody_config(
         &mut self,
@@ -261,6 +795,59 @@ impl PhysicsWorld {
         self.solver.config.em_magnetic_field = glam::Vec3::new(magnetic_bx, magnetic_by, magnetic_bz);
     }
 
+    /// Toggle distance-dependent softening `softening * (1 + k / r)` for N-body and EM forces.
+    #[wasm_bindgen]
+    pub fn set_adaptive_softening(&mut self, enabled: bool, k: f32) {
+        self.solver.config.adaptive_softening = enabled;
+        self.solver.config.adaptive_softening_k = k;
+    }
+
+    #[wasm_bindgen]
+    pub fn set_central_attractor_enabled(&mut self, enabled: bool) {
+        self.solver.config.central_attractor_enabled = enabled;
+    }
+
+    #[wasm_bindgen]
+    pub fn set_central_attractor_pos(&mut self, x: f32, y: f32, z: f32) {
+        self.solver.config.central_attractor_pos = glam::Vec3::new(x, y, z);
+    }
+
+    #[wasm_bindgen]
+    pub fn set_central_attractor_mass(&mut self, mass: f32) {
+        self.solver.config.central_attractor_mass = mass;
+    }
+
+    /// Toggle the pull toward the beam segment.
+    #[wasm_bindgen]
+    pub fn set_beam_enabled(&mut self, enabled: bool) {
+        self.solver.config.beam_enabled = enabled;
+    }
+
+    /// Beam segment from (sx, sy, sz) to (ex, ey, ez).
+    #[wasm_bindgen]
+    pub fn set_beam_segment(&mut self, sx: f32, sy: f32, sz: f32, ex: f32, ey: f32, ez: f32) {
+        self.solver.config.beam_start = glam::Vec3::new(sx, sy, sz);
+        self.solver.config.beam_end = glam::Vec3::new(ex, ey, ez);
+    }
+
+    /// Spring constant of the beam pull toward its closest point.
+    #[wasm_bindgen]
+    pub fn set_beam_strength(&mut self, strength: f32) {
+        self.solver.config.beam_strength = strength;
+    }
+
+    /// Radius around the pointer inside which its force fades to zero (0 = off).
+    #[wasm_bindgen]
+    pub fn set_pointer_min_distance(&mut self, distance: f32) {
+        self.solver.config.pointer_min_distance = distance.max(0.0);
+    }
+
+    /// Derive particle mass from radius as `density * 4/3 pi r^3` (disabling restores earlier masses).
+    #[wasm_bindgen]
+    pub fn couple_mass_to_radius(&mut self, enabled: bool, density: f32) {
+        self.solver.couple_mass_to_radius(enabled, density);
+    }
+
     #[wasm_bindgen]
     pub fn set_particle_charge(&mut self, index: usize, charge: f32) {
         if index < self.solver.particles.count {
@@ -268,6 +855,142 @@ impl PhysicsWorld {
         }
     }
 
+    /// Random charges uniform in [-magnitude, magnitude], reproducible per seed.
+    #[wasm_bindgen]
+    pub fn randomize_charges(&mut self, magnitude: f32, seed: u32) {
+        self.solver.randomize_charges(magnitude, seed as u64);
+    }
+
+    /// Brake: scale every particle's velocity by `factor` (0..1, 0 = full stop).
+    #[wasm_bindgen]
+    pub fn damp_all_velocities(&mut self, factor: f32) {
+        self.solver.damp_all_velocities(factor);
+        self.write_gpu_output();
+    }
+
+    /// Set all velocities from interleaved xyz; returns false (no change) unless the length is count*3.
+    #[wasm_bindgen]
+    pub fn set_velocities(&mut self, vels: &[f32]) -> bool {
+        let ok = self.solver.set_velocities(vels);
+        if ok {
+            self.write_gpu_output();
+        }
+        ok
+    }
+
+    /// Add an external force to one particle, accumulated until the next step.
+    #[wasm_bindgen]
+    pub fn add_external_force(&mut self, index: usize, fx: f32, fy: f32, fz: f32) {
+        self.solver.add_external_force(index, glam::Vec3::new(fx, fy, fz));
+    }
+
+    /// Recompute per-particle SPH pressure from density at the current positions.
+    #[wasm_bindgen]
+    pub fn compute_pressure(&mut self, stiffness: f32) {
+        self.solver.compute_pressure(stiffness);
+    }
+
+    /// Pointer to the per-particle pressure buffer (`particle_count` f32 values).
+    #[wasm_bindgen]
+    pub fn get_pressure_ptr(&self) -> *const f32 {
+        self.solver.particles.pressure.as_ptr()
+    }
+
+    /// Pointer to the per-particle SPH density from the last fluid solve (`particle_count` f32 values).
+    #[wasm_bindgen]
+    pub fn get_density_ptr(&self) -> *const f32 {
+        self.solver.particles.density.as_ptr()
+    }
+
+    /// Poly6 fluid field at a point (smoothing radius `h`), for isosurface sampling.
+    #[wasm_bindgen]
+    pub fn sample_field(&mut self, x: f32, y: f32, z: f32, h: f32) -> f32 {
+        self.solver.sample_field(glam::Vec3::new(x, y, z), h)
+    }
+
+    /// `sample_field` at each [x, y, z] point of `points` (one grid rebuild for the batch).
+    #[wasm_bindgen]
+    pub fn sample_fields(&mut self, points: &[f32], h: f32) -> Vec<f32> {
+        let points: Vec<glam::Vec3> = points.chunks_exact(3).map(glam::Vec3::from_slice).collect();
+        self.solver.sample_fields(&points, h)
+    }
+
+    /// Largest fluid density overshoot over rest density from the last step (0.05 = 5% compressed).
+    #[wasm_bindgen]
+    pub fn max_density_overshoot(&self) -> f32 {
+        self.solver.max_density_overshoot()
+    }
+
+    /// Recompute per-particle SPH velocity divergence (incompressibility diagnostic).
+    #[wasm_bindgen]
+    pub fn compute_velocity_divergence(&mut self) {
+        self.solver.compute_velocity_divergence();
+    }
+
+    /// Pointer to the per-particle divergence buffer (`particle_count` f32 values).
+    #[wasm_bindgen]
+    pub fn get_divergence_ptr(&self) -> *const f32 {
+        self.solver.particles.divergence.as_ptr()
+    }
+
+    /// Pointer to the per-particle fluid vorticity (curl of velocity) from the last
+    /// confinement pass (`particle_count * 3` f32 values; zero for non-fluid particles).
+    #[wasm_bindgen]
+    pub fn get_vorticity_buffer_ptr(&self) -> *const f32 {
+        self.solver.particles.vorticity.as_ptr() as *const f32
+    }
+
+    /// Pointer to the per-particle acceleration from the last force pass (`particle_count * 3` f32 values).
+    #[wasm_bindgen]
+    pub fn get_acceleration_ptr(&self) -> *const f32 {
+        self.solver.particles.acceleration.as_ptr() as *const f32
+    }
+
+    /// Recompute the per-particle 0..1 heat value from speed and fluid density.
+    #[wasm_bindgen]
+    pub fn compute_heat(&mut self, speed_weight: f32, density_weight: f32) {
+        self.solver.compute_heat(speed_weight, density_weight);
+    }
+
+    /// Pointer to the per-particle heat buffer (`particle_count` f32 values).
+    #[wasm_bindgen]
+    pub fn get_heat_ptr(&self) -> *const f32 {
+        self.solver.particles.heat.as_ptr()
+    }
+
+    /// Barnes-Hut octree cells flattened as [min_x, min_y, min_z, max_x, max_y, max_z, mass] per cell.
+    #[wasm_bindgen]
+    pub fn get_octree_cells(&self) -> Vec<f32> {
+        let cells = self.solver.debug_octree_cells();
+        let mut out = Vec::with_capacity(cells.len() * 7);
+        for (min, max, mass) in cells {
+            out.extend_from_slice(&[min.x, min.y, min.z, max.x, max.y, max.z, mass]);
+        }
+        out
+    }
+
+    /// Set one particle's temperature (used by melt/freeze phase transitions).
+    #[wasm_bindgen]
+    pub fn set_particle_temperature(&mut self, index: usize, temperature: f32) {
+        if index < self.solver.particles.count {
+            self.solver.particles.temperature[index] = temperature;
+        }
+    }
+
+    /// Configure melt/freeze thresholds; `to_static` freezes into Static instead of Rigid.
+    #[wasm_bindgen]
+    pub fn set_phase_transition_config(&mut self, melt: f32, freeze: f32, to_static: bool) {
+        self.solver.config.melt_temperature = melt;
+        self.solver.config.freeze_temperature = freeze;
+        self.solver.config.freeze_to_static = to_static;
+    }
+
+    /// Apply melt/freeze transitions from the current particle temperatures.
+    #[wasm_bindgen]
+    pub fn update_phase_transitions(&mut self) {
+        self.solver.update_phase_transitions();
+    }
+
     /// Set all particles to a given phase at once (for bulk mode changes).
     #[wasm_bindgen]
     pub fn set_all_particles_phase(&mut self, phase: u8) {
@@ -299,6 +1022,34 @@ impl PhysicsWorld {
         self.write_gpu_output();
     }
 
+    /// True when every dynamic particle is slower than `velocity_threshold`.
+    #[wasm_bindgen]
+    pub fn is_settled(&self, velocity_threshold: f32) -> bool {
+        self.solver.is_settled(velocity_threshold)
+    }
+
+    /// Consecutive steps that ended below the settle threshold (0 while moving).
+    #[wasm_bindgen]
+    pub fn get_settled_frames(&self) -> u32 {
+        self.solver.settled_frames()
+    }
+
+    #[wasm_bindgen]
+    pub fn set_settle_threshold(&mut self, velocity_threshold: f32) {
+        self.solver.config.settle_velocity_threshold = velocity_threshold;
+    }
+
+    /// Integration path the next step takes: 0 = Euler, 1 = XPBD (`collisions_enabled`).
+    #[wasm_bindgen]
+    pub fn get_integration_path(&self) -> u32 {
+        match self.solver.active_integration_path() {
+            IntegrationPath::Euler => 0,
+            IntegrationPath::Xpbd => 1,
+        }
+    }
+
+    /// Collision settings; `boundary_stiffness` is the inverse compliance of the
+    /// boundary constraint (`Infinity` = rigid wall).
     #[wasm_bindgen]
     pub fn set_collision_config(&mut self, enabled: bool, friction: f32, restitution: f32, boundary_stiffness: f32) {
         self.solver.config.collisions_enabled = enabled;
@@ -307,6 +1058,125 @@ impl PhysicsWorld {
         self.solver.config.boundary_stiffness = boundary_stiffness;
     }
 
+    /// Steps over which velocities cross-fade after toggling collisions switches paths (0 = abrupt).
+    #[wasm_bindgen]
+    pub fn set_path_blend_frames(&mut self, frames: u32) {
+        self.solver.config.path_blend_frames = frames;
+    }
+
+    /// Scale contact corrections (1.0 = full resolution, <1 damps jitter in stacks).
+    #[wasm_bindgen]
+    pub fn set_contact_relaxation(&mut self, relaxation: f32) {
+        self.solver.config.contact_relaxation = relaxation;
+    }
+
+    /// Cap the penetration depth each contact resolves per iteration (0 = no cap) for soft collisions.
+    #[wasm_bindgen]
+    pub fn set_max_penetration_correction(&mut self, max_correction: f32) {
+        self.solver.config.max_penetration_correction = max_correction;
+    }
+
+    /// End each substep's solver iterations once corrections fall below `epsilon` (0 = off).
+    #[wasm_bindgen]
+    pub fn set_convergence_epsilon(&mut self, epsilon: f32) {
+        self.solver.config.convergence_epsilon = epsilon.max(0.0);
+    }
+
+    /// Friction and restitution material multipliers for particles [start, start + count).
+    #[wasm_bindgen]
+    pub fn set_particle_material(&mut self, start: u32, count: u32, friction: f32, restitution: f32) {
+        let end = ((start + count) as usize).min(self.solver.particles.count);
+        for i in (start as usize)..end {
+            self.solver.particles.friction[i] = friction.max(0.0);
+            self.solver.particles.restitution[i] = restitution.max(0.0);
+        }
+    }
+
+    /// How contact pairs combine material values: 0 = average, 1 = min, 2 = geometric mean.
+    #[wasm_bindgen]
+    pub fn set_material_combine(&mut self, mode: u8) {
+        self.solver.config.material_combine = match mode {
+            1 => xpbd_core::config::MaterialCombine::Min,
+            2 => xpbd_core::config::MaterialCombine::GeometricMean,
+            _ => xpbd_core::config::MaterialCombine::Average,
+        };
+    }
+
+    /// Collide rope bonds as capsules so ropes and hair don't pass through each other.
+    #[wasm_bindgen]
+    pub fn set_capsule_contacts(&mut self, enabled: bool) {
+        self.solver.config.capsule_contacts = enabled;
+    }
+
+    /// Friction for Granular contacts and the slip speed below which they stick (sand repose).
+    #[wasm_bindgen]
+    pub fn set_granular_friction(&mut self, friction: f32, static_velocity: f32) {
+        self.solver.config.granular_friction = friction.max(0.0);
+        self.solver.config.granular_static_velocity = static_velocity.max(0.0);
+    }
+
+    /// Form shapes via the XPBD target constraint only (no spring force) when collisions are on.
+    #[wasm_bindgen]
+    pub fn set_shape_as_constraint(&mut self, enabled: bool) {
+        self.solver.config.shape_as_constraint = enabled;
+    }
+
+    /// Resize the spatial hash table (e.g. larger for dense scenes to cut bucket collisions).
+    #[wasm_bindgen]
+    pub fn set_grid_table_size(&mut self, size: u32) {
+        self.solver.set_grid_table_size(size as usize);
+    }
+
+    /// Rebuild the spatial grid every XPBD substep (true) or once per frame (false, cheaper).
+    #[wasm_bindgen]
+    pub fn set_grid_rebuild_every_substep(&mut self, enabled: bool) {
+        self.solver.config.grid_rebuild_every_substep = enabled;
+    }
+
+    /// Cap contacts per particle per substep (0 = unlimited); the deepest are kept.
+    #[wasm_bindgen]
+    pub fn set_max_contacts_per_particle(&mut self, max: u32) {
+        self.solver.config.max_contacts_per_particle = max as usize;
+    }
+
+    /// Gap within which fast pairs are swept-tested for speculative contacts (0 = off).
+    #[wasm_bindgen]
+    pub fn set_speculative_margin(&mut self, margin: f32) {
+        self.solver.config.speculative_margin = margin.max(0.0);
+    }
+
+    /// Spring coefficient pulling particles back inside the spherical boundary.
+    #[wasm_bindgen]
+    pub fn set_boundary_return_stiffness(&mut self, stiffness: f32) {
+        self.solver.config.boundary_return_stiffness = stiffness;
+    }
+
+    /// Hard-clamp particles to the boundary sphere when collisions are off (Euler path).
+    #[wasm_bindgen]
+    pub fn set_euler_boundary_clamp(&mut self, enabled: bool) {
+        self.solver.config.euler_boundary_clamp = enabled;
+    }
+
+    /// Cap how far any particle may move in one step (0 = unlimited).
+    #[wasm_bindgen]
+    pub fn set_max_particle_displacement(&mut self, distance: f32) {
+        self.solver.config.max_particle_displacement = distance.max(0.0);
+    }
+
+    /// Switch between the spherical boundary and a periodic box with the given half extents
+    /// (clamped to at least `MIN_PERIODIC_HALF_EXTENT`).
+    #[wasm_bindgen]
+    pub fn set_boundary_shape(&mut self, periodic: bool, half_x: f32, half_y: f32, half_z: f32) {
+        let min = glam::Vec3::splat(xpbd_core::config::MIN_PERIODIC_HALF_EXTENT);
+        self.solver.config.boundary_shape = if periodic {
+            xpbd_core::config::BoundaryShape::Periodic {
+                half_extents: glam::Vec3::new(half_x, half_y, half_z).max(min),
+            }
+        } else {
+            xpbd_core::config::BoundaryShape::Sphere
+        };
+    }
+
     #[wasm_bindgen]
     pub fn set_particle_mass(&mut self, start: u32, count: u32, mass: f32) {
         let inv_m = if mass <= 0.0 { 0.0 } else { 1.0 / mass };
@@ -316,14 +1186,48 @@ impl PhysicsWorld {
         }
     }
 
+    /// Set the radius of particles `start..start + count` (a single particle with `count = 1`).
+    /// With mass coupling on, their masses follow the new radius.
     #[wasm_bindgen]
     pub fn set_particle_radius(&mut self, start: u32, count: u32, radius: f32) {
         let end = ((start + count) as usize).min(self.solver.particles.count);
         for i in (start as usize)..end {
-            self.solver.particles.radius[i] = radius;
+            self.solver.set_particle_radius(i, radius);
         }
     }
 
+    /// Bounding sphere of the particles as [center_x, center_y, center_z, radius].
+    #[wasm_bindgen]
+    pub fn get_bounding_sphere(&self) -> Vec<f32> {
+        let (center, radius) = self.solver.bounding_sphere();
+        vec![center.x, center.y, center.z, radius]
+    }
+
+    /// Kernel-averaged particle velocity within `radius` of a point, as [x, y, z].
+    #[wasm_bindgen]
+    pub fn sample_velocity(&mut self, x: f32, y: f32, z: f32, radius: f32) -> Vec<f32> {
+        self.solver.sample_velocity(glam::Vec3::new(x, y, z), radius).to_array().to_vec()
+    }
+
+    /// `sample_velocity` at each [x, y, z] point of `points`, as flat [x, y, z] triples.
+    #[wasm_bindgen]
+    pub fn sample_velocities(&mut self, points: &[f32], radius: f32) -> Vec<f32> {
+        let points: Vec<glam::Vec3> = points.chunks_exact(3).map(glam::Vec3::from_slice).collect();
+        self.solver.sample_velocities(&points, radius).iter().flat_map(|v| v.to_array()).collect()
+    }
+
+    /// Sample the flow field at a point as [x, y, z].
+    #[wasm_bindgen]
+    pub fn sample_flow(&self, x: f32, y: f32, z: f32, time: f32) -> Vec<f32> {
+        self.solver.sample_flow(glam::Vec3::new(x, y, z), time).to_array().to_vec()
+    }
+
+    /// Sample the pointer force at a point as [x, y, z].
+    #[wasm_bindgen]
+    pub fn sample_pointer_force(&self, x: f32, y: f32, z: f32, time: f32) -> Vec<f32> {
+        self.solver.sample_pointer_force(glam::Vec3::new(x, y, z), time).to_array().to_vec()
+    }
+
     #[wasm_bindgen]
     pub fn write_webgl_buffers(&self, pos_out: &js_sys::Float32Array, vel_out: &js_sys::Float32Array) {
         for i in 0..self.solver.particles.count {
@@ -345,15 +1249,56 @@ impl PhysicsWorld {
     pub fn get_perf_stats(&self) -> String {
         let s = &self.solver.last_stats;
         format!(
-            r#"{{"step_ms":{:.2},"substeps":{},"iterations":{},"particle_count":{},"contact_count":{}}}"#,
-            s.total_ms, s.substeps, s.iterations, s.particle_count, s.contact_count
+            r#"{{"step_ms":{:.2},"wrapper_ms":{:.2},"substeps":{},"iterations":{},"particle_count":{},"contact_count":{}}}"#,
+            s.total_ms, self.wrapper_ms, s.substeps, s.iterations, s.particle_count, s.contact_count
         )
     }
 }
 
+/// Decode the frontend's phase code (0 = Free .. 6 = Static; unknown codes are Free).
+fn phase_from_u8(phase: u8) -> xpbd_core::particle::Phase {
+    match phase {
+        1 => xpbd_core::particle::Phase::Fluid,
+        2 => xpbd_core::particle::Phase::Cloth,
+        3 => xpbd_core::particle::Phase::Rigid,
+        4 => xpbd_core::particle::Phase::Granular,
+        5 => xpbd_core::particle::Phase::Gas,
+        6 => xpbd_core::particle::Phase::Static,
+        _ => xpbd_core::particle::Phase::Free,
+    }
+}
+
 impl PhysicsWorld {
+    /// Refresh the interpolation snapshot (if enabled) before the solver steps.
+    fn capture_interp_prev(&mut self) {
+        if self.interp_prev.is_some() {
+            self.interp_prev = Some(self.solver.snapshot());
+        }
+    }
+
+    /// Log a console warning the first time the solver switches integration paths.
+    fn warn_on_path_switch(&mut self) {
+        if self.solver.take_integration_path_switch() && !self.path_switch_warned {
+            self.path_switch_warned = true;
+            web_sys::console::warn_1(
+                &"XPBD/Euler integration path switched mid-run; velocities are not re-predicted".into()
+            );
+        }
+    }
+
+    /// Zero the radius and velocity of killed slots, which `write_gpu_output` skips.
+    fn hide_dead_slots(&mut self) {
+        for (gpu, &alive) in self.gpu_buffer.iter_mut().zip(&self.solver.particles.alive) {
+            if !alive {
+                gpu.radius = 0.0;
+                gpu.velocity = [0.0; 3];
+            }
+        }
+    }
+
     fn write_gpu_output(&mut self) {
-        for i in 0..self.solver.particles.count {
+        for &idx in self.solver.active_indices() {
+            let i = idx as usize;
             let pos = self.solver.particles.position[i];
             let vel = self.solver.particles.velocity[i];
             self.gpu_buffer[i] = GpuParticle {
@@ -365,3 +1310,29 @@ impl PhysicsWorld {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A world built without the browser-only constructor calls.
+    fn native_world(count: usize) -> PhysicsWorld {
+        PhysicsWorld {
+            solver: Solver::new(count),
+            gpu_buffer: vec![bytemuck::Zeroable::zeroed(); count],
+            path_switch_warned: false,
+            interp_prev: None,
+            wrapper_ms: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_randomize_radii_reaches_gpu_buffer() {
+        let mut world = native_world(16);
+        world.randomize_radii(3, 0.02, 0.2);
+        for (gpu, &radius) in world.gpu_buffer.iter().zip(&world.solver.particles.radius) {
+            assert_eq!(gpu.radius, radius);
+            assert!((0.02..=0.2).contains(&gpu.radius));
+        }
+    }
+}